@@ -0,0 +1,4 @@
+DROP INDEX sessions_join_code;
+
+ALTER TABLE sessions DROP COLUMN organiser_key;
+ALTER TABLE sessions DROP COLUMN join_code;
@@ -0,0 +1,4 @@
+ALTER TABLE sessions ADD COLUMN join_code TEXT;
+ALTER TABLE sessions ADD COLUMN organiser_key TEXT;
+
+CREATE UNIQUE INDEX sessions_join_code ON sessions (join_code);
@@ -0,0 +1,8 @@
+CREATE TABLE runs (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+    data TEXT NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX runs_session_id ON runs (session_id);
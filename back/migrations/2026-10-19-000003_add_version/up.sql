@@ -0,0 +1,2 @@
+-- Bumped by every change of a session, and sent as its ETag
+ALTER TABLE sessions ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
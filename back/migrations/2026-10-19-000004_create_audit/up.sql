@@ -0,0 +1,9 @@
+-- Every change of a session, kept for as long as the session
+CREATE TABLE audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    session_id TEXT NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+    data TEXT NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX audit_session_id ON audit (session_id);
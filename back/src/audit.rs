@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use serde::Deserialize;
+use solver::{Actor, AuditEdit, AuditEntry, Change, Role};
+use uuid::Uuid;
+
+use crate::db::now;
+use crate::error::AppError;
+use crate::identity::OrganiserKey;
+use crate::models::AuditRow;
+use crate::schema::audit;
+use crate::sessions::find;
+use crate::state::AppState;
+
+/// Record a change of a session in its audit log, with the values it edited.
+pub(crate) fn record(
+    conn: &mut SqliteConnection,
+    uuid: Uuid,
+    actor: Actor,
+    change: &Change,
+    edits: Vec<AuditEdit>,
+) -> Result<(), AppError> {
+    let entry = AuditEntry {
+        at: now(),
+        actor,
+        change: change.clone(),
+        edits,
+    };
+    diesel::insert_into(audit::table)
+        .values(AuditRow::new(uuid, &entry)?)
+        .execute(conn)?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AuditParams {
+    /// Name of a user to keep the changes about, or made by, only.
+    user: Option<String>,
+    /// Name of a team to keep the changes about only.
+    team: Option<String>,
+}
+
+/// `GET /api/sessions/{uuid}/audit`: the changes of a session, most recent first,
+/// only for organisers.
+///
+/// Users and teams are filtered by the names they had when the changes were made.
+pub(crate) async fn list(
+    State(state): State<AppState>,
+    OrganiserKey(organiser_key): OrganiserKey,
+    Path(uuid): Path<Uuid>,
+    Query(params): Query<AuditParams>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    let session = find(&state, uuid)?;
+    if session.role(organiser_key) != Role::Organiser {
+        return Err(AppError::Forbidden);
+    }
+    let rows: Vec<AuditRow> = audit::table
+        .filter(audit::session_id.eq(&session.id))
+        .order(audit::id.desc())
+        .select(AuditRow::as_select())
+        .load(&mut *state.conn()?)?;
+    let user = params.user.as_deref().filter(|user| !user.is_empty());
+    let team = params.team.as_deref().filter(|team| !team.is_empty());
+    let mut entries = Vec::new();
+    for row in &rows {
+        entries.extend(row.entry()?.filtered(user, team));
+    }
+    Ok(Json(entries))
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use axum::http::HeaderValue;
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
+use tracing_subscriber::EnvFilter;
+
+/// File read when `--config` is not given, if it exists.
+const DEFAULT_CONFIG: &str = "teams.toml";
+
+/// Settings of the server: the defaults, overridden by a TOML file, then by
+/// `TEAMS_*` environment variables, then by command line flags.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    pub bind: SocketAddr,
+    /// Path of the SQLite database.
+    pub database_url: String,
+    /// Days after their last edit when sessions are deleted, never if unset.
+    pub session_ttl_days: Option<u64>,
+    /// Other origins allowed to call the API from a browser, none if empty.
+    pub cors_origins: Vec<String>,
+    /// Directives of the log filter, e.g. `info` or `back=debug,tower_http=info`.
+    pub log_level: String,
+    pub log_format: LogFormat,
+    /// Directory of the frontend built with `dx bundle`, served along the API.
+    pub static_dir: Option<PathBuf>,
+    pub limits: Limits,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            database_url: "teams.db".to_string(),
+            session_ttl_days: None,
+            cors_origins: Vec::new(),
+            log_level: "info".to_string(),
+            log_format: LogFormat::default(),
+            static_dir: None,
+            limits: Limits::default(),
+        }
+    }
+}
+
+/// Bounds on what callers may send, to keep requests cheap to serve.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Limits {
+    /// Largest request body, in bytes.
+    pub body_bytes: usize,
+    /// Most users in a session.
+    pub users: usize,
+    /// Most teams in a session.
+    pub teams: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            body_bytes: 2 * 1024 * 1024,
+            users: 1000,
+            teams: 200,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// Shorter human-readable lines.
+    Compact,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+/// Command line flags, each also read from an environment variable.
+#[derive(Debug, Parser)]
+#[command(version, about = "Backend of Smart Teams Ordering")]
+struct Args {
+    /// TOML file to read the settings from [default: teams.toml, if it exists]
+    #[arg(long, env = "TEAMS_CONFIG")]
+    config: Option<PathBuf>,
+    /// Address to listen on [default: 0.0.0.0:3000]
+    #[arg(long, env = "TEAMS_BIND")]
+    bind: Option<SocketAddr>,
+    /// Path of the SQLite database [default: teams.db]
+    #[arg(long, env = "TEAMS_DATABASE_URL")]
+    database_url: Option<String>,
+    /// Days after their last edit when sessions are deleted [default: never]
+    #[arg(long, env = "TEAMS_SESSION_TTL_DAYS")]
+    session_ttl_days: Option<u64>,
+    /// Comma-separated other origins allowed to call the API [default: none]
+    #[arg(long, env = "TEAMS_CORS_ORIGINS", value_delimiter = ',')]
+    cors_origins: Option<Vec<String>>,
+    /// Directives of the log filter [default: info]
+    #[arg(long, env = "TEAMS_LOG_LEVEL")]
+    log_level: Option<String>,
+    /// Format of the logs [default: text]
+    #[arg(long, env = "TEAMS_LOG_FORMAT")]
+    log_format: Option<LogFormat>,
+    /// Directory of the frontend built with `dx bundle` [default: API only]
+    #[arg(long, env = "TEAMS_STATIC_DIR")]
+    static_dir: Option<PathBuf>,
+    /// Largest request body, in bytes [default: 2097152]
+    #[arg(long, env = "TEAMS_BODY_LIMIT")]
+    body_limit: Option<usize>,
+    /// Most users in a session [default: 1000]
+    #[arg(long, env = "TEAMS_MAX_USERS")]
+    max_users: Option<usize>,
+    /// Most teams in a session [default: 200]
+    #[arg(long, env = "TEAMS_MAX_TEAMS")]
+    max_teams: Option<usize>,
+}
+
+/// Why the settings could not be loaded.
+#[derive(Debug)]
+pub(crate) enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// A setting has a value the server cannot run with.
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => write!(f, "cannot read {}: {err}", path.display()),
+            ConfigError::Parse(path, err) => write!(f, "invalid settings in {}: {err}", path.display()),
+            ConfigError::Invalid(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Config {
+    /// Load the settings from the file, the environment and the command line,
+    /// exiting with the usage on invalid flags.
+    pub fn load() -> Result<Self, ConfigError> {
+        let args = Args::parse();
+        let mut config = match &args.config {
+            Some(path) => Self::read(path)?,
+            None if Path::new(DEFAULT_CONFIG).exists() => Self::read(Path::new(DEFAULT_CONFIG))?,
+            None => Self::default(),
+        };
+        config.apply(args);
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn read(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|err| ConfigError::Read(path.to_path_buf(), err))?;
+        toml::from_str(&text).map_err(|err| ConfigError::Parse(path.to_path_buf(), err))
+    }
+
+    fn apply(&mut self, args: Args) {
+        if let Some(bind) = args.bind {
+            self.bind = bind;
+        }
+        if let Some(database_url) = args.database_url {
+            self.database_url = database_url;
+        }
+        if let Some(days) = args.session_ttl_days {
+            self.session_ttl_days = Some(days);
+        }
+        if let Some(origins) = args.cors_origins {
+            self.cors_origins = origins.into_iter().filter(|origin| !origin.trim().is_empty()).collect();
+        }
+        if let Some(log_level) = args.log_level {
+            self.log_level = log_level;
+        }
+        if let Some(log_format) = args.log_format {
+            self.log_format = log_format;
+        }
+        if let Some(static_dir) = args.static_dir {
+            self.static_dir = Some(static_dir);
+        }
+        if let Some(body_bytes) = args.body_limit {
+            self.limits.body_bytes = body_bytes;
+        }
+        if let Some(users) = args.max_users {
+            self.limits.users = users;
+        }
+        if let Some(teams) = args.max_teams {
+            self.limits.teams = teams;
+        }
+    }
+
+    /// Check every setting, so that the server does not fail once started.
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |message: String| Err(ConfigError::Invalid(message));
+        if self.database_url.trim().is_empty() {
+            return invalid("the database URL is empty".to_string());
+        }
+        if self.session_ttl_days == Some(0) {
+            return invalid("the session TTL must be at least 1 day".to_string());
+        }
+        for origin in &self.cors_origins {
+            let scheme = origin.starts_with("http://") || origin.starts_with("https://");
+            if !scheme || origin.ends_with('/') || HeaderValue::from_str(origin).is_err() {
+                return invalid(format!(
+                    "invalid CORS origin {origin:?}, expected e.g. https://teams.example.org"
+                ));
+            }
+        }
+        if let Err(err) = EnvFilter::try_new(&self.log_level) {
+            return invalid(format!("invalid log level {:?}: {err}", self.log_level));
+        }
+        if let Some(dir) = self.static_dir.as_ref().filter(|dir| !dir.join("index.html").is_file()) {
+            return invalid(format!("{} has no index.html, build the frontend with `dx bundle`", dir.display()));
+        }
+        if self.limits.body_bytes < 1024 {
+            return invalid("the body limit must be at least 1024 bytes".to_string());
+        }
+        if self.limits.users == 0 || self.limits.teams == 0 {
+            return invalid("sessions must be allowed at least 1 user and 1 team".to_string());
+        }
+        Ok(())
+    }
+
+    /// Origins allowed by CORS, checked by [`Config::validate`].
+    pub fn origins(&self) -> Vec<HeaderValue> {
+        self.cors_origins
+            .iter()
+            .filter_map(|origin| HeaderValue::from_str(origin).ok())
+            .collect()
+    }
+}
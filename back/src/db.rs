@@ -0,0 +1,23 @@
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+use crate::error::StartupError;
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+/// Open the SQLite database and bring its schema up to date.
+pub(crate) fn connect(database_url: &str) -> Result<SqliteConnection, StartupError> {
+    let mut conn = SqliteConnection::establish(database_url)
+        .map_err(|err| StartupError::Database(format!("cannot open database {database_url}: {err}")))?;
+    conn.run_pending_migrations(MIGRATIONS)
+        .map_err(|err| StartupError::Database(format!("cannot migrate database {database_url}: {err}")))?;
+    Ok(conn)
+}
+
+/// Current time in milliseconds since the Unix epoch.
+pub(crate) fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::net::SocketAddr;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use solver::SessionResource;
+
+use crate::versions::Versioned;
+
+/// Errors returned by the API handlers.
+#[derive(Debug)]
+pub(crate) enum AppError {
+    NotFound,
+    Forbidden,
+    /// The session changed since the version the write was made against, as it is now.
+    Conflict(Box<SessionResource>),
+    /// The ID sent for a new resource is already used by another.
+    Taken,
+    /// The session cannot be processed, e.g. sorted, as it is.
+    Invalid(String),
+    Database(diesel::result::Error),
+    Corrupted(serde_json::Error),
+    Poisoned,
+}
+
+impl From<diesel::result::Error> for AppError {
+    fn from(err: diesel::result::Error) -> Self {
+        match err {
+            diesel::result::Error::NotFound => AppError::NotFound,
+            err => AppError::Database(err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::Corrupted(err)
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            AppError::NotFound => (StatusCode::NOT_FOUND, "Not found".to_string()),
+            AppError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden".to_string()),
+            AppError::Conflict(current) => return (StatusCode::CONFLICT, Versioned(*current)).into_response(),
+            AppError::Taken => (StatusCode::CONFLICT, "Already taken".to_string()),
+            AppError::Invalid(message) => (StatusCode::UNPROCESSABLE_ENTITY, message),
+            AppError::Database(err) => {
+                tracing::error!("database error: {err}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Database error".to_string())
+            }
+            AppError::Corrupted(err) => {
+                tracing::error!("corrupted session data: {err}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Corrupted session data".to_string())
+            }
+            AppError::Poisoned => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database lock poisoned".to_string(),
+            ),
+        };
+        (status, message).into_response()
+    }
+}
+
+/// Errors stopping the server from starting or serving.
+#[derive(Debug)]
+pub(crate) enum StartupError {
+    /// The database cannot be opened or migrated.
+    Database(String),
+    Bind(SocketAddr, std::io::Error),
+    Serve(std::io::Error),
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartupError::Database(message) => f.write_str(message),
+            StartupError::Bind(addr, err) => write!(f, "cannot listen on {addr}: {err}"),
+            StartupError::Serve(err) => write!(f, "server error: {err}"),
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderName, HeaderValue},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use futures_util::stream::{self, StreamExt};
+use serde::Deserialize;
+use solver::{Change, SessionEvent};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::db::now;
+use crate::error::AppError;
+use crate::sessions::find;
+use crate::state::AppState;
+
+/// Events kept per session, for clients resuming after a disconnection.
+const HISTORY: usize = 100;
+
+/// Events buffered per subscriber, a slower one being told to reload.
+const BUFFER: usize = 64;
+
+/// How long the feed of a session nobody follows is kept after its latest event,
+/// in milliseconds, for clients resuming after a disconnection.
+const RESUME_WINDOW: i64 = 10 * 60 * 1000;
+
+/// The changes of the sessions, published by the handlers as they happen.
+///
+/// Event IDs grow across all the sessions and across restarts, being seeded
+/// with the time the server started. Only the latest events of each session
+/// are kept, in memory, and only for the [`RESUME_WINDOW`] once nobody follows
+/// it: clients resuming from an older event, or from before a restart, are sent
+/// a `reset` event telling them to reload everything.
+pub(crate) struct Feeds {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    next_id: u64,
+    feeds: HashMap<Uuid, Feed>,
+}
+
+impl Inner {
+    /// The feed of a session, created if there is none. A new feed knows none of
+    /// the events before it, which may have been evicted with a previous feed.
+    fn feed(&mut self, uuid: Uuid) -> &mut Feed {
+        let time = now();
+        self.feeds.retain(|_, feed| !feed.is_idle(time));
+        let latest = self.next_id.saturating_sub(1);
+        self.feeds.entry(uuid).or_insert_with(|| Feed::new(latest))
+    }
+}
+
+struct Feed {
+    sender: broadcast::Sender<Published>,
+    history: VecDeque<Published>,
+    /// Latest event which is no longer in the history.
+    dropped_up_to: u64,
+}
+
+#[derive(Clone)]
+struct Published {
+    id: u64,
+    event: SessionEvent,
+}
+
+/// What a subscriber is sent.
+enum Message {
+    Published(Published),
+    /// Events were missed, with the ID to resume from if known.
+    Reset(Option<u64>),
+}
+
+impl Message {
+    fn event(&self) -> Result<Event, axum::Error> {
+        match self {
+            Message::Published(Published { id, event }) => Event::default()
+                .id(id.to_string())
+                .event(event.change.name())
+                .json_data(event),
+            Message::Reset(id) => {
+                let event = Event::default().event("reset").data("{}");
+                Ok(match id {
+                    Some(id) => event.id(id.to_string()),
+                    None => event,
+                })
+            }
+        }
+    }
+}
+
+impl Feeds {
+    pub fn new() -> Self {
+        let start = u64::try_from(now()).unwrap_or_default();
+        Self {
+            inner: Mutex::new(Inner {
+                next_id: start,
+                feeds: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Send a change of a session to its subscribers, made by `client` if known.
+    pub fn publish(&self, uuid: Uuid, change: Change, client: Option<Uuid>) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let id = inner.next_id;
+        let is_final = change.is_final();
+        let published = Published {
+            id,
+            event: SessionEvent {
+                at: now(),
+                client,
+                change,
+            },
+        };
+        let feed = inner.feed(uuid);
+        feed.history.push_back(published.clone());
+        if feed.history.len() > HISTORY
+            && let Some(dropped) = feed.history.pop_front()
+        {
+            feed.dropped_up_to = dropped.id;
+        }
+        // There is no error but the lack of subscribers
+        let _ = feed.sender.send(published);
+        inner.next_id += 1;
+        if is_final {
+            // Subscribers get the buffered events, then the end of the stream
+            inner.feeds.remove(&uuid);
+        }
+    }
+
+    /// The events after `last` and the receiver of the next ones, taken at once so
+    /// that none is missed or sent twice.
+    fn subscribe(&self, uuid: Uuid, last: Option<u64>) -> Result<(Vec<Message>, broadcast::Receiver<Published>), AppError> {
+        let mut inner = self.inner.lock().map_err(|_| AppError::Poisoned)?;
+        let latest = inner.next_id.checked_sub(1);
+        let feed = inner.feed(uuid);
+        let mut backlog = Vec::new();
+        if let Some(last) = last {
+            if last < feed.dropped_up_to {
+                backlog.push(Message::Reset(latest));
+            } else {
+                backlog.extend(
+                    feed.history
+                        .iter()
+                        .filter(|published| published.id > last)
+                        .cloned()
+                        .map(Message::Published),
+                );
+            }
+        }
+        Ok((backlog, feed.sender.subscribe()))
+    }
+}
+
+impl Feed {
+    fn new(dropped_up_to: u64) -> Self {
+        Self {
+            sender: broadcast::channel(BUFFER).0,
+            history: VecDeque::new(),
+            dropped_up_to,
+        }
+    }
+
+    /// Whether nobody follows the feed, nor may resume it any more.
+    fn is_idle(&self, time: i64) -> bool {
+        self.sender.receiver_count() == 0
+            && self.history.back().is_none_or(|published| published.event.at < time - RESUME_WINDOW)
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct StreamParams {
+    /// Event to resume after, for clients which cannot send `Last-Event-ID`.
+    last_event_id: Option<u64>,
+}
+
+/// `GET /api/sessions/{uuid}/events`: the changes of a session as server-sent events,
+/// resuming after the `Last-Event-ID` header or the `last_event_id` parameter.
+///
+/// Events only tell what changed and which client changed it, never the
+/// preferences, so anyone who can load the session may follow it.
+pub(crate) async fn stream(
+    State(state): State<AppState>,
+    Path(uuid): Path<Uuid>,
+    Query(params): Query<StreamParams>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    find(&state, uuid)?;
+    let last = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .or(params.last_event_id);
+    let (backlog, receiver) = state.feeds().subscribe(uuid, last)?;
+    let live = stream::unfold(receiver, |mut receiver| async move {
+        let message = match receiver.recv().await {
+            Ok(published) => Message::Published(published),
+            Err(RecvError::Lagged(_)) => Message::Reset(None),
+            Err(RecvError::Closed) => return None,
+        };
+        Some((message, receiver))
+    });
+    let events = stream::iter(backlog).chain(live).map(|message| message.event());
+    // Proxies like nginx would otherwise hold the events back
+    let headers = [(HeaderName::from_static("x-accel-buffering"), HeaderValue::from_static("no"))];
+    Ok((headers, Sse::new(events).keep_alive(KeepAlive::default())))
+}
@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use axum::{
+    extract::Request,
+    handler::HandlerWithoutStateExt,
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use tower::ServiceExt;
+use tower_http::services::{ServeDir, ServeFile};
+
+use crate::state::AppState;
+
+/// Marker Dioxus puts in the names of the assets it hashes, e.g. `main-dxh2f1e.css`.
+const HASHED: &str = "-dxh";
+
+/// Serve the frontend built with `dx bundle` from `dir`, falling back on its
+/// `index.html` for the routes of the application, e.g. `/teams/{uuid}`.
+///
+/// Precompressed `.br` and `.gz` files are served when the bundle has them.
+pub(crate) fn router(dir: &Path) -> Router<AppState> {
+    let index = ServeFile::new(dir.join("index.html"))
+        .precompressed_br()
+        .precompressed_gzip();
+    let app = move |request: Request| async move {
+        // Routes of the application have no extension, unlike missing files
+        if Path::new(request.uri().path()).extension().is_some() {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        match index.oneshot(request).await {
+            Ok(response) => response.into_response(),
+            Err(never) => match never {},
+        }
+    };
+    let files = ServeDir::new(dir)
+        .precompressed_br()
+        .precompressed_gzip()
+        .fallback(app.into_service());
+    Router::new()
+        .fallback_service(files)
+        .layer(middleware::from_fn(cache_control))
+}
+
+/// Hashed assets never change, so browsers may keep them for good; anything else,
+/// starting with `index.html`, is checked again on each use.
+async fn cache_control(request: Request, next: Next) -> Response {
+    let hashed = request
+        .uri()
+        .path()
+        .rsplit('/')
+        .next()
+        .is_some_and(|name| name.contains(HASHED));
+    let mut response = next.run(request).await;
+    let value = if hashed && response.status().is_success() {
+        "public, max-age=31536000, immutable"
+    } else {
+        "no-cache"
+    };
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static(value));
+    response
+}
@@ -0,0 +1,57 @@
+use axum::{extract::FromRequestParts, http::request::Parts};
+use uuid::Uuid;
+
+/// Header carrying the account key of a signed-in user.
+pub(crate) const IDENTITY_HEADER: &str = "x-identity";
+/// Header carrying the organiser key of a session.
+pub(crate) const ORGANISER_KEY_HEADER: &str = "x-organiser-key";
+/// Header carrying the random key of the browser tab making a request.
+pub(crate) const CLIENT_HEADER: &str = "x-client-id";
+
+/// The account key of the caller, if they are signed in.
+///
+/// There are no passwords: the key is a random UUID generated by the frontend,
+/// which the user can copy to sign in from another browser.
+pub(crate) struct Identity(pub Option<Uuid>);
+
+/// The organiser key presented by the caller, if any.
+///
+/// The key is generated by the browser creating the session and shared
+/// through the organiser link.
+pub(crate) struct OrganiserKey(pub Option<Uuid>);
+
+/// The key of the browser tab making the request, if any, passed on in the events
+/// of its changes so that the tab can ignore them.
+pub(crate) struct ClientId(pub Option<Uuid>);
+
+fn header_uuid(parts: &Parts, name: &str) -> Option<Uuid> {
+    parts
+        .headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| Uuid::parse_str(value).ok())
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Identity {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Identity(header_uuid(parts, IDENTITY_HEADER)))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for OrganiserKey {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(OrganiserKey(header_uuid(parts, ORGANISER_KEY_HEADER)))
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientId {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ClientId(header_uuid(parts, CLIENT_HEADER)))
+    }
+}
@@ -1,57 +1,112 @@
+mod audit;
+mod config;
+mod db;
+mod error;
+mod events;
+mod frontend;
+mod identity;
+mod models;
+mod runs;
+mod schema;
+mod sessions;
+mod share;
+mod state;
+mod versions;
+
+use std::process::ExitCode;
+
 use axum::{
-    routing::{get, post},
-    http::StatusCode,
-    Json, Router,
+    extract::DefaultBodyLimit,
+    routing::{any, get, post},
+    Router,
 };
-use serde::{Deserialize, Serialize};
+use tower::util::option_layer;
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing_subscriber::EnvFilter;
+
+use crate::config::{Config, LogFormat};
+use crate::error::{AppError, StartupError};
+use crate::state::AppState;
 
 #[tokio::main]
-async fn main() {
-    // initialize tracing
-    tracing_subscriber::fmt::init();
-
-    // build our application with a route
-    let app = Router::new()
-        // `GET /` goes to `root`
-        .route("/", get(root))
-        // `POST /users` goes to `create_user`
-        .route("/users", post(create_user));
-
-    // run our app with hyper, listening globally on port 3000
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+async fn main() -> ExitCode {
+    // the logs are set up from the settings, so their errors are printed as is
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    init_tracing(&config);
+    match serve(config).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            tracing::error!("{err}");
+            ExitCode::FAILURE
+        }
+    }
 }
 
-// basic handler that responds with a static string
-async fn root() -> &'static str {
-    "Hello, World!"
+fn init_tracing(config: &Config) {
+    let subscriber = tracing_subscriber::fmt().with_env_filter(EnvFilter::new(&config.log_level));
+    match config.log_format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Compact => subscriber.compact().init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
 }
 
-async fn create_user(
-    // this argument tells axum to parse the request body
-    // as JSON into a `CreateUser` type
-    Json(payload): Json<CreateUser>,
-) -> (StatusCode, Json<User>) {
-    // insert your application logic here
-    let user = User {
-        id: 1337,
-        username: payload.username,
-    };
+async fn serve(config: Config) -> Result<(), StartupError> {
+    let state = AppState::new(db::connect(&config.database_url)?, config.limits);
+    if let Some(ttl_days) = config.session_ttl_days {
+        tokio::spawn(sessions::expire_periodically(state.clone(), ttl_days));
+    }
 
-    // this will be converted into a JSON response
-    // with a status code of `201 Created`
-    (StatusCode::CREATED, Json(user))
-}
+    // Same origin only, unless other origins are listed, e.g. that of `dx serve`
+    // during development
+    let cors = (!config.cors_origins.is_empty()).then(|| {
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(config.origins()))
+            .allow_methods(Any)
+            .allow_headers(Any)
+            .expose_headers(Any)
+    });
 
-// the input to our `create_user` handler
-#[derive(Deserialize)]
-struct CreateUser {
-    username: String,
-}
+    // build our application with the API routes
+    let mut app = Router::new()
+        .route("/api/sessions", get(sessions::list))
+        .route(
+            "/api/sessions/{uuid}",
+            get(sessions::get)
+                .put(sessions::put)
+                .patch(sessions::patch)
+                .delete(sessions::delete),
+        )
+        .route("/api/sessions/{uuid}/events", get(events::stream))
+        .route("/api/sessions/{uuid}/audit", get(audit::list))
+        .route("/api/sessions/{uuid}/duplicate", post(sessions::duplicate))
+        .route(
+            "/api/sessions/{uuid}/runs",
+            get(runs::list).post(runs::create),
+        )
+        .route("/api/sessions/{uuid}/runs/{run}", get(runs::published))
+        .route("/api/sessions/{uuid}/runs/{run}/withdraw", post(runs::withdraw))
+        .route("/api/sessions/{uuid}/solve", post(runs::solve))
+        .route("/api/join/{code}", get(share::join))
+        .route("/api/{*path}", any(|| async { AppError::NotFound }))
+        .layer(DefaultBodyLimit::max(config.limits.body_bytes))
+        .layer(option_layer(cors));
+    // and the frontend on the same origin, when it was built
+    if let Some(dir) = &config.static_dir {
+        app = app.merge(frontend::router(dir));
+    }
+    let app = app.layer(CompressionLayer::new()).with_state(state);
 
-// the output to our `create_user` handler
-#[derive(Serialize)]
-struct User {
-    id: u64,
-    username: String,
-}
\ No newline at end of file
+    let listener = tokio::net::TcpListener::bind(config.bind)
+        .await
+        .map_err(|err| StartupError::Bind(config.bind, err))?;
+    tracing::info!("listening on {}", config.bind);
+    axum::serve(listener, app).await.map_err(StartupError::Serve)
+}
@@ -0,0 +1,142 @@
+use diesel::prelude::*;
+use solver::{aggregate, redact, AuditEntry, Role, Run, Session, SessionResource, SessionSummary};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::schema::{audit, runs, sessions};
+
+/// A session as stored in the database, its content being serialized as JSON.
+#[derive(Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = sessions)]
+pub(crate) struct SessionRow {
+    pub id: String,
+    pub name: String,
+    pub owner: Option<String>,
+    pub data: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub join_code: Option<String>,
+    pub organiser_key: Option<String>,
+    pub version: i64,
+}
+
+impl SessionRow {
+    pub fn new(
+        id: Uuid,
+        owner: Option<Uuid>,
+        organiser_key: Option<Uuid>,
+        session: &Session,
+        now: i64,
+    ) -> Result<Self, AppError> {
+        Ok(Self {
+            id: id.to_string(),
+            name: session.name.clone(),
+            owner: owner.map(|owner| owner.to_string()),
+            data: serde_json::to_string(session)?,
+            created_at: now,
+            updated_at: now,
+            join_code: None,
+            organiser_key: organiser_key.map(|key| key.to_string()),
+            version: 1,
+        })
+    }
+
+    pub fn uuid(&self) -> Result<Uuid, AppError> {
+        Uuid::parse_str(&self.id).map_err(|_| AppError::NotFound)
+    }
+
+    pub fn session(&self) -> Result<Session, AppError> {
+        Ok(serde_json::from_str(&self.data)?)
+    }
+
+    pub fn summary(&self) -> Result<SessionSummary, AppError> {
+        Ok(SessionSummary::new(self.uuid()?, &self.session()?, self.updated_at))
+    }
+
+    /// The session as the caller may see it, see [`solver::redact`].
+    pub fn resource(
+        &self,
+        organiser_key: Option<Uuid>,
+        identity: Option<Uuid>,
+    ) -> Result<SessionResource, AppError> {
+        let session = self.session()?;
+        let role = self.role(organiser_key);
+        let aggregates = (session.settings.private_preferences && role == Role::Organiser)
+            .then(|| aggregate(&session));
+        Ok(SessionResource {
+            id: self.uuid()?,
+            session: redact(&session, role, identity),
+            role,
+            join_code: self.join_code.clone().unwrap_or_default(),
+            aggregates,
+            version: self.version,
+        })
+    }
+
+    /// Whether `identity` may delete this session. Sessions created anonymously
+    /// cannot be deleted from the API.
+    pub fn is_owned_by(&self, identity: Option<Uuid>) -> bool {
+        match (&self.owner, identity) {
+            (Some(owner), Some(identity)) => *owner == identity.to_string(),
+            _ => false,
+        }
+    }
+
+    /// Role of a caller presenting `organiser_key`. Sessions saved before organiser
+    /// keys existed have none, and nobody organises them: they can only be duplicated.
+    pub fn role(&self, organiser_key: Option<Uuid>) -> Role {
+        match (&self.organiser_key, organiser_key) {
+            (Some(expected), Some(key)) if *expected == key.to_string() => Role::Organiser,
+            _ => Role::Participant,
+        }
+    }
+}
+
+/// A run of a session as stored in the database, serialized as JSON.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = runs)]
+pub(crate) struct RunRow {
+    pub id: String,
+    pub session_id: String,
+    pub data: String,
+    pub created_at: i64,
+}
+
+impl RunRow {
+    pub fn new(session_id: Uuid, run: &Run) -> Result<Self, AppError> {
+        Ok(Self {
+            id: run.id.to_string(),
+            session_id: session_id.to_string(),
+            data: serde_json::to_string(run)?,
+            created_at: run.created_at,
+        })
+    }
+
+    pub fn run(&self) -> Result<Run, AppError> {
+        Ok(serde_json::from_str(&self.data)?)
+    }
+}
+
+/// An entry of the audit log of a session, serialized as JSON. Entries are
+/// numbered by the database, in the order they were recorded.
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = audit)]
+pub(crate) struct AuditRow {
+    pub session_id: String,
+    pub data: String,
+    pub created_at: i64,
+}
+
+impl AuditRow {
+    pub fn new(session_id: Uuid, entry: &AuditEntry) -> Result<Self, AppError> {
+        Ok(Self {
+            session_id: session_id.to_string(),
+            data: serde_json::to_string(entry)?,
+            created_at: entry.at,
+        })
+    }
+
+    pub fn entry(&self) -> Result<AuditEntry, AppError> {
+        Ok(serde_json::from_str(&self.data)?)
+    }
+}
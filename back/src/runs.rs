@@ -0,0 +1,285 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use serde::Deserialize;
+use solver::{evaluate, redact_run, rematch, validate, Actor, Change, PublishedRun, Role, Run, MINIMAL_DISRUPTION};
+use uuid::Uuid;
+
+use crate::audit::record;
+use crate::db::now;
+use crate::error::AppError;
+use crate::identity::{ClientId, Identity, OrganiserKey};
+use crate::models::RunRow;
+use crate::schema::runs;
+use crate::sessions::find;
+use crate::state::AppState;
+
+/// `GET /api/sessions/{uuid}/runs`: the runs of a session, most recent first, as
+/// the caller may see them (see [`solver::redact_run`]).
+pub(crate) async fn list(
+    State(state): State<AppState>,
+    OrganiserKey(organiser_key): OrganiserKey,
+    Path(uuid): Path<Uuid>,
+) -> Result<Json<Vec<Run>>, AppError> {
+    let row = find(&state, uuid)?;
+    let session = row.session()?;
+    let role = row.role(organiser_key);
+    let rows: Vec<RunRow> = runs::table
+        .filter(runs::session_id.eq(&row.id))
+        .order(runs::created_at.desc())
+        .select(RunRow::as_select())
+        .load(&mut *state.conn()?)?;
+    let runs = rows
+        .iter()
+        .map(|row| Ok(redact_run(&row.run()?, &session, role)))
+        .collect::<Result<_, AppError>>()?;
+    Ok(Json(runs))
+}
+
+/// `POST /api/sessions/{uuid}/runs`: record a run, only for organisers.
+///
+/// Runs are immutable, so posting the same run again returns the recorded one
+/// with `200 OK`. Its creation time, metrics and input hash are set from the
+/// session stored, rather than trusted, and the run it derives from must be
+/// one of the session.
+pub(crate) async fn create(
+    State(state): State<AppState>,
+    OrganiserKey(organiser_key): OrganiserKey,
+    ClientId(client): ClientId,
+    Path(uuid): Path<Uuid>,
+    Json(mut run): Json<Run>,
+) -> Result<(StatusCode, Json<Run>), AppError> {
+    let row = find(&state, uuid)?;
+    if row.role(organiser_key) != Role::Organiser {
+        return Err(AppError::Forbidden);
+    }
+    let session = row.session()?;
+    if !run.assignment.fits(session.users.len(), session.teams.len()) {
+        return Err(AppError::Invalid(
+            "The run refers to users or teams the session does not have.".to_string(),
+        ));
+    }
+    run.created_at = now();
+    run.metrics = evaluate(&session, &run.assignment);
+    run.input_hash = session.input_hash();
+    let change = recorded(&run);
+    let new_row = RunRow::new(uuid, &run)?;
+    let known = state.conn()?.transaction(|conn| {
+        let existing: Option<RunRow> = runs::table
+            .find(&new_row.id)
+            .select(RunRow::as_select())
+            .first(conn)
+            .optional()?;
+        match existing {
+            Some(existing) if existing.session_id == row.id => return Ok(Some(existing.run()?)),
+            Some(_) => return Err(AppError::Taken),
+            None => {}
+        }
+        if let Some(previous) = run.previous {
+            find_run(conn, &row.id, previous).map_err(|err| match err {
+                AppError::NotFound => {
+                    AppError::Invalid("The run derives from a run the session does not have.".to_string())
+                }
+                err => err,
+            })?;
+        }
+        diesel::insert_into(runs::table).values(new_row).execute(conn)?;
+        record(conn, uuid, organiser(), &change, Vec::new())?;
+        Ok::<_, AppError>(None)
+    })?;
+    if let Some(known) = known {
+        return Ok((StatusCode::OK, Json(redact_run(&known, &session, Role::Organiser))));
+    }
+    state.feeds().publish(uuid, change, client);
+    Ok((StatusCode::CREATED, Json(redact_run(&run, &session, Role::Organiser))))
+}
+
+/// Runs are recorded by organisers, who need not claim a user.
+fn organiser() -> Actor {
+    Actor {
+        role: Role::Organiser,
+        user: None,
+    }
+}
+
+fn recorded(run: &Run) -> Change {
+    Change::RunRecorded {
+        run: run.id,
+        solver: run.solver.clone(),
+    }
+}
+
+/// A run of a session, and the runs derived from it by withdrawals, oldest first.
+fn with_derived(conn: &mut SqliteConnection, session_id: &str, run: Uuid) -> Result<(Run, Vec<Run>), AppError> {
+    let rows: Vec<RunRow> = runs::table
+        .filter(runs::session_id.eq(session_id))
+        .order(runs::created_at.asc())
+        .select(RunRow::as_select())
+        .load(conn)?;
+    let runs: Vec<Run> = rows.iter().map(RunRow::run).collect::<Result<_, _>>()?;
+    let published = runs.iter().find(|other| other.id == run).cloned().ok_or(AppError::NotFound)?;
+    let mut derived: Vec<Run> = Vec::new();
+    let mut current = run;
+    while let Some(next) = runs.iter().find(|other| other.previous == Some(current)) {
+        current = next.id;
+        derived.push(next.clone());
+    }
+    Ok((published, derived))
+}
+
+/// `GET /api/sessions/{uuid}/runs/{run}`: a run as shown to participants, by names only.
+///
+/// Withdrawals since the run was published are applied, and listed.
+pub(crate) async fn published(
+    State(state): State<AppState>,
+    Identity(identity): Identity,
+    Path((uuid, run)): Path<(Uuid, Uuid)>,
+) -> Result<Json<PublishedRun>, AppError> {
+    let row = find(&state, uuid)?;
+    let session = row.session()?;
+    let (published, derived) = with_derived(&mut *state.conn()?, &row.id, run)?;
+    let latest = derived.last().unwrap_or(&published).assignment_in(&session);
+    let own = identity
+        .and_then(|identity| session.member_of(identity))
+        .filter(|&user| latest.team_of(user).is_some() || latest.unassigned.contains(&user));
+    Ok(Json(PublishedRun {
+        own,
+        ..PublishedRun::new(&session, &published, &derived)
+    }))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct WithdrawBody {
+    user: usize,
+}
+
+/// `POST /api/sessions/{uuid}/runs/{run}/withdraw`: take a user out of a published
+/// run, for the organiser or the participant who claimed the user.
+///
+/// The withdrawal applies to the latest run derived from `run`, and is recorded
+/// as a new run promoting the users on the waitlists. It is computed without
+/// holding the connection, then recorded only if no other withdrawal was recorded
+/// meanwhile, being computed again otherwise.
+pub(crate) async fn withdraw(
+    State(state): State<AppState>,
+    Identity(identity): Identity,
+    OrganiserKey(organiser_key): OrganiserKey,
+    ClientId(client): ClientId,
+    Path((uuid, run)): Path<(Uuid, Uuid)>,
+    Json(body): Json<WithdrawBody>,
+) -> Result<(StatusCode, Json<Run>), AppError> {
+    let row = find(&state, uuid)?;
+    let session = row.session()?;
+    let claimed = identity.is_some_and(|identity| session.member_of(identity) == Some(body.user));
+    if row.role(organiser_key) != Role::Organiser && !claimed {
+        return Err(AppError::Forbidden);
+    }
+    loop {
+        let (published, derived) = with_derived(&mut *state.conn()?, &row.id, run)?;
+        let latest = derived.last().unwrap_or(&published);
+        let assignment = latest.assignment_in(&session);
+        if assignment.team_of(body.user).is_none() && !assignment.unassigned.contains(&body.user) {
+            return Err(AppError::Invalid("This user already withdrew.".to_string()));
+        }
+        let next = latest.withdraw(Uuid::new_v4(), now(), &session, body.user);
+        // Keep the connection until the new run is recorded, so that two withdrawals
+        // cannot derive from the same run
+        let mut conn = state.conn()?;
+        let (_, current) = with_derived(&mut conn, &row.id, run)?;
+        if current.last().map(|run| run.id) != derived.last().map(|run| run.id) {
+            continue;
+        }
+        let user = session.users.get(body.user).cloned().unwrap_or_default();
+        let change = Change::Withdrawn { run: next.id, user };
+        let actor = Actor::new(&session, row.role(organiser_key), identity);
+        conn.transaction(|conn| {
+            diesel::insert_into(runs::table).values(RunRow::new(uuid, &next)?).execute(conn)?;
+            record(conn, uuid, actor, &change, Vec::new())
+        })?;
+        state.feeds().publish(uuid, change, client);
+        return Ok((StatusCode::CREATED, Json(redact_run(&next, &session, row.role(organiser_key)))));
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SolveParams {
+    /// Seed of a previous run to reproduce, a new one being drawn otherwise.
+    seed: Option<u64>,
+    /// Previous run to re-match from, moving as few users as possible.
+    from: Option<Uuid>,
+    /// Previous run to sort again as it was, with its seed and the settings it
+    /// was sorted with.
+    run: Option<Uuid>,
+}
+
+/// A run of a session.
+fn find_run(conn: &mut SqliteConnection, session_id: &str, run: Uuid) -> Result<Run, AppError> {
+    let row: RunRow = runs::table
+        .find(run.to_string())
+        .filter(runs::session_id.eq(session_id))
+        .select(RunRow::as_select())
+        .first(conn)?;
+    row.run()
+}
+
+/// `POST /api/sessions/{uuid}/solve`: sort the teams on the backend and record the run,
+/// re-match them from a previous run with `from`, or sort them again as a previous
+/// run did with `run`.
+///
+/// The organiser of a private session cannot see the preferences, so cannot sort
+/// the teams in the browser.
+pub(crate) async fn solve(
+    State(state): State<AppState>,
+    OrganiserKey(organiser_key): OrganiserKey,
+    ClientId(client): ClientId,
+    Path(uuid): Path<Uuid>,
+    Query(params): Query<SolveParams>,
+) -> Result<(StatusCode, Json<Run>), AppError> {
+    let row = find(&state, uuid)?;
+    if row.role(organiser_key) != Role::Organiser {
+        return Err(AppError::Forbidden);
+    }
+    let mut session = row.session()?;
+    let mut seed = params.seed;
+    if let Some(run) = params.run {
+        let run = find_run(&mut *state.conn()?, &row.id, run)?;
+        session.settings = run.settings(&session.settings);
+        seed = run.seed.or(seed);
+    }
+    let validation = validate(&session);
+    if !validation.is_valid() {
+        let messages: Vec<String> = validation
+            .issues
+            .iter()
+            .map(|issue| issue.message(&session))
+            .collect();
+        return Err(AppError::Invalid(messages.join("\n")));
+    }
+    let seed = seed.unwrap_or_else(|| Uuid::new_v4().as_u64_pair().0);
+    let (solver, assignment) = match params.from {
+        Some(from) => {
+            let previous = find_run(&mut *state.conn()?, &row.id, from)?;
+            (MINIMAL_DISRUPTION, rematch(&session, seed, &previous))
+        }
+        None => (session.settings.solver(), solver::solve(&session, seed)),
+    };
+    let run = Run::new(
+        Uuid::new_v4(),
+        now(),
+        solver,
+        session.settings.uses_seed().then_some(seed),
+        &session,
+        assignment,
+    );
+    let change = recorded(&run);
+    state.conn()?.transaction(|conn| {
+        diesel::insert_into(runs::table).values(RunRow::new(uuid, &run)?).execute(conn)?;
+        record(conn, uuid, organiser(), &change, Vec::new())
+    })?;
+    state.feeds().publish(uuid, change, client);
+    Ok((StatusCode::CREATED, Json(redact_run(&run, &session, Role::Organiser))))
+}
@@ -0,0 +1,330 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use serde::Deserialize;
+use solver::{audit::edits, merge, ops, redact, Actor, Change, Op, Role, Session, SessionSummary};
+use uuid::Uuid;
+
+use crate::audit::record;
+use crate::config::Limits;
+use crate::db::now;
+use crate::error::AppError;
+use crate::identity::{ClientId, Identity, OrganiserKey};
+use crate::models::SessionRow;
+use crate::schema::{audit, runs, sessions};
+use crate::share::{claim_join_code, ensure_join_code, new_join_code};
+use crate::state::AppState;
+use crate::versions::{IfMatch, Versioned};
+
+/// `GET /api/sessions`: the sessions owned by the signed-in user, most recent first.
+pub(crate) async fn list(
+    State(state): State<AppState>,
+    Identity(identity): Identity,
+) -> Result<Json<Vec<SessionSummary>>, AppError> {
+    let Some(identity) = identity else {
+        return Ok(Json(Vec::new()));
+    };
+    let rows: Vec<SessionRow> = sessions::table
+        .filter(sessions::owner.eq(identity.to_string()))
+        .order(sessions::updated_at.desc())
+        .select(SessionRow::as_select())
+        .load(&mut *state.conn()?)?;
+    let summaries = rows
+        .iter()
+        .map(SessionRow::summary)
+        .collect::<Result<_, _>>()?;
+    Ok(Json(summaries))
+}
+
+/// `GET /api/sessions/{uuid}`: the session with the caller's role and its join code,
+/// its version being sent as `ETag`.
+pub(crate) async fn get(
+    State(state): State<AppState>,
+    Identity(identity): Identity,
+    OrganiserKey(organiser_key): OrganiserKey,
+    Path(uuid): Path<Uuid>,
+) -> Result<Versioned, AppError> {
+    let mut row = find(&state, uuid)?;
+    ensure_join_code(&mut *state.conn()?, &mut row)?;
+    Ok(Versioned(row.resource(organiser_key, identity)?))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PutParams {
+    /// Join code wished for a new session, e.g. one typed by a user which did not exist.
+    join_code: Option<String>,
+}
+
+/// `PUT /api/sessions/{uuid}`: create or replace a session, returning it as `GET` would.
+///
+/// New sessions are organised with the organiser key presented, which is required,
+/// and the first signed-in organiser to save an anonymous session becomes its owner.
+/// The session is merged into the stored one, so that callers cannot overwrite
+/// what was hidden from them (see [`solver::merge`]). With `If-Match`, the
+/// session is only replaced if it did not change since the given version, the
+/// current one being returned with `409 Conflict` otherwise.
+#[allow(clippy::too_many_arguments, reason = "one per extractor")]
+pub(crate) async fn put(
+    State(state): State<AppState>,
+    Identity(identity): Identity,
+    OrganiserKey(organiser_key): OrganiserKey,
+    ClientId(client): ClientId,
+    if_match: IfMatch,
+    Path(uuid): Path<Uuid>,
+    Query(params): Query<PutParams>,
+    Json(session): Json<Session>,
+) -> Result<Versioned, AppError> {
+    check_limits(state.limits(), &session)?;
+    let mut conn = state.conn()?;
+    let (mut row, change) = match sessions::table
+        .find(uuid.to_string())
+        .select(SessionRow::as_select())
+        .first(&mut *conn)
+        .optional()?
+    {
+        Some(existing) => {
+            check_version(&existing, &if_match, organiser_key, identity)?;
+            let stored = existing.session()?;
+            update(&mut conn, uuid, existing, &stored, session, identity, organiser_key)?
+        }
+        None if if_match.is_some() => return Err(AppError::NotFound),
+        None if organiser_key.is_none() => return Err(organiser_key_required()),
+        None => {
+            let row = SessionRow {
+                join_code: Some(claim_join_code(&mut conn, params.join_code.as_deref())?),
+                ..SessionRow::new(uuid, identity, organiser_key, &session, now())?
+            };
+            let actor = Actor::new(&session, row.role(organiser_key), identity);
+            conn.transaction(|conn| {
+                diesel::insert_into(sessions::table).values(&row).execute(conn)?;
+                record(conn, uuid, actor, &Change::Created, edits(&Session::default(), &session))
+            })?;
+            (row, Change::Created)
+        }
+    };
+    ensure_join_code(&mut conn, &mut row)?;
+    state.feeds().publish(uuid, change, client);
+    Ok(Versioned(row.resource(organiser_key, identity)?))
+}
+
+/// `PATCH /api/sessions/{uuid}`: apply edits to a session, returning it as `GET` would.
+///
+/// The edits are applied to the session as the caller is shown it, so that they
+/// merge with the edits made by others since the caller last loaded it (see
+/// [`solver::ops`]). Callers wishing to choose how they merge send `If-Match`,
+/// as with `PUT`.
+pub(crate) async fn patch(
+    State(state): State<AppState>,
+    Identity(identity): Identity,
+    OrganiserKey(organiser_key): OrganiserKey,
+    ClientId(client): ClientId,
+    if_match: IfMatch,
+    Path(uuid): Path<Uuid>,
+    Json(edits): Json<Vec<Op>>,
+) -> Result<Versioned, AppError> {
+    let mut conn = state.conn()?;
+    let existing: SessionRow = sessions::table
+        .find(uuid.to_string())
+        .select(SessionRow::as_select())
+        .first(&mut *conn)?;
+    check_version(&existing, &if_match, organiser_key, identity)?;
+    let stored = existing.session()?;
+    let mut session = redact(&stored, existing.role(organiser_key), identity);
+    ops::apply(&mut session, &edits);
+    check_limits(state.limits(), &session)?;
+    let (mut row, change) = update(&mut conn, uuid, existing, &stored, session, identity, organiser_key)?;
+    ensure_join_code(&mut conn, &mut row)?;
+    state.feeds().publish(uuid, change, client);
+    Ok(Versioned(row.resource(organiser_key, identity)?))
+}
+
+/// Refuse writes made against another version than the stored one, with the
+/// session as the caller may see it.
+fn check_version(
+    existing: &SessionRow,
+    if_match: &IfMatch,
+    organiser_key: Option<Uuid>,
+    identity: Option<Uuid>,
+) -> Result<(), AppError> {
+    if if_match.matches(existing.version) {
+        return Ok(());
+    }
+    Err(AppError::Conflict(Box::new(existing.resource(organiser_key, identity)?)))
+}
+
+/// Store a new state of a session, merged into the stored one as the caller's
+/// role allows, and tell how it changed, recording it in the audit log.
+fn update(
+    conn: &mut SqliteConnection,
+    uuid: Uuid,
+    existing: SessionRow,
+    stored: &Session,
+    incoming: Session,
+    identity: Option<Uuid>,
+    organiser_key: Option<Uuid>,
+) -> Result<(SessionRow, Change), AppError> {
+    let role = existing.role(organiser_key);
+    let session = merge(stored, incoming, role, identity);
+    let actor = Actor::new(&session, role, identity);
+    let change = match role {
+        Role::Organiser => Change::Edited,
+        _ => Change::Submitted {
+            user: actor.user.clone(),
+        },
+    };
+    let row = SessionRow {
+        owner: existing
+            .owner
+            .or(identity.filter(|_| role == Role::Organiser).map(|id| id.to_string())),
+        created_at: existing.created_at,
+        join_code: existing.join_code,
+        organiser_key: existing.organiser_key,
+        version: existing.version + 1,
+        ..SessionRow::new(uuid, identity, organiser_key, &session, now())?
+    };
+    let edits = edits(stored, &session);
+    conn.transaction(|conn| {
+        diesel::update(sessions::table.find(&row.id)).set(&row).execute(conn)?;
+        if !edits.is_empty() {
+            record(conn, uuid, actor, &change, edits)?;
+        }
+        Ok::<_, AppError>(())
+    })?;
+    Ok((row, change))
+}
+
+/// `DELETE /api/sessions/{uuid}`: only the owner may delete a session, and with
+/// `If-Match` only if it did not change since.
+pub(crate) async fn delete(
+    State(state): State<AppState>,
+    Identity(identity): Identity,
+    OrganiserKey(organiser_key): OrganiserKey,
+    ClientId(client): ClientId,
+    if_match: IfMatch,
+    Path(uuid): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    state.conn()?.transaction(|conn| {
+        let row: SessionRow = sessions::table
+            .find(uuid.to_string())
+            .select(SessionRow::as_select())
+            .first(conn)?;
+        if !row.is_owned_by(identity) {
+            return Err(AppError::Forbidden);
+        }
+        check_version(&row, &if_match, organiser_key, identity)?;
+        diesel::delete(runs::table.filter(runs::session_id.eq(&row.id))).execute(conn)?;
+        diesel::delete(audit::table.filter(audit::session_id.eq(&row.id))).execute(conn)?;
+        diesel::delete(sessions::table.find(&row.id)).execute(conn)?;
+        Ok(())
+    })?;
+    state.feeds().publish(uuid, Change::Deleted, client);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DuplicateBody {
+    /// Organiser key of the copy, generated by the caller.
+    organiser_key: Option<Uuid>,
+}
+
+/// `POST /api/sessions/{uuid}/duplicate`: copy a session under a new UUID,
+/// organised with the organiser key sent in the body.
+///
+/// Only what the caller may see is copied, so that nobody reads the hidden
+/// fields of a session through a copy of it.
+pub(crate) async fn duplicate(
+    State(state): State<AppState>,
+    Identity(identity): Identity,
+    OrganiserKey(organiser_key): OrganiserKey,
+    Path(uuid): Path<Uuid>,
+    Json(body): Json<DuplicateBody>,
+) -> Result<(StatusCode, Json<SessionSummary>), AppError> {
+    if body.organiser_key.is_none() {
+        return Err(organiser_key_required());
+    }
+    let existing = find(&state, uuid)?;
+    let mut session = redact(&existing.session()?, existing.role(organiser_key), identity);
+    session.name = format!("{} (copy)", session.name);
+    // Participants claimed users of the original session only
+    session.members.clear();
+    let mut conn = state.conn()?;
+    let uuid = Uuid::new_v4();
+    let row = SessionRow {
+        join_code: Some(new_join_code(&mut conn)?),
+        ..SessionRow::new(uuid, identity, body.organiser_key, &session, now())?
+    };
+    let actor = Actor::new(&session, Role::Organiser, identity);
+    conn.transaction(|conn| {
+        diesel::insert_into(sessions::table).values(&row).execute(conn)?;
+        record(conn, uuid, actor, &Change::Created, edits(&Session::default(), &session))
+    })?;
+    Ok((StatusCode::CREATED, Json(row.summary()?)))
+}
+
+/// Sessions are created with the organiser key of their organiser, so that no
+/// caller can take over a session without one.
+fn organiser_key_required() -> AppError {
+    AppError::Invalid("An organiser key is required to create a session.".to_string())
+}
+
+pub(crate) fn find(state: &AppState, uuid: Uuid) -> Result<SessionRow, AppError> {
+    Ok(sessions::table
+        .find(uuid.to_string())
+        .select(SessionRow::as_select())
+        .first(&mut *state.conn()?)?)
+}
+
+/// Refuse sessions larger than the server allows.
+fn check_limits(limits: Limits, session: &Session) -> Result<(), AppError> {
+    if session.users.len() > limits.users {
+        return Err(AppError::Invalid(format!("Sessions are limited to {} users", limits.users)));
+    }
+    if session.teams.len() > limits.teams {
+        return Err(AppError::Invalid(format!("Sessions are limited to {} teams", limits.teams)));
+    }
+    Ok(())
+}
+
+/// Delete the sessions not edited for `ttl_days`, with their runs and audit logs,
+/// returning their IDs.
+pub(crate) fn expire(conn: &mut SqliteConnection, ttl_days: u64) -> QueryResult<Vec<String>> {
+    const DAY: i64 = 24 * 60 * 60 * 1000;
+    let cutoff = now().saturating_sub(i64::try_from(ttl_days).unwrap_or(i64::MAX).saturating_mul(DAY));
+    conn.transaction(|conn| {
+        let expired: Vec<String> = sessions::table
+            .filter(sessions::updated_at.lt(cutoff))
+            .select(sessions::id)
+            .load(conn)?;
+        diesel::delete(runs::table.filter(runs::session_id.eq_any(&expired))).execute(conn)?;
+        diesel::delete(audit::table.filter(audit::session_id.eq_any(&expired))).execute(conn)?;
+        diesel::delete(sessions::table.filter(sessions::id.eq_any(&expired))).execute(conn)?;
+        Ok(expired)
+    })
+}
+
+/// Delete the expired sessions every hour, for as long as the server runs.
+pub(crate) async fn expire_periodically(state: AppState, ttl_days: u64) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+    loop {
+        interval.tick().await;
+        let expired = state
+            .conn()
+            .and_then(|mut conn| Ok(expire(&mut conn, ttl_days)?));
+        match expired {
+            Ok(ids) if ids.is_empty() => {}
+            Ok(ids) => {
+                tracing::info!("deleted {} expired session(s)", ids.len());
+                for uuid in ids.iter().filter_map(|id| Uuid::parse_str(id).ok()) {
+                    state.feeds().publish(uuid, Change::Expired, None);
+                }
+            }
+            Err(err) => tracing::error!("cannot delete expired sessions: {err:?}"),
+        }
+    }
+}
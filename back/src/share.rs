@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use solver::SessionSummary;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::SessionRow;
+use crate::schema::sessions;
+use crate::state::AppState;
+
+/// Characters of the join codes, without the easily confused 0/O and 1/I.
+const ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
+
+/// `GET /api/join/{code}`: the session a join code refers to.
+pub(crate) async fn join(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+) -> Result<Json<SessionSummary>, AppError> {
+    let row: SessionRow = sessions::table
+        .filter(sessions::join_code.eq(normalize(&code)))
+        .select(SessionRow::as_select())
+        .first(&mut *state.conn()?)?;
+    Ok(Json(row.summary()?))
+}
+
+/// Give a join code to a session which has none yet.
+pub(crate) fn ensure_join_code(conn: &mut SqliteConnection, row: &mut SessionRow) -> Result<(), AppError> {
+    if row.join_code.is_none() {
+        let code = new_join_code(conn)?;
+        diesel::update(sessions::table.find(&row.id))
+            .set(sessions::join_code.eq(&code))
+            .execute(conn)?;
+        row.join_code = Some(code);
+    }
+    Ok(())
+}
+
+/// A join code which is not used by any session.
+pub(crate) fn new_join_code(conn: &mut SqliteConnection) -> Result<String, AppError> {
+    loop {
+        // The first bytes of a v4 UUID are random
+        let code: String = Uuid::new_v4().as_bytes()[..CODE_LENGTH]
+            .iter()
+            .map(|byte| ALPHABET[*byte as usize % ALPHABET.len()] as char)
+            .collect();
+        let taken = sessions::table
+            .filter(sessions::join_code.eq(&code))
+            .count()
+            .get_result::<i64>(conn)?;
+        if taken == 0 {
+            return Ok(code);
+        }
+    }
+}
+
+/// The wished join code if it is well-formed and free, a new one otherwise.
+pub(crate) fn claim_join_code(conn: &mut SqliteConnection, wished: Option<&str>) -> Result<String, AppError> {
+    if let Some(code) = wished.map(normalize).filter(|code| is_well_formed(code)) {
+        let taken = sessions::table
+            .filter(sessions::join_code.eq(&code))
+            .count()
+            .get_result::<i64>(conn)?;
+        if taken == 0 {
+            return Ok(code);
+        }
+    }
+    new_join_code(conn)
+}
+
+fn is_well_formed(code: &str) -> bool {
+    code.len() == CODE_LENGTH && code.bytes().all(|byte| ALPHABET.contains(&byte))
+}
+
+/// Codes are case-insensitive and may be typed with spaces or dashes.
+fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
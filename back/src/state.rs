@@ -0,0 +1,39 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use diesel::sqlite::SqliteConnection;
+
+use crate::config::Limits;
+use crate::error::AppError;
+use crate::events::Feeds;
+
+/// State shared by all the handlers.
+#[derive(Clone)]
+pub(crate) struct AppState {
+    db: Arc<Mutex<SqliteConnection>>,
+    limits: Limits,
+    feeds: Arc<Feeds>,
+}
+
+impl AppState {
+    pub fn new(conn: SqliteConnection, limits: Limits) -> Self {
+        Self {
+            db: Arc::new(Mutex::new(conn)),
+            limits,
+            feeds: Arc::new(Feeds::new()),
+        }
+    }
+
+    /// Lock the database connection for the duration of a request.
+    pub fn conn(&self) -> Result<MutexGuard<'_, SqliteConnection>, AppError> {
+        self.db.lock().map_err(|_| AppError::Poisoned)
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// The changes of the sessions, followed by the clients.
+    pub fn feeds(&self) -> &Feeds {
+        &self.feeds
+    }
+}
@@ -0,0 +1,56 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use solver::SessionResource;
+
+/// The versions of a session a write was made against, from the `If-Match` header.
+///
+/// Writes without the header apply to whatever version is stored.
+pub(crate) struct IfMatch(Option<String>);
+
+impl IfMatch {
+    pub fn is_some(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Whether the write may apply to `version`. Entity tags are compared
+    /// strongly, so weak ones never match.
+    pub fn matches(&self, version: i64) -> bool {
+        let Some(tags) = &self.0 else {
+            return true;
+        };
+        let current = etag(version);
+        tags.split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag == current)
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let tags = parts
+            .headers
+            .get(header::IF_MATCH)
+            .map(|value| value.to_str().unwrap_or_default().to_string());
+        Ok(IfMatch(tags))
+    }
+}
+
+/// A session sent with its version as `ETag`.
+pub(crate) struct Versioned(pub SessionResource);
+
+impl IntoResponse for Versioned {
+    fn into_response(self) -> Response {
+        let etag = HeaderValue::from_str(&etag(self.0.version)).expect("entity tags are ASCII");
+        ([(header::ETAG, etag)], Json(self.0)).into_response()
+    }
+}
+
+fn etag(version: i64) -> String {
+    format!("\"{version}\"")
+}
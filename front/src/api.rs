@@ -0,0 +1,313 @@
+//! Client for the backend API.
+//!
+//! Every call is best-effort: the app keeps working from the browser's local
+//! storage when the backend is unreachable.
+
+use std::fmt;
+use std::sync::LazyLock;
+
+use dioxus_logger::tracing::warn;
+use gloo_net::http::{Request, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use solver::{AuditEntry, Change, Op, PublishedRun, Run, Session, SessionEvent, SessionResource, SessionSummary};
+use uuid::Uuid;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{EventSource, MessageEvent};
+
+/// Base URL of the backend, overridable at build time with `API_URL`: empty when
+/// the backend serves the frontend itself.
+const API_URL: &str = match option_env!("API_URL") {
+    Some(url) => url,
+    None => "http://localhost:3000",
+};
+
+#[derive(Debug)]
+pub(crate) enum ApiError {
+    /// The backend could not be reached or answered garbage.
+    Network(gloo_net::Error),
+    /// The backend answered with an error status.
+    Status(u16),
+    /// The session changed since the version a write was made against, as it is now.
+    Conflict(Box<SessionResource>),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Network(err) => write!(f, "backend unreachable: {err}"),
+            ApiError::Status(status) => write!(f, "backend answered with status {status}"),
+            ApiError::Conflict(_) => write!(f, "the session was edited elsewhere meanwhile"),
+        }
+    }
+}
+
+impl From<gloo_net::Error> for ApiError {
+    fn from(err: gloo_net::Error) -> Self {
+        ApiError::Network(err)
+    }
+}
+
+pub(crate) type ApiResult<T> = Result<T, ApiError>;
+
+/// Random key of this tab, sent along its changes so that it can tell them from
+/// the changes made elsewhere.
+static CLIENT_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+fn url(path: &str) -> String {
+    format!("{API_URL}/api{path}")
+}
+
+/// Keys identifying the caller to the backend.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Credentials {
+    /// Account key of the signed-in user.
+    pub identity: Option<Uuid>,
+    /// Organiser key of the session the request is about.
+    pub organiser_key: Option<Uuid>,
+}
+
+fn authenticated(mut request: RequestBuilder, credentials: Credentials) -> RequestBuilder {
+    request = request.header("x-client-id", &CLIENT_ID.to_string());
+    if let Some(key) = credentials.identity {
+        request = request.header("x-identity", &key.to_string());
+    }
+    if let Some(key) = credentials.organiser_key {
+        request = request.header("x-organiser-key", &key.to_string());
+    }
+    request
+}
+
+fn check(response: Response) -> ApiResult<Response> {
+    if response.ok() {
+        Ok(response)
+    } else {
+        Err(ApiError::Status(response.status()))
+    }
+}
+
+async fn json<T: DeserializeOwned>(response: Response) -> ApiResult<T> {
+    Ok(check(response)?.json().await?)
+}
+
+/// A session written only if it is still at `version`, when given.
+fn versioned(mut request: RequestBuilder, version: Option<i64>) -> RequestBuilder {
+    if let Some(version) = version {
+        request = request.header("if-match", &format!("\"{version}\""));
+    }
+    request
+}
+
+/// A session written, or the session as it is now if it changed meanwhile.
+async fn written(response: Response) -> ApiResult<SessionResource> {
+    if response.status() == 409 {
+        return Err(ApiError::Conflict(Box::new(response.json().await?)));
+    }
+    json(response).await
+}
+
+/// `None` when the backend answered 404.
+async fn optional<T: DeserializeOwned>(response: Response) -> ApiResult<Option<T>> {
+    if response.status() == 404 {
+        return Ok(None);
+    }
+    json(response).await.map(Some)
+}
+
+/// Sessions owned by a signed-in user.
+pub(crate) async fn list_sessions(identity: Uuid) -> ApiResult<Vec<SessionSummary>> {
+    let credentials = Credentials {
+        identity: Some(identity),
+        ..Credentials::default()
+    };
+    json(authenticated(Request::get(&url("/sessions")), credentials).send().await?).await
+}
+
+/// A session, or `None` if the backend does not know it.
+pub(crate) async fn get_session(uuid: Uuid, credentials: Credentials) -> ApiResult<Option<SessionResource>> {
+    let request = authenticated(Request::get(&url(&format!("/sessions/{uuid}"))), credentials);
+    optional(request.send().await?).await
+}
+
+/// Create or replace a session, only if it is still at `version` when given.
+pub(crate) async fn put_session(
+    uuid: Uuid,
+    session: &Session,
+    version: Option<i64>,
+    credentials: Credentials,
+) -> ApiResult<SessionResource> {
+    let request = authenticated(Request::put(&url(&format!("/sessions/{uuid}"))), credentials);
+    written(versioned(request, version).json(session)?.send().await?).await
+}
+
+/// Apply edits made to `version` of a session on the backend, failing with
+/// [`ApiError::Conflict`] if it was edited from elsewhere since.
+pub(crate) async fn edit_session(
+    uuid: Uuid,
+    edits: &[Op],
+    version: Option<i64>,
+    credentials: Credentials,
+) -> ApiResult<SessionResource> {
+    let request = authenticated(Request::patch(&url(&format!("/sessions/{uuid}"))), credentials);
+    written(versioned(request, version).json(edits)?.send().await?).await
+}
+
+/// Create a session on the backend, asking for a specific join code.
+pub(crate) async fn create_session(
+    uuid: Uuid,
+    session: &Session,
+    join_code: &str,
+    credentials: Credentials,
+) -> ApiResult<SessionResource> {
+    let request = authenticated(Request::put(&url(&format!("/sessions/{uuid}"))), credentials)
+        .query([("join_code", join_code)]);
+    json(request.json(session)?.send().await?).await
+}
+
+pub(crate) async fn delete_session(uuid: Uuid, credentials: Credentials) -> ApiResult<()> {
+    let request = authenticated(Request::delete(&url(&format!("/sessions/{uuid}"))), credentials);
+    check(request.send().await?).map(|_| ())
+}
+
+#[derive(Serialize)]
+struct DuplicateBody {
+    organiser_key: Uuid,
+}
+
+/// Copy a session on the backend, the copy being organised with `organiser_key`.
+pub(crate) async fn duplicate_session(
+    uuid: Uuid,
+    organiser_key: Uuid,
+    credentials: Credentials,
+) -> ApiResult<SessionSummary> {
+    let request = authenticated(Request::post(&url(&format!("/sessions/{uuid}/duplicate"))), credentials);
+    json(request.json(&DuplicateBody { organiser_key })?.send().await?).await
+}
+
+/// Changes of a session, most recent first, about or made by the user and about
+/// the team named, if any. Only organisers may read them.
+pub(crate) async fn get_audit(
+    uuid: Uuid,
+    user: Option<&str>,
+    team: Option<&str>,
+    credentials: Credentials,
+) -> ApiResult<Vec<AuditEntry>> {
+    let mut request = authenticated(Request::get(&url(&format!("/sessions/{uuid}/audit"))), credentials);
+    if let Some(user) = user {
+        request = request.query([("user", user)]);
+    }
+    if let Some(team) = team {
+        request = request.query([("team", team)]);
+    }
+    json(request.send().await?).await
+}
+
+/// Runs of a session, most recent first.
+pub(crate) async fn list_runs(uuid: Uuid, credentials: Credentials) -> ApiResult<Vec<Run>> {
+    let request = authenticated(Request::get(&url(&format!("/sessions/{uuid}/runs"))), credentials);
+    json(request.send().await?).await
+}
+
+pub(crate) async fn create_run(uuid: Uuid, run: &Run, credentials: Credentials) -> ApiResult<Run> {
+    let request = authenticated(Request::post(&url(&format!("/sessions/{uuid}/runs"))), credentials);
+    json(request.json(run)?.send().await?).await
+}
+
+/// Sort the teams on the backend, which records the run, reproducing a run with its seed,
+/// or re-matching them from a previous run with minimal disruption.
+pub(crate) async fn solve(
+    uuid: Uuid,
+    seed: Option<u64>,
+    from: Option<Uuid>,
+    rerun: Option<Uuid>,
+    credentials: Credentials,
+) -> ApiResult<Run> {
+    let mut request = authenticated(Request::post(&url(&format!("/sessions/{uuid}/solve"))), credentials);
+    if let Some(seed) = seed {
+        request = request.query([("seed", seed.to_string())]);
+    }
+    if let Some(from) = from {
+        request = request.query([("from", from.to_string())]);
+    }
+    if let Some(rerun) = rerun {
+        request = request.query([("run", rerun.to_string())]);
+    }
+    json(request.send().await?).await
+}
+
+/// A run as shown to participants, or `None` if the backend does not know it.
+pub(crate) async fn get_published_run(
+    uuid: Uuid,
+    run: Uuid,
+    credentials: Credentials,
+) -> ApiResult<Option<PublishedRun>> {
+    let request = authenticated(Request::get(&url(&format!("/sessions/{uuid}/runs/{run}"))), credentials);
+    optional(request.send().await?).await
+}
+
+#[derive(Serialize)]
+struct WithdrawBody {
+    user: usize,
+}
+
+/// Take a user out of a published run, which records the run left with the promotions.
+pub(crate) async fn withdraw(uuid: Uuid, run: Uuid, user: usize, credentials: Credentials) -> ApiResult<Run> {
+    let request = authenticated(
+        Request::post(&url(&format!("/sessions/{uuid}/runs/{run}/withdraw"))),
+        credentials,
+    );
+    json(request.json(&WithdrawBody { user })?.send().await?).await
+}
+
+/// The session a join code refers to, or `None` if the code is unknown.
+pub(crate) async fn join(code: &str) -> ApiResult<Option<SessionSummary>> {
+    let code: String = code.chars().filter(char::is_ascii_alphanumeric).collect();
+    optional(Request::get(&url(&format!("/join/{code}"))).send().await?).await
+}
+
+/// Changes of a session streamed by the backend, followed until dropped.
+pub(crate) struct Subscription {
+    source: EventSource,
+    _listener: Closure<dyn FnMut(MessageEvent)>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.source.close();
+    }
+}
+
+/// Follow the changes of a session made from other tabs and browsers: `on_event`
+/// gets each of them, or `None` when some were missed and everything should be
+/// loaded again.
+///
+/// The browser reconnects by itself, resuming after the last event it got.
+pub(crate) fn follow_session(
+    uuid: Uuid,
+    mut on_event: impl FnMut(Option<SessionEvent>) + 'static,
+) -> Option<Subscription> {
+    let source = EventSource::new(&url(&format!("/sessions/{uuid}/events"))).ok()?;
+    let listener = Closure::<dyn FnMut(MessageEvent)>::new(move |message: MessageEvent| {
+        if message.type_() == "reset" {
+            on_event(None);
+            return;
+        }
+        let Some(data) = message.data().as_string() else {
+            return;
+        };
+        match serde_json::from_str::<SessionEvent>(&data) {
+            Ok(event) if event.client == Some(*CLIENT_ID) => {}
+            Ok(event) => on_event(Some(event)),
+            Err(err) => warn!("Could not read event of session {}: {}", uuid, err),
+        }
+    });
+    for name in Change::NAMES.iter().chain(["reset"].iter()) {
+        source
+            .add_event_listener_with_callback(name, listener.as_ref().unchecked_ref())
+            .ok()?;
+    }
+    Some(Subscription {
+        source,
+        _listener: listener,
+    })
+}
@@ -0,0 +1,59 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use crate::identity::{use_identity, Identity};
+
+/// Sign in with a name and, to use an existing account, its key.
+#[component]
+pub(crate) fn Account() -> Element {
+    let mut identity = use_identity();
+
+    let mut name = use_signal(|| "".to_string());
+    let mut key = use_signal(|| "".to_string());
+
+    if let Some(current) = identity() {
+        return rsx! {
+            div { class: "box is-flex is-align-items-center is-justify-content-space-between",
+                div {
+                    p { "Signed in as " strong { {current.name} } }
+                    p { class: "is-size-7",
+                        "Account key (to sign in from another browser): "
+                        code { "{current.key}" }
+                    }
+                }
+                button {
+                    class: "button is-light",
+                    onclick: move |_| identity.set(None),
+                    "Sign out"
+                }
+            }
+        };
+    }
+
+    rsx! {
+        form {
+            class: "box field is-flex is-grouped",
+            onsubmit: move |_| {
+                identity.set(Some(Identity {
+                    key: Uuid::parse_str(key().trim()).unwrap_or_else(|_| Uuid::new_v4()),
+                    name: name(),
+                }));
+            },
+            input {
+                class: "input",
+                placeholder: "Your name",
+                oninput: move |e| name.set(e.value()), value: name()
+            }
+            input {
+                class: "input",
+                placeholder: "Account key (leave empty to create one)",
+                oninput: move |e| key.set(e.value()), value: key()
+            }
+            button {
+                class: "button is-primary",
+                disabled: name().is_empty() || (!key().trim().is_empty() && Uuid::parse_str(key().trim()).is_err()),
+                "Sign in"
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use dioxus::prelude::*;
+use solver::{Attributes, BalanceRule, Constraints, RuleKind};
+
+/// Attributes of the users, and the balance rules on them for the organiser.
+#[component]
+pub(crate) fn AttributesPanel(
+    users: Vec<String>,
+    teams: Vec<String>,
+    attributes: Attributes,
+    constraints: Constraints,
+    /// Users whose row is shown.
+    rows: Vec<usize>,
+    /// Whether the values of the shown rows can be edited.
+    editable: bool,
+    /// Whether the attributes and the rules can be edited.
+    is_organiser: bool,
+    on_change: EventHandler<(Attributes, Constraints)>,
+) -> Element {
+    let mut new_attribute = use_signal(String::new);
+    // Rule being written: team (None for every team), attribute, value (empty for a share rule) and number
+    let mut rule_team = use_signal(|| None::<usize>);
+    let mut rule_attribute = use_signal(|| 0usize);
+    let mut rule_value = use_signal(String::new);
+    let mut rule_number = use_signal(|| 1usize);
+
+    let add_attribute = {
+        let attributes = attributes.clone();
+        let constraints = constraints.clone();
+        move |_: MouseEvent| {
+            let name = new_attribute().trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            let mut attributes = attributes.clone();
+            attributes.names.push(name);
+            new_attribute.set(String::new());
+            on_change.call((attributes, constraints.clone()));
+        }
+    };
+
+    let add_rule = {
+        let attributes = attributes.clone();
+        let constraints = constraints.clone();
+        move |_: MouseEvent| {
+            let value = rule_value().trim().to_string();
+            let kind = if value.is_empty() {
+                RuleKind::AtMostShare { percent: rule_number() }
+            } else {
+                RuleKind::AtLeast { value, count: rule_number() }
+            };
+            let mut constraints = constraints.clone();
+            constraints.balance.push(BalanceRule {
+                team: rule_team(),
+                attribute: rule_attribute(),
+                kind,
+            });
+            on_change.call((attributes.clone(), constraints));
+        }
+    };
+
+    rsx! {
+        div { class: "box",
+            h2 { class: "title is-4", "Attributes" }
+            p { class: "mb-3 has-text-grey",
+                "Describe the users, e.g. their level or department, so that the teams can be balanced on it."
+            }
+            if is_organiser {
+                div { class: "field has-addons",
+                    div { class: "control",
+                        input {
+                            class: "input",
+                            placeholder: "New attribute, e.g. Department",
+                            value: new_attribute(),
+                            oninput: move |e| new_attribute.set(e.value()),
+                        }
+                    }
+                    div { class: "control",
+                        button { class: "button is-primary", onclick: add_attribute, "➕" }
+                    }
+                }
+            }
+            if !attributes.names.is_empty() {
+                div { class: "table-container",
+                    table { class: "table is-striped is-hoverable",
+                        thead {
+                            tr {
+                                th { "User" }
+                                {attributes.names.iter().enumerate().map(|(attribute, name)| rsx! {
+                                    th {
+                                        {name.clone()}
+                                        if is_organiser {
+                                            button {
+                                                class: "delete is-small ml-2",
+                                                title: "Remove the attribute and its rules",
+                                                onclick: {
+                                                    let attributes = attributes.clone();
+                                                    let constraints = constraints.clone();
+                                                    move |_| {
+                                                        let mut attributes = attributes.clone();
+                                                        let mut constraints = constraints.clone();
+                                                        attributes.remove(attribute);
+                                                        constraints.remove_attribute(attribute);
+                                                        on_change.call((attributes, constraints));
+                                                    }
+                                                },
+                                            }
+                                        }
+                                    }
+                                })}
+                            }
+                        }
+                        tbody {
+                            {rows.iter().map(|&user| rsx! {
+                                tr {
+                                    td { {users.get(user).cloned().unwrap_or_default()} }
+                                    {(0..attributes.names.len()).map(|attribute| rsx! {
+                                        td {
+                                            input {
+                                                class: "input is-static",
+                                                readonly: !editable,
+                                                value: attributes.value(user, attribute).unwrap_or_default().to_string(),
+                                                oninput: {
+                                                    let attributes = attributes.clone();
+                                                    let constraints = constraints.clone();
+                                                    move |e: FormEvent| {
+                                                        let mut attributes = attributes.clone();
+                                                        attributes.set_value(user, attribute, e.value());
+                                                        on_change.call((attributes, constraints.clone()));
+                                                    }
+                                                },
+                                            }
+                                        }
+                                    })}
+                                }
+                            })}
+                        }
+                    }
+                }
+            }
+            if is_organiser && !attributes.names.is_empty() {
+                h3 { class: "title is-5 mt-4", "Balance rules" }
+                {constraints.balance.iter().enumerate().map(|(idx, rule)| rsx! {
+                    div { class: "tags mb-2",
+                        span { class: "tag is-link is-light is-medium",
+                            {rule.describe(&attributes, &teams)}
+                            button {
+                                class: "delete is-small",
+                                onclick: {
+                                    let attributes = attributes.clone();
+                                    let constraints = constraints.clone();
+                                    move |_| {
+                                        let mut constraints = constraints.clone();
+                                        constraints.balance.remove(idx);
+                                        on_change.call((attributes.clone(), constraints));
+                                    }
+                                },
+                            }
+                        }
+                    }
+                })}
+                div { class: "field is-grouped is-grouped-multiline",
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                onchange: move |e: FormEvent| rule_team.set(e.value().parse::<usize>().ok()),
+                                option { value: "", selected: rule_team().is_none(), "Every team" }
+                                {teams.iter().enumerate().map(|(team, name)| rsx! {
+                                    option { value: "{team}", selected: rule_team() == Some(team), {name.clone()} }
+                                })}
+                            }
+                        }
+                    }
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                onchange: move |e: FormEvent| rule_attribute.set(e.value().parse::<usize>().unwrap_or(0)),
+                                {attributes.names.iter().enumerate().map(|(attribute, name)| rsx! {
+                                    option { value: "{attribute}", selected: rule_attribute() == attribute, {name.clone()} }
+                                })}
+                            }
+                        }
+                    }
+                    div { class: "control",
+                        input {
+                            class: "input",
+                            placeholder: "Value (empty to cap a share)",
+                            value: rule_value(),
+                            oninput: move |e| rule_value.set(e.value()),
+                        }
+                    }
+                    div { class: "control",
+                        input {
+                            class: "input",
+                            "style": "width: 6em",
+                            r#type: "number",
+                            min: "1",
+                            title: if rule_value().trim().is_empty() { "Maximum share in %" } else { "Minimum count" },
+                            value: rule_number().to_string(),
+                            oninput: move |e| {
+                                if let Ok(number) = e.value().parse::<usize>() {
+                                    rule_number.set(number);
+                                }
+                            },
+                        }
+                    }
+                    div { class: "control",
+                        button { class: "button is-link", onclick: add_rule,
+                            if rule_value().trim().is_empty() { "Cap the share" } else { "Require at least" }
+                        }
+                    }
+                }
+                p { class: "help",
+                    "With a value, every team needs at least that many members with it; without one, no value may be held by more than that share of a team."
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use dioxus::prelude::*;
+use serde_json::Value;
+use solver::{Actor, AuditEdit, AuditEntry, Change, Field, Role};
+use uuid::Uuid;
+
+use crate::api::{self, Credentials};
+use crate::storage::format_time;
+
+/// Timeline of the changes of a session, for the organiser to tell who changed
+/// what when results are contested. It is loaded when opened, and filtered by
+/// the names users and teams had when the changes were made.
+#[component]
+pub(crate) fn AuditTimeline(uuid: Uuid, users: Vec<String>, teams: Vec<String>, credentials: Credentials) -> Element {
+    let mut open = use_signal(|| false);
+    let mut user = use_signal(String::new);
+    let mut team = use_signal(String::new);
+    let mut entries = use_resource(move || async move {
+        if !open() {
+            return None;
+        }
+        let (user, team) = (user(), team());
+        let named = |name: &str| Some(name.to_string()).filter(|name| !name.is_empty());
+        Some(api::get_audit(uuid, named(&user).as_deref(), named(&team).as_deref(), credentials).await)
+    });
+
+    rsx! {
+        div { class: "box",
+            div { class: "level",
+                div { class: "level-left",
+                    h2 { class: "title is-4 level-item", "Timeline" }
+                }
+                div { class: "level-right",
+                    if open() {
+                        button { class: "button is-small level-item", onclick: move |_| entries.restart(), "Refresh" }
+                    }
+                    button {
+                        class: "button is-small level-item",
+                        onclick: move |_| open.toggle(),
+                        if open() { "Hide" } else { "Show who changed what" }
+                    }
+                }
+            }
+            if open() {
+                div { class: "field is-grouped",
+                    div { class: "control",
+                        div { class: "select is-small",
+                            select {
+                                onchange: move |e: FormEvent| user.set(e.value()),
+                                option { value: "", selected: user().is_empty(), "All users" }
+                                for name in users.iter() {
+                                    option { value: name.clone(), selected: user() == *name, {name.clone()} }
+                                }
+                            }
+                        }
+                    }
+                    div { class: "control",
+                        div { class: "select is-small",
+                            select {
+                                onchange: move |e: FormEvent| team.set(e.value()),
+                                option { value: "", selected: team().is_empty(), "All teams" }
+                                for name in teams.iter() {
+                                    option { value: name.clone(), selected: team() == *name, {name.clone()} }
+                                }
+                            }
+                        }
+                    }
+                }
+                match &*entries.read() {
+                    Some(Some(Ok(entries))) if entries.is_empty() => rsx! {
+                        p { class: "has-text-grey", "No change matches." }
+                    },
+                    Some(Some(Ok(entries))) => rsx! {
+                        div { class: "table-container",
+                            table { class: "table is-striped is-fullwidth",
+                                thead {
+                                    tr {
+                                        th { "Date" }
+                                        th { "Who" }
+                                        th { "What" }
+                                    }
+                                }
+                                tbody {
+                                    for entry in entries.iter() {
+                                        tr {
+                                            td { {format_time(entry.at)} }
+                                            td { {actor(&entry.actor)} }
+                                            td {
+                                                p { {change(entry)} }
+                                                if !entry.edits.is_empty() {
+                                                    ul {
+                                                        for edit in entry.edits.iter() {
+                                                            li { {describe(edit)} }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    Some(Some(Err(err))) => rsx! {
+                        p { class: "has-text-danger", "The timeline could not be loaded: {err}" }
+                    },
+                    _ => rsx! {
+                        p { class: "has-text-grey", "Loading…" }
+                    },
+                }
+            }
+        }
+    }
+}
+
+fn actor(actor: &Actor) -> String {
+    match (actor.role, &actor.user) {
+        (Role::Organiser, None) => "Organiser".to_string(),
+        (Role::Organiser, Some(user)) => format!("Organiser ({user})"),
+        (Role::Participant, Some(user)) => user.clone(),
+        (Role::Participant, None) => "A participant".to_string(),
+    }
+}
+
+fn change(entry: &AuditEntry) -> String {
+    match &entry.change {
+        Change::Created => "Created the session".to_string(),
+        Change::Edited => "Edited the session".to_string(),
+        Change::Submitted { .. } => "Saved preferences".to_string(),
+        Change::RunRecorded { solver, .. } => format!("Recorded a run with {solver}"),
+        Change::Withdrawn { user, .. } => format!("Withdrew {user} from the results"),
+        Change::Deleted => "Deleted the session".to_string(),
+        Change::Expired => "Expired".to_string(),
+    }
+}
+
+/// An edited value, in words.
+fn describe(edit: &AuditEdit) -> String {
+    let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+    let user = edit.user.clone().unwrap_or_default();
+    let team = edit.team.clone().unwrap_or_default();
+    match (edit.field, &edit.before, &edit.after) {
+        (Field::Name, before, after) => format!("Session name: {} → {}", value(before), value(after)),
+        (Field::User, None, Some(name)) => format!("Added user {name}"),
+        (Field::User, Some(name), None) => format!("Removed user {name}"),
+        (Field::User, before, after) => format!("Renamed user {} to {}", value(before), value(after)),
+        (Field::Member, _, Some(_)) => format!("{user} claimed"),
+        (Field::Member, _, None) => format!("{user} released"),
+        (Field::Team, None, Some(name)) => format!("Added team {name}"),
+        (Field::Team, Some(name), None) => format!("Removed team {name}"),
+        (Field::Team, before, after) => format!("Renamed team {} to {}", value(before), value(after)),
+        (Field::Size, before, after) => format!("Size of {team}: {} → {}", value(before), value(after)),
+        (Field::Rank, before, after) => format!("Rank of {team} for {user}: {} → {}", value(before), value(after)),
+        (Field::Preferences, _, _) => format!("Preferences of {user} (private)"),
+        (Field::Constraints, before, after) => format!("Constraints: {}", parts(before, after)),
+        (Field::Attributes, before, after) => format!("Attributes: {}", parts(before, after)),
+        (Field::Roles, before, after) => format!("Roles: {}", parts(before, after)),
+        (Field::Settings, before, after) => format!("Settings: {}", parts(before, after)),
+    }
+}
+
+/// The parts of a value recorded as JSON which changed, with their values when
+/// they are short, e.g. `mechanism: "GaleShapley" → "Boston"`.
+fn parts(before: &Option<String>, after: &Option<String>) -> String {
+    let parse = |value: &Option<String>| {
+        value
+            .as_deref()
+            .and_then(|value| serde_json::from_str::<serde_json::Map<String, Value>>(value).ok())
+            .unwrap_or_default()
+    };
+    let (before, after) = (parse(before), parse(after));
+    let changed: Vec<String> = after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(*value))
+        .map(|(key, value)| match before.get(key) {
+            Some(old) if !is_compound(old) && !is_compound(value) => format!("{key}: {old} → {value}"),
+            _ => key.clone(),
+        })
+        .collect();
+    if changed.is_empty() {
+        "changed".to_string()
+    } else {
+        changed.join(", ")
+    }
+}
+
+fn is_compound(value: &Value) -> bool {
+    value.is_array() || value.is_object()
+}
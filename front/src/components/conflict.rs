@@ -0,0 +1,93 @@
+use dioxus::prelude::*;
+use solver::{Op, SessionResource};
+
+/// Edits of this page clashing with the edits made elsewhere since it last
+/// saved, found when saving or loading the session again.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Conflict {
+    /// The session as the backend has it now.
+    pub remote: SessionResource,
+    /// Each edit of this page with an edit made elsewhere it clashes with.
+    pub clashes: Vec<(Op, Op)>,
+}
+
+/// Prompt asking whose edits to keep when they clash. The other edits of both
+/// sides are merged whatever the choice, unless the user discards their own.
+#[component]
+pub(crate) fn ConflictPrompt(
+    clashes: Vec<(Op, Op)>,
+    on_keep_mine: EventHandler,
+    on_keep_theirs: EventHandler,
+    on_discard: EventHandler,
+) -> Element {
+    rsx! {
+        div { class: "modal is-active",
+            div { class: "modal-background" }
+            div { class: "modal-card",
+                header { class: "modal-card-head",
+                    p { class: "modal-card-title", "The session was edited elsewhere" }
+                }
+                section { class: "modal-card-body",
+                    p { class: "mb-4",
+                        "Some of your edits were not saved yet when the same values were edited from another browser."
+                    }
+                    table { class: "table is-fullwidth is-striped",
+                        thead {
+                            tr {
+                                th { "Your edit" }
+                                th { "Edit made elsewhere" }
+                            }
+                        }
+                        tbody {
+                            for (mine, theirs) in clashes.iter() {
+                                tr {
+                                    td { {describe(mine)} }
+                                    td { {describe(theirs)} }
+                                }
+                            }
+                        }
+                    }
+                }
+                footer { class: "modal-card-foot buttons",
+                    button {
+                        class: "button is-primary",
+                        onclick: move |_| on_keep_mine.call(()),
+                        "Keep my edits"
+                    }
+                    button {
+                        class: "button",
+                        onclick: move |_| on_keep_theirs.call(()),
+                        "Keep their edits"
+                    }
+                    button {
+                        class: "button is-danger is-light",
+                        onclick: move |_| on_discard.call(()),
+                        "Discard all my edits"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What an edit does, in words.
+fn describe(op: &Op) -> String {
+    match op {
+        Op::SetName { name } => format!("Renamed the session to “{name}”"),
+        Op::AddUser { name } => format!("Added user “{name}”"),
+        Op::RemoveUser { user } => format!("Removed user “{}”", user.name),
+        Op::RenameUser { user, name } => format!("Renamed user “{}” to “{name}”", user.name),
+        Op::SetMember { user, member: Some(_) } => format!("Claimed user “{}”", user.name),
+        Op::SetMember { user, member: None } => format!("Released user “{}”", user.name),
+        Op::AddTeam { name, .. } => format!("Added team “{name}”"),
+        Op::RemoveTeam { team } => format!("Removed team “{}”", team.name),
+        Op::RenameTeam { team, name } => format!("Renamed team “{}” to “{name}”", team.name),
+        Op::SetSize { team, size } => format!("Set the size of “{}” to {size}", team.name),
+        Op::SetRank { user, team, rank: 0 } => format!("Unset the rank of “{}” for “{}”", user.name, team.name),
+        Op::SetRank { user, team, rank } => format!("Ranked “{}” {rank} for “{}”", team.name, user.name),
+        Op::SetConstraints { .. } => "Changed the constraints".to_string(),
+        Op::SetAttributes { .. } => "Changed the attributes".to_string(),
+        Op::SetRoles { .. } => "Changed the roles".to_string(),
+        Op::SetSettings { .. } => "Changed the settings".to_string(),
+    }
+}
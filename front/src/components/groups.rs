@@ -0,0 +1,120 @@
+use dioxus::prelude::*;
+use solver::Constraints;
+
+/// Users who asked to be together or apart.
+#[component]
+pub(crate) fn GroupsPanel(
+    users: Vec<String>,
+    constraints: Constraints,
+    editable: bool,
+    on_change: EventHandler<Constraints>,
+) -> Element {
+    // Pair of users picked to be put together or apart
+    let mut picked = use_signal(|| (0usize, 1usize));
+    let (first, second) = picked();
+    let valid_pair = first != second && first.max(second) < users.len();
+    let name = |user: usize| users.get(user).cloned().unwrap_or_default();
+
+    let user_select = |selected: usize, second: bool| rsx! {
+        div { class: "select",
+            select {
+                disabled: !editable,
+                onchange: move |e: FormEvent| {
+                    if let Ok(user) = e.value().parse::<usize>() {
+                        picked.with_mut(|(first, other)| if second { *other = user } else { *first = user });
+                    }
+                },
+                {users.iter().enumerate().map(|(user, name)| rsx! {
+                    option { value: "{user}", selected: user == selected, {name.clone()} }
+                })}
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "box",
+            h2 { class: "title is-4", "Groups" }
+            p { class: "mb-3 has-text-grey",
+                "Users of a group are put in the same team, taking a slot each, and users kept apart never share a team."
+            }
+            if editable && users.len() > 1 {
+                div { class: "field is-grouped is-grouped-multiline",
+                    div { class: "control",
+                        {user_select(first, false)}
+                    }
+                    div { class: "control",
+                        {user_select(second, true)}
+                    }
+                    div { class: "control",
+                        button {
+                            class: "button is-primary",
+                            disabled: !valid_pair,
+                            onclick: {
+                                let constraints = constraints.clone();
+                                move |_| on_change.call(changed(&constraints, |constraints| constraints.join(first, second)))
+                            },
+                            "Together"
+                        }
+                    }
+                    div { class: "control",
+                        button {
+                            class: "button is-warning",
+                            disabled: !valid_pair,
+                            onclick: {
+                                let constraints = constraints.clone();
+                                move |_| on_change.call(changed(&constraints, |constraints| constraints.set_apart(first, second, true)))
+                            },
+                            "Apart"
+                        }
+                    }
+                }
+            }
+            if constraints.groups.is_empty() && constraints.apart.is_empty() {
+                p { class: "has-text-grey", "Nobody asked to be together or apart yet." }
+            }
+            {constraints.groups.iter().enumerate().map(|(idx, group)| rsx! {
+                div { class: "tags mb-2",
+                    span { class: "tag is-primary is-light", {format!("Group {}", idx + 1)} }
+                    {group.iter().map(|&user| rsx! {
+                        span { class: "tag is-medium",
+                            {name(user)}
+                            if editable {
+                                button {
+                                    class: "delete is-small",
+                                    title: "Leave the group",
+                                    onclick: {
+                                        let constraints = constraints.clone();
+                                        move |_| on_change.call(changed(&constraints, |constraints| constraints.leave_group(user)))
+                                    },
+                                }
+                            }
+                        }
+                    })}
+                }
+            })}
+            {constraints.apart.iter().map(|&(a, b)| rsx! {
+                div { class: "tags mb-2",
+                    span { class: "tag is-warning is-light is-medium",
+                        {format!("{} and {} apart", name(a), name(b))}
+                        if editable {
+                            button {
+                                class: "delete is-small",
+                                onclick: {
+                                    let constraints = constraints.clone();
+                                    move |_| on_change.call(changed(&constraints, |constraints| constraints.set_apart(a, b, false)))
+                                },
+                            }
+                        }
+                    }
+                }
+            })}
+        }
+    }
+}
+
+// A copy of the constraints with a change applied
+fn changed(constraints: &Constraints, change: impl FnOnce(&mut Constraints)) -> Constraints {
+    let mut constraints = constraints.clone();
+    change(&mut constraints);
+    constraints
+}
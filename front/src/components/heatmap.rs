@@ -0,0 +1,55 @@
+use dioxus::prelude::*;
+use solver::{Assignment, Fractional, Session};
+
+/// Probability of each user to end in each team, darker being likelier, with the
+/// team they got outlined.
+#[component]
+pub(crate) fn Heatmap(session: Session, probabilities: Fractional, assignment: Assignment) -> Element {
+    let cell = |probability: f64, drawn: bool| {
+        let text = if probability > 0.5 { "#fff" } else { "inherit" };
+        let outline = if drawn { "box-shadow: inset 0 0 0 2px #363636;" } else { "" };
+        rsx! {
+            td {
+                class: if drawn { "has-text-centered has-text-weight-bold" } else { "has-text-centered" },
+                style: "background-color: rgba(72, 95, 199, {probability:.2}); color: {text}; {outline}",
+                if probability > 0.0005 { {format!("{:.0}%", probability * 100.0)} }
+            }
+        }
+    };
+
+    rsx! {
+        h3 { class: "title is-5 mt-5", "Probabilities" }
+        p { class: "mb-3 has-text-grey", "The teams were drawn by lot with these probabilities, the team each user got being outlined." }
+        div { class: "table-container",
+            table { class: "table is-bordered is-narrow",
+                thead {
+                    tr {
+                        th { "User" }
+                        {session.teams.iter().map(|team| rsx! { th { {team.clone()} } })}
+                        th { "No team" }
+                    }
+                }
+                tbody {
+                    {session.users.iter().enumerate().map(|(user, name)| {
+                        let team = assignment.team_of(user);
+                        rsx! {
+                            tr {
+                                td { {name.clone()} }
+                                {(0..session.teams.len()).map(|idx| {
+                                    let probability = probabilities
+                                        .probabilities
+                                        .get(user)
+                                        .and_then(|row| row.get(idx))
+                                        .copied()
+                                        .unwrap_or(0.0);
+                                    cell(probability, team == Some(idx))
+                                })}
+                                {cell(probabilities.unassigned(user), team.is_none())}
+                            }
+                        }
+                    })}
+                }
+            }
+        }
+    }
+}
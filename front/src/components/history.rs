@@ -0,0 +1,166 @@
+use dioxus::prelude::*;
+use solver::{Run, Session};
+use uuid::Uuid;
+
+use crate::routes::Route;
+use crate::storage::format_time;
+
+/// Past runs of a session, two of which can be compared.
+#[component]
+pub(crate) fn RunHistory(
+    uuid: Uuid,
+    session: Session,
+    runs: Vec<Run>,
+    on_show: EventHandler<Run>,
+    /// Sort the teams again as a run did, with its seed and settings.
+    on_rerun: EventHandler<Run>,
+) -> Element {
+    // Runs picked for the comparison, in the order they were picked
+    let mut compared = use_signal(Vec::<Uuid>::new);
+    let input_hash = session.input_hash();
+
+    let mut toggle_compared = move |id: Uuid| {
+        compared.with_mut(|compared| {
+            if let Some(idx) = compared.iter().position(|&other| other == id) {
+                compared.remove(idx);
+            } else {
+                compared.push(id);
+                if compared.len() > 2 {
+                    compared.remove(0);
+                }
+            }
+        });
+    };
+
+    let find = |id: &Uuid| runs.iter().find(|run| run.id == *id).cloned();
+    let diff = match compared().as_slice() {
+        [a, b] => find(a).zip(find(b)).map(|(a, b)| {
+            // Compare from the oldest run to the newest one
+            if a.created_at <= b.created_at { (a, b) } else { (b, a) }
+        }),
+        _ => None,
+    };
+
+    rsx! {
+        div { class: "box",
+            h2 { class: "title is-4", "Run history" }
+            if runs.is_empty() {
+                p { class: "has-text-grey", "No run yet: sort the teams to start one." }
+            } else {
+                p { class: "mb-3 has-text-grey", "Tick two runs to see who moved between them." }
+                div { class: "table-container",
+                    table { class: "table is-striped is-hoverable is-fullwidth",
+                        thead {
+                            tr {
+                                th {}
+                                th { "Date" }
+                                th { "Solver" }
+                                th { "Seed" }
+                                th { "Input" }
+                                th { "Average choice" }
+                                th { "First choices" }
+                                th { "Blocking pairs" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            {runs.iter().rev().map(|run| {
+                                let id = run.id;
+                                let shown = run.clone();
+                                let rerun = run.clone();
+                                rsx! {
+                                    tr { key: "{id}",
+                                        td {
+                                            input {
+                                                r#type: "checkbox",
+                                                checked: compared().contains(&id),
+                                                onchange: move |_| toggle_compared(id),
+                                            }
+                                        }
+                                        td { {format_time(run.created_at)} }
+                                        td { {run.solver.clone()} }
+                                        td { class: "is-family-monospace",
+                                            {run.seed.map(|seed| seed.to_string()).unwrap_or("—".to_string())}
+                                        }
+                                        td { class: "is-family-monospace",
+                                            {run.input_hash.chars().take(8).collect::<String>()}
+                                            if run.input_hash != input_hash {
+                                                span { class: "tag is-warning ml-2", title: "Preferences or teams changed since", "outdated" }
+                                            }
+                                        }
+                                        td { {format!("{:.2}", run.metrics.average_rank)} }
+                                        td { "{run.metrics.first_choices()}" }
+                                        td { "{run.metrics.blocking_pairs.len()}" }
+                                        td {
+                                            div { class: "buttons are-small is-flex-wrap-nowrap",
+                                                button {
+                                                    class: "button is-link",
+                                                    onclick: move |_| on_show.call(shown.clone()),
+                                                    "Show"
+                                                }
+                                                if run.seed.is_some() {
+                                                    button {
+                                                        class: "button",
+                                                        title: "Sort the teams again with this seed and the settings of the run",
+                                                        onclick: move |_| on_rerun.call(rerun.clone()),
+                                                        "Re-run"
+                                                    }
+                                                }
+                                                Link {
+                                                    class: "button",
+                                                    to: Route::PublishedResults { uuid, run: id },
+                                                    "Page"
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            })}
+                        }
+                    }
+                }
+            }
+            if let Some((from, to)) = diff {
+                RunDiff { session: session.clone(), from, to }
+            }
+        }
+    }
+}
+
+/// Who moved between two runs.
+#[component]
+fn RunDiff(session: Session, from: Run, to: Run) -> Element {
+    let moves = from.assignment.diff(&to.assignment);
+    let team = |team: Option<usize>| match team {
+        Some(team) => session.teams.get(team).cloned().unwrap_or_default(),
+        None => "Unassigned".to_string(),
+    };
+
+    rsx! {
+        h3 { class: "title is-5 mt-5",
+            {format!("From {} to {}", format_time(from.created_at), format_time(to.created_at))}
+        }
+        if moves.is_empty() {
+            p { "No one moved." }
+        } else {
+            table { class: "table is-striped is-fullwidth",
+                thead {
+                    tr {
+                        th { "User" }
+                        th { "Before" }
+                        th { "After" }
+                    }
+                }
+                tbody {
+                    {moves.iter().map(|m| rsx! {
+                        tr {
+                            td { {session.users.get(m.user).cloned().unwrap_or_default()} }
+                            td { {team(m.from)} }
+                            td { {team(m.to)} }
+                        }
+                    })}
+                }
+            }
+        }
+    }
+}
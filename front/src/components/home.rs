@@ -1,7 +1,13 @@
 use dioxus::prelude::*;
+use dioxus_logger::tracing::warn;
 use uuid::Uuid;
-use dioxus_sdk_storage::use_persistent;
+use crate::api::{self, Credentials};
+use crate::components::account::Account;
+use crate::components::not_found::{create_session_for_code, UnknownSession};
+use crate::components::sessions::SessionList;
+use crate::identity::use_identity;
 use crate::routes::Route;
+use crate::storage::{register_session, store, use_known_sessions};
 
 static LOGO: Asset = asset!("/assets/logo.png");
 
@@ -11,6 +17,12 @@ pub(crate) fn Home() -> Element {
 
     let mut team = use_signal(|| "".to_string());
     let mut team_code = use_signal(|| "".to_string());
+    let known = use_known_sessions();
+    let identity = use_identity();
+    let mut checking = use_signal(|| false);
+    // Set when the code matches no session, to whether the backend was unreachable
+    let mut unknown = use_signal(|| None::<bool>);
+    let mut error = use_signal(|| None::<String>);
 
     rsx! {
     div {
@@ -39,8 +51,8 @@ pub(crate) fn Home() -> Element {
                 form {
                     onsubmit: move |_| {
                             let uuid = Uuid::new_v4();
-                            let mut name = use_persistent(format!("name_{}", uuid), || "".to_string());
-                            name.set(team().to_string());
+                            store(uuid, "name", &team());
+                            register_session(uuid);
                         nav.push(Route::Teams { uuid } );
                     },
                     class: "box field is-flex is-grouped",
@@ -65,20 +77,72 @@ pub(crate) fn Home() -> Element {
                 form {
                     class: "box field is-flex is-grouped",
                         onsubmit: move |_| {
-                        nav.push(Route::Teams { uuid: Uuid::parse_str(team_code().as_str()).unwrap() } );
+                        // Either a full session UUID or a short join code
+                        let code = team_code().trim().to_string();
+                        let uuid = Uuid::parse_str(&code).ok();
+                        if let Some(uuid) = uuid.filter(|uuid| known().contains(uuid)) {
+                            nav.push(Route::Teams { uuid });
+                            return;
+                        }
+                        checking.set(true);
+                        spawn(async move {
+                            // Check that the session exists before opening it
+                            let found = match uuid {
+                                Some(uuid) => api::get_session(uuid, Credentials::default())
+                                    .await
+                                    .map(|resource| resource.map(|resource| resource.id)),
+                                None => api::join(&code)
+                                    .await
+                                    .map(|summary| summary.map(|summary| summary.id)),
+                            };
+                            checking.set(false);
+                            match found {
+                                Ok(Some(uuid)) => {
+                                    nav.push(Route::Teams { uuid });
+                                }
+                                Ok(None) => unknown.set(Some(false)),
+                                Err(err) => {
+                                    warn!("Could not check code {}: {}", code, err);
+                                    unknown.set(Some(true));
+                                }
+                            }
+                        });
                     },
                     input {
                         class: "input",
                         placeholder: "Team code",
-                        oninput: move |e| team_code.set(e.value()), value: team_code() }
+                        oninput: move |e| {
+                            team_code.set(e.value());
+                            unknown.set(None);
+                            error.set(None);
+                        },
+                        value: team_code() }
                     button {
-                        class: "button is-primary",
-                        disabled: Uuid::parse_str(team_code().as_str()).is_err(),
+                        class: if checking() { "button is-primary is-loading" } else { "button is-primary" },
+                        disabled: team_code().trim().is_empty(),
                         "Join"
                     }
                     }
+                if let Some(unreachable) = unknown() {
+                    UnknownSession {
+                        code: team_code().trim().to_string(),
+                        unreachable,
+                        error: error(),
+                        on_create: move |_| {
+                            let identity = identity().map(|identity| identity.key);
+                            create_session_for_code(nav, team_code().trim().to_string(), identity, error);
+                        },
+                        on_back: move |_| {
+                            team_code.set("".to_string());
+                            unknown.set(None);
+                            error.set(None);
+                        },
+                    }
+                }
                 }
             }
+        Account {}
+        SessionList {}
         }
     }
 }
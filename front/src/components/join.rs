@@ -0,0 +1,76 @@
+use dioxus::prelude::*;
+use dioxus_sdk_storage::use_persistent;
+use uuid::Uuid;
+
+use crate::api;
+use crate::components::not_found::{create_session_for_code, UnknownSession};
+use crate::identity::use_identity;
+use crate::routes::Route;
+
+/// Resolve a join code with the backend and open its session.
+#[component]
+pub(crate) fn Join(code: String) -> Element {
+    let nav = navigator();
+    let identity = use_identity();
+    let error = use_signal(|| None::<String>);
+
+    let session = use_resource({
+        let code = code.clone();
+        move || {
+            let code = code.clone();
+            async move { api::join(&code).await }
+        }
+    });
+
+    use_effect(move || {
+        if let Some(Ok(Some(summary))) = &*session.read() {
+            nav.replace(Route::Teams { uuid: summary.id });
+        }
+    });
+
+    let unreachable = match &*session.read() {
+        None | Some(Ok(Some(_))) => return rsx! {
+            div { class: "container has-text-centered py-6",
+                p { class: "is-size-4", "Joining…" }
+            }
+        },
+        Some(Ok(None)) => false,
+        Some(Err(_)) => true,
+    };
+
+    let create = {
+        let code = code.clone();
+        move |_: ()| {
+            let identity = identity().map(|identity| identity.key);
+            create_session_for_code(nav, code.clone(), identity, error);
+        }
+    };
+
+    rsx! {
+        div { class: "container py-6",
+            UnknownSession {
+                code,
+                unreachable,
+                error: error(),
+                on_create: create,
+                on_back: move |_| {
+                    nav.push(Route::Home {});
+                },
+            }
+        }
+    }
+}
+
+/// Open a session as an organiser, keeping the key from the organiser link.
+#[component]
+pub(crate) fn Organise(uuid: Uuid, organiser_key: Uuid) -> Element {
+    let nav = navigator();
+    let mut stored = use_persistent(format!("organiser_key_{}", uuid), || None::<Uuid>);
+
+    use_hook(move || stored.set(Some(organiser_key)));
+    use_effect(move || {
+        nav.replace(Route::Teams { uuid });
+    });
+
+    rsx! {}
+}
@@ -1,3 +1,20 @@
+pub mod account;
+pub mod attributes;
+pub mod audit;
+pub mod conflict;
+pub mod groups;
 pub mod header;
+pub mod heatmap;
+pub mod history;
 pub mod home;
-pub mod teams;
\ No newline at end of file
+pub mod join;
+pub mod not_found;
+pub mod priorities;
+pub mod published;
+pub mod results;
+pub mod roles;
+pub mod sessions;
+pub mod settings;
+pub mod share;
+pub mod stable;
+pub mod teams;
@@ -0,0 +1,96 @@
+use dioxus::prelude::*;
+use dioxus::router::Navigator;
+use solver::Session;
+use uuid::Uuid;
+
+use crate::api::{self, Credentials};
+use crate::routes::Route;
+use crate::storage::{new_organiser_key, register_session};
+
+/// Page of the URLs matching no route.
+#[component]
+pub(crate) fn NotFound(segments: Vec<String>) -> Element {
+    rsx! {
+        div { class: "container has-text-centered py-6",
+            h1 { class: "title", "Page not found" }
+            p { class: "mb-5", "There is nothing at /{segments.join(\"/\")}." }
+            Link { class: "button is-link", to: Route::Home {}, "Back to home" }
+        }
+    }
+}
+
+/// Choice offered when a session code matches no session, or when the backend
+/// could not be reached to check it (`unreachable`).
+#[component]
+pub(crate) fn UnknownSession(
+    code: String,
+    unreachable: bool,
+    error: Option<String>,
+    on_create: EventHandler,
+    on_back: EventHandler,
+) -> Element {
+    rsx! {
+        div { class: "notification is-warning has-text-centered",
+            if unreachable {
+                p { class: "is-size-5 mb-4",
+                    "The server could not be reached to check whether "
+                    code { {code.clone()} }
+                    " exists."
+                }
+            } else {
+                p { class: "is-size-5 mb-4",
+                    "No session matches "
+                    code { {code.clone()} }
+                    "."
+                }
+            }
+            if let Some(error) = error {
+                p { class: "has-text-danger mb-4", {error} }
+            }
+            div { class: "buttons is-centered",
+                button {
+                    class: "button is-primary",
+                    onclick: move |_| on_create.call(()),
+                    "Create a new session with this code"
+                }
+                button {
+                    class: "button",
+                    onclick: move |_| on_back.call(()),
+                    "Go back"
+                }
+            }
+        }
+    }
+}
+
+/// Create the session a code was meant for and open it: a UUID is used as is,
+/// while a join code must be claimed on the backend.
+pub(crate) fn create_session_for_code(
+    nav: Navigator,
+    code: String,
+    identity: Option<Uuid>,
+    mut error: Signal<Option<String>>,
+) {
+    match Uuid::parse_str(code.trim()) {
+        Ok(uuid) => {
+            register_session(uuid);
+            nav.push(Route::Teams { uuid });
+        }
+        Err(_) => {
+            let uuid = Uuid::new_v4();
+            let credentials = Credentials {
+                identity,
+                organiser_key: Some(new_organiser_key(uuid)),
+            };
+            spawn(async move {
+                let session = Session::new("Unnamed".to_string());
+                match api::create_session(uuid, &session, &code, credentials).await {
+                    Ok(_) => {
+                        nav.push(Route::Teams { uuid });
+                    }
+                    Err(err) => error.set(Some(format!("Could not create the session: {}", err))),
+                }
+            });
+        }
+    }
+}
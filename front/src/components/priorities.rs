@@ -0,0 +1,82 @@
+use dioxus::prelude::*;
+use dioxus_logger::tracing::info;
+use solver::Constraints;
+
+/// The users each team wants most, for the organiser.
+#[component]
+pub(crate) fn PrioritiesPanel(
+    users: Vec<String>,
+    teams: Vec<String>,
+    constraints: Constraints,
+    on_change: EventHandler<Constraints>,
+) -> Element {
+    let mut shown = use_signal(|| false);
+    let n_users = users.len();
+
+    rsx! {
+        div { class: "box",
+            div { class: "is-flex is-justify-content-space-between is-align-items-center",
+                h2 { class: "title is-4 mb-0", "Team priorities" }
+                button {
+                    class: "button is-small",
+                    onclick: move |_| shown.toggle(),
+                    if shown() { "Hide" } else { "Edit" }
+                }
+            }
+            if shown() {
+                p { class: "my-3 has-text-grey",
+                    "Rank the users each team wants, 1 being the one it wants most. Teams prefer the users they ranked, then the users that ranked them higher."
+                }
+                div { class: "table-container",
+                    table { class: "table is-striped is-hoverable m-auto",
+                        thead {
+                            tr {
+                                th { "/" }
+                                {teams.iter().map(|team| rsx! {
+                                    th { {team.clone()} }
+                                })}
+                            }
+                        }
+                        tbody {
+                            {users.iter().enumerate().map(|(user, name)| rsx! {
+                                tr {
+                                    td { {name.clone()} }
+                                    {(0..teams.len()).map(|team| {
+                                        let constraints = constraints.clone();
+                                        rsx! {
+                                            td {
+                                                input {
+                                                    class: "input is-static",
+                                                    r#type: "number",
+                                                    placeholder: "—",
+                                                    min: "1",
+                                                    max: n_users.to_string(),
+                                                    value: constraints.priority(team, user).map(|rank| rank.to_string()).unwrap_or_default(),
+                                                    oninput: move |e| {
+                                                        let rank = match e.value().trim() {
+                                                            "" => None,
+                                                            value => match value.parse::<usize>() {
+                                                                Ok(rank) => Some(rank),
+                                                                Err(_) => {
+                                                                    info!("Invalid input: {}", e.value());
+                                                                    return;
+                                                                }
+                                                            },
+                                                        };
+                                                        let mut constraints = constraints.clone();
+                                                        constraints.set_priority(team, user, rank);
+                                                        on_change.call(constraints);
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    })}
+                                }
+                            })}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use dioxus::prelude::*;
+use dioxus_sdk_storage::use_persistent;
+use uuid::Uuid;
+
+use crate::api::{self, Credentials};
+use crate::identity::use_identity;
+use crate::routes::Route;
+use crate::storage::format_time;
+
+/// Read-only results of a run, as shown to participants: who is in which team,
+/// who is waiting for a seat, and who moved since a withdrawal.
+#[component]
+pub(crate) fn PublishedResults(uuid: Uuid, run: Uuid) -> Element {
+    let mut search = use_signal(String::new);
+    let identity = use_identity();
+    let organiser_key = use_persistent(format!("organiser_key_{}", uuid), || None::<Uuid>);
+    let mut confirming = use_signal(|| false);
+    let mut withdraw_error = use_signal(|| None::<String>);
+
+    // Helper: Keys sent to the backend
+    let credentials = move || Credentials {
+        identity: identity.peek().as_ref().map(|identity| identity.key),
+        organiser_key: *organiser_key.peek(),
+    };
+
+    let mut published = use_resource(move || async move { api::get_published_run(uuid, run, credentials()).await });
+
+    // Helper: Give up the seat of the user claimed by the caller
+    let withdraw = move |user: usize| {
+        spawn(async move {
+            match api::withdraw(uuid, run, user, credentials()).await {
+                Ok(_) => {
+                    confirming.set(false);
+                    withdraw_error.set(None);
+                    published.restart();
+                }
+                Err(err) => withdraw_error.set(Some(err.to_string())),
+            }
+        });
+    };
+
+    let published = match &*published.read() {
+        None => return rsx! {
+            div { class: "container has-text-centered py-6",
+                p { class: "is-size-4", "Loading…" }
+            }
+        },
+        Some(Ok(Some(published))) => published.clone(),
+        Some(result) => {
+            let message = match result {
+                Err(_) => "The results could not be loaded: the server is unreachable.",
+                _ => "These results do not exist, or were removed.",
+            };
+            return rsx! {
+                div { class: "container py-6",
+                    div { class: "notification is-warning", {message} }
+                    Link { class: "button", to: Route::Teams { uuid }, "Back to the session" }
+                }
+            };
+        }
+    };
+
+    let query = search().trim().to_lowercase();
+    let matches = |name: &str| !query.is_empty() && name.to_lowercase().contains(&query);
+    let found: Vec<(String, String)> = published
+        .teams
+        .iter()
+        .flat_map(|team| team.members.iter().map(move |member| (member.clone(), team.name.clone())))
+        .chain(published.unassigned.iter().map(|user| (user.clone(), "no team".to_string())))
+        .filter(|(user, _)| matches(user))
+        .collect();
+    let waiting: Vec<(String, usize, String)> = published
+        .teams
+        .iter()
+        .flat_map(|team| {
+            team.waitlist
+                .iter()
+                .enumerate()
+                .map(move |(position, user)| (user.clone(), position + 1, team.name.clone()))
+        })
+        .filter(|(user, _, _)| matches(user))
+        .collect();
+
+    rsx! {
+        div { class: "container py-5",
+            h1 { class: "title has-text-centered", {published.session.clone()} }
+            p { class: "subtitle has-text-centered", {format!("Teams of {}", format_time(published.created_at))} }
+            if !published.withdrawn.is_empty() || !published.promotions.is_empty() {
+                div { class: "notification is-info is-light",
+                    p { class: "has-text-weight-bold mb-2", "Changes since the results were published" }
+                    {published.withdrawn.iter().map(|user| rsx! {
+                        p { {format!("🚪 {user} withdrew.")} }
+                    })}
+                    {published.promotions.iter().map(|promotion| rsx! {
+                        p { class: if matches(&promotion.user) { "has-text-weight-bold" } else { "" },
+                            {format!(
+                                "⬆️ {} moved up from the waitlist to {}, leaving {}.",
+                                promotion.user,
+                                promotion.to,
+                                promotion.from.clone().unwrap_or("no team".to_string())
+                            )}
+                        }
+                    })}
+                }
+            }
+            if let Some(user) = published.own {
+                div { class: "box",
+                    if confirming() {
+                        p { class: "mb-3", "Your seat will go to the next user waiting for your team. This cannot be undone." }
+                        div { class: "buttons",
+                            button { class: "button is-danger", onclick: move |_| withdraw(user), "Withdraw" }
+                            button { class: "button", onclick: move |_| confirming.set(false), "Cancel" }
+                        }
+                    } else {
+                        p { class: "mb-3", "You can no longer take part? Withdraw so that someone waiting can take your seat." }
+                        button { class: "button is-danger is-light", onclick: move |_| confirming.set(true), "Withdraw from the teams" }
+                    }
+                    if let Some(error) = withdraw_error() {
+                        p { class: "help is-danger", {error} }
+                    }
+                }
+            }
+            div { class: "field",
+                div { class: "control",
+                    input {
+                        class: "input is-medium",
+                        placeholder: "Search your name",
+                        value: search(),
+                        oninput: move |e| search.set(e.value()),
+                    }
+                }
+            }
+            if !query.is_empty() {
+                div { class: "notification",
+                    if found.is_empty() && waiting.is_empty() {
+                        p { "No one matches this name." }
+                    }
+                    {found.iter().map(|(user, team)| rsx! {
+                        p {
+                            span { class: "has-text-weight-bold", {user.clone()} }
+                            {format!(" is in {team}")}
+                        }
+                    })}
+                    {waiting.iter().map(|(user, position, team)| rsx! {
+                        p {
+                            span { class: "has-text-weight-bold", {user.clone()} }
+                            {format!(" is #{position} on the waitlist of {team}")}
+                        }
+                    })}
+                }
+            }
+            div { class: "columns is-multiline",
+                {published.teams.iter().map(|team| rsx! {
+                    div { class: "column is-one-third",
+                        div { class: "box",
+                            p { class: "has-text-weight-bold mb-2", {team.name.clone()} }
+                            div { class: "tags",
+                                {team.members.iter().map(|member| rsx! {
+                                    span {
+                                        class: if matches(member) { "tag is-primary is-medium" } else { "tag is-light is-medium" },
+                                        {member.clone()}
+                                    }
+                                })}
+                            }
+                            if !team.waitlist.is_empty() {
+                                p { class: "is-size-7 has-text-grey",
+                                    {format!(
+                                        "Waitlist: {}",
+                                        team.waitlist
+                                            .iter()
+                                            .enumerate()
+                                            .map(|(idx, user)| format!("{}. {user}", idx + 1))
+                                            .collect::<Vec<_>>()
+                                            .join(", ")
+                                    )}
+                                }
+                            }
+                        }
+                    }
+                })}
+                if !published.unassigned.is_empty() {
+                    div { class: "column is-one-third",
+                        div { class: "box has-background-warning-light",
+                            p { class: "has-text-weight-bold mb-2", "Unassigned users" }
+                            div { class: "tags",
+                                {published.unassigned.iter().map(|user| rsx! {
+                                    span {
+                                        class: if matches(user) { "tag is-primary is-medium" } else { "tag is-light is-medium" },
+                                        {user.clone()}
+                                    }
+                                })}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
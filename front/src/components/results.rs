@@ -0,0 +1,211 @@
+use dioxus::prelude::*;
+use solver::{evaluate, Assignment, Session};
+
+/// The teams of an assignment, whose users can be moved by drag and drop when editable,
+/// along with its metrics recomputed on each change.
+#[component]
+pub(crate) fn Results(session: Session, assignment: Signal<Assignment>, editable: bool) -> Element {
+    // User being dragged
+    let mut dragged = use_signal(|| None::<usize>);
+    let metrics = evaluate(&session, &assignment());
+
+    let mut drop_on = move |team: Option<usize>| {
+        let user = dragged();
+        dragged.set(None);
+        if let Some(user) = user {
+            assignment.with_mut(|assignment| assignment.move_user(user, team));
+        }
+    };
+
+    let user_tag = |user: usize, team: Option<usize>| {
+        let mut dragged = dragged.to_owned();
+        let pinned = team.is_some() && session.constraints.pin(user) == team;
+        let violation = metrics.constraint_violations.contains(&user)
+            || metrics.split_groups.iter().any(|&group| session.constraints.groups[group].contains(&user))
+            || metrics.apart_together.iter().any(|&(a, b)| a == user || b == user);
+        let blocking = metrics.blocking_pairs.iter().any(|pair| pair.user == user);
+        let class = if violation {
+            "tag is-danger is-medium"
+        } else if blocking {
+            "tag is-warning is-medium"
+        } else if pinned {
+            "tag is-info is-medium"
+        } else {
+                "tag is-light is-medium"
+            };
+            let rank = team
+                .and_then(|team| session.preferences.get(user)?.get(team).copied())
+                .filter(|&rank| rank > 0);
+            let name = session.users.get(user).cloned().unwrap_or_default();
+            rsx! {
+                span {
+                    class,
+                    draggable: editable,
+                    "style": if editable { "cursor: grab" } else { "" },
+                    title: if let Some(rank) = rank { format!("Choice #{rank}") } else { String::new() },
+                    ondragstart: move |_| dragged.set(Some(user)),
+                    if pinned { "📌 " }
+                    {name}
+                }
+            }
+        };
+
+        rsx! {
+            div { class: "columns is-multiline",
+                {session.teams.iter().enumerate().map(|(team, team_name)| {
+                    let members = assignment().teams.get(team).cloned().unwrap_or_default();
+                    let size = session.teams_size.get(team).copied().unwrap_or(0);
+                    let over = metrics.over_capacity.contains(&team);
+                    rsx! {
+                        div { class: "column is-one-third",
+                            div {
+                                class: if over { "box has-background-danger-light" } else { "box" },
+                                ondragover: move |e| e.prevent_default(),
+                                ondrop: move |e| {
+                                    e.prevent_default();
+                                    drop_on(Some(team));
+                                },
+                                p { class: "has-text-weight-bold mb-2",
+                                    {team_name.clone()}
+                                    span { class: if over { "has-text-danger ml-2" } else { "has-text-grey ml-2" },
+                                        "{members.len()}/{size}"
+                                    }
+                                }
+                                div { class: "tags",
+                                    {members.iter().map(|&user| user_tag(user, Some(team)))}
+                                }
+                                if !session.roles.requirements(team).is_empty() {
+                                    p { class: "is-size-7 has-text-grey",
+                                        {session.roles.requirements(team).iter().zip(session.roles.filled(team, &members)).map(|(requirement, filled)| {
+                                            format!("{} {}/{}", requirement.role, filled, requirement.count)
+                                        }).collect::<Vec<_>>().join(", ")}
+                                    }
+                                }
+                                {session.attributes.names.iter().enumerate().map(|(attribute, name)| {
+                                    let distribution = session.attributes.distribution(&members, attribute);
+                                    rsx! {
+                                        p { class: "is-size-7 has-text-grey",
+                                            {format!(
+                                                "{name}: {}",
+                                                distribution
+                                                    .iter()
+                                                    .map(|(value, count)| format!("{value} ×{count}"))
+                                                    .collect::<Vec<_>>()
+                                                    .join(", ")
+                                            )}
+                                        }
+                                    }
+                                })}
+                            }
+                        }
+                    }
+                })}
+                if editable || !assignment().unassigned.is_empty() {
+                    div { class: "column is-one-third",
+                        div {
+                            class: "box has-background-warning-light",
+                            ondragover: move |e| e.prevent_default(),
+                            ondrop: move |e| {
+                                e.prevent_default();
+                                drop_on(None);
+                            },
+                            p { class: "has-text-weight-bold mb-2", "Unassigned users" }
+                            div { class: "tags",
+                                {assignment().unassigned.iter().map(|&user| user_tag(user, None))}
+                            }
+                        }
+                    }
+                }
+            }
+            if session.settings.private_preferences {
+                div { class: "notification is-info",
+                    p { "Preferences are private: satisfaction and blocking pairs are only computed by the backend when sorting." }
+                    if !metrics.over_capacity.is_empty() {
+                        p {
+                            "❌ Over capacity: "
+                            {metrics.over_capacity.iter().map(|&team| session.teams[team].clone()).collect::<Vec<_>>().join(", ")}
+                        }
+                    }
+                }
+            } else {
+            div { class: if metrics.is_stable() { "notification is-success" } else { "notification is-warning" },
+                p {
+                    {format!(
+                        "Average choice: {:.2} — first choices: {}/{}",
+                        metrics.average_rank,
+                        metrics.first_choices(),
+                        session.users.len()
+                    )}
+                }
+                if metrics.is_stable() {
+                    p { "✅ The assignment is stable." }
+                }
+                if !metrics.over_capacity.is_empty() {
+                    p {
+                        "❌ Over capacity: "
+                        {metrics.over_capacity.iter().map(|&team| session.teams[team].clone()).collect::<Vec<_>>().join(", ")}
+                    }
+                }
+                if !metrics.constraint_violations.is_empty() {
+                    p {
+                        "❌ Pinned or forbidden team ignored for: "
+                        {metrics.constraint_violations.iter().map(|&user| session.users[user].clone()).collect::<Vec<_>>().join(", ")}
+                    }
+                }
+                if !metrics.rule_violations.is_empty() {
+                    p { "❌ Balance rules not followed:" }
+                    ul {
+                        {metrics.rule_violations.iter().map(|violation| rsx! {
+                            li {
+                                {format!(
+                                    "{} — {}",
+                                    session.teams[violation.team],
+                                    session.constraints.balance[violation.rule].describe(&session.attributes, &session.teams)
+                                )}
+                            }
+                        })}
+                    }
+                }
+                if !metrics.missing_roles.is_empty() {
+                    p {
+                        "❌ Roles missing: "
+                        {metrics.missing_roles.iter().map(|missing| {
+                            format!("{} ×{} in {}", missing.role, missing.missing, session.teams[missing.team])
+                        }).collect::<Vec<_>>().join(", ")}
+                    }
+                }
+                if !metrics.split_groups.is_empty() {
+                    p {
+                        "❌ Groups split between teams: "
+                        {metrics.split_groups.iter().map(|&group| {
+                            session.constraints.groups[group].iter().map(|&user| session.users[user].clone()).collect::<Vec<_>>().join(" + ")
+                        }).collect::<Vec<_>>().join(", ")}
+                    }
+                }
+                if !metrics.apart_together.is_empty() {
+                    p {
+                        "❌ In the same team although they asked to be apart: "
+                        {metrics.apart_together.iter().map(|&(a, b)| format!("{} and {}", session.users[a], session.users[b])).collect::<Vec<_>>().join(", ")}
+                    }
+                }
+                if session.constraints.has_groups() && metrics.unassigned > 0 {
+                    p { "⚠️ Some users are unassigned: groups and users kept apart cannot always all be satisfied." }
+                }
+                if !metrics.blocking_pairs.is_empty() {
+                    p { "⚠️ Blocking pairs:" }
+                    ul {
+                        {metrics.blocking_pairs.iter().map(|pair| rsx! {
+                            li {
+                                {format!(
+                                    "{} and {} would rather be together",
+                                    session.users[pair.user],
+                                    session.teams[pair.team]
+                                )}
+                            }
+                        })}
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use dioxus::prelude::*;
+use solver::{Requirement, Roles};
+
+/// Roles required by the teams, for the organiser, and the skills of the users.
+#[component]
+pub(crate) fn RolesPanel(
+    users: Vec<String>,
+    teams: Vec<String>,
+    roles: Roles,
+    /// Users whose skills are shown.
+    rows: Vec<usize>,
+    /// Whether the skills of the shown rows can be edited.
+    editable: bool,
+    is_organiser: bool,
+    on_change: EventHandler<Roles>,
+) -> Element {
+    // Requirement being written
+    let mut new_team = use_signal(|| 0usize);
+    let mut new_role = use_signal(String::new);
+    let mut new_count = use_signal(|| 1usize);
+
+    let add_requirement = {
+        let roles = roles.clone();
+        move |_: MouseEvent| {
+            let role = new_role().trim().to_string();
+            if role.is_empty() {
+                return;
+            }
+            let mut roles = roles.clone();
+            roles.add_requirement(new_team(), Requirement { role, count: new_count() });
+            new_role.set(String::new());
+            on_change.call(roles);
+        }
+    };
+
+    rsx! {
+        div { class: "box",
+            h2 { class: "title is-4", "Roles and skills" }
+            p { class: "mb-3 has-text-grey",
+                "Teams may require roles, counted within their size, and users list the roles they can take, separated by commas."
+            }
+            {teams.iter().enumerate().filter(|&(team, _)| !roles.requirements(team).is_empty()).map(|(team, name)| rsx! {
+                div { class: "tags mb-2",
+                    span { class: "tag is-primary is-light", {name.clone()} }
+                    {roles.requirements(team).iter().enumerate().map(|(idx, requirement)| rsx! {
+                        span { class: "tag is-medium",
+                            {format!("{} ×{}", requirement.role, requirement.count)}
+                            if is_organiser {
+                                button {
+                                    class: "delete is-small",
+                                    onclick: {
+                                        let roles = roles.clone();
+                                        move |_| {
+                                            let mut roles = roles.clone();
+                                            roles.requirements[team].remove(idx);
+                                            on_change.call(roles);
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                    })}
+                }
+            })}
+            if is_organiser && !teams.is_empty() {
+                div { class: "field is-grouped is-grouped-multiline",
+                    div { class: "control",
+                        div { class: "select",
+                            select {
+                                onchange: move |e: FormEvent| new_team.set(e.value().parse::<usize>().unwrap_or(0)),
+                                {teams.iter().enumerate().map(|(team, name)| rsx! {
+                                    option { value: "{team}", selected: new_team() == team, {name.clone()} }
+                                })}
+                            }
+                        }
+                    }
+                    div { class: "control",
+                        input {
+                            class: "input",
+                            placeholder: "Role, e.g. Designer",
+                            value: new_role(),
+                            oninput: move |e| new_role.set(e.value()),
+                        }
+                    }
+                    div { class: "control",
+                        input {
+                            class: "input",
+                            "style": "width: 5em",
+                            r#type: "number",
+                            min: "1",
+                            value: new_count().to_string(),
+                            oninput: move |e| {
+                                if let Ok(count) = e.value().parse::<usize>() {
+                                    new_count.set(count);
+                                }
+                            },
+                        }
+                    }
+                    div { class: "control",
+                        button { class: "button is-link", onclick: add_requirement, "Require" }
+                    }
+                }
+            }
+            table { class: "table is-striped is-hoverable",
+                thead {
+                    tr {
+                        th { "User" }
+                        th { "Skills" }
+                    }
+                }
+                tbody {
+                    {rows.iter().map(|&user| rsx! {
+                        tr {
+                            td { {users.get(user).cloned().unwrap_or_default()} }
+                            td {
+                                input {
+                                    class: "input is-static",
+                                    readonly: !editable,
+                                    placeholder: "e.g. Designer, Backend",
+                                    value: roles.skills.get(user).map(|skills| skills.join(", ")).unwrap_or_default(),
+                                    // Parsed once typed, so that commas can be typed
+                                    onchange: {
+                                        let roles = roles.clone();
+                                        move |e: FormEvent| {
+                                            let skills = e
+                                                .value()
+                                                .split(',')
+                                                .map(|skill| skill.trim().to_string())
+                                                .filter(|skill| !skill.is_empty())
+                                                .collect();
+                                            let mut roles = roles.clone();
+                                            roles.set_skills(user, skills);
+                                            on_change.call(roles);
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                    })}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use dioxus::prelude::*;
+use dioxus_logger::tracing::warn;
+use dioxus_sdk_storage::use_persistent;
+use solver::{Attributes, Constraints, Roles, SessionSummary, Settings};
+use uuid::Uuid;
+
+use crate::api::{self, Credentials};
+use crate::identity::use_identity;
+use crate::routes::Route;
+use crate::storage::{forget_session, format_time, now, store, use_known_sessions};
+
+/// Sessions known to this browser and, when signed in, to the backend.
+#[component]
+pub(crate) fn SessionList() -> Element {
+    let known = use_known_sessions();
+    let identity = use_identity();
+
+    let mut remote = use_resource(move || async move {
+        let Some(identity) = identity() else {
+            return Vec::new();
+        };
+        api::list_sessions(identity.key).await.unwrap_or_else(|err| {
+            warn!("Could not list sessions: {}", err);
+            Vec::new()
+        })
+    });
+    let remote_only: Vec<SessionSummary> = remote()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|summary| !known().contains(&summary.id))
+        .collect();
+
+    if known().is_empty() && remote_only.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "box",
+            h2 { class: "title is-4", "Your sessions" }
+            div { class: "table-container",
+                table { class: "table is-hoverable is-fullwidth has-text-left",
+                    thead {
+                        tr {
+                            th { "Name" }
+                            th { "Last edit" }
+                            th { "Users" }
+                            th { "Teams" }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        // Most recently registered first
+                        for uuid in known().into_iter().rev() {
+                            LocalSession { key: "{uuid}", uuid, known }
+                        }
+                        for summary in remote_only {
+                            RemoteSession {
+                                key: "{summary.id}",
+                                summary: summary.clone(),
+                                on_change: move |_| remote.restart(),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A session whose content is in the local storage.
+#[component]
+fn LocalSession(uuid: Uuid, known: Signal<Vec<Uuid>>) -> Element {
+    let mut known = known;
+    let nav = navigator();
+    let identity = use_identity();
+
+    let name = use_persistent(format!("name_{}", uuid), || "Unnamed".to_string());
+    let users = use_persistent(format!("users_{}", uuid), Vec::<String>::new);
+    let teams = use_persistent(format!("teams_{}", uuid), Vec::<String>::new);
+    let teams_size = use_persistent(format!("teams_size_{}", uuid), Vec::<usize>::new);
+    let preferences = use_persistent(format!("preferences_{}", uuid), Vec::<Vec<usize>>::new);
+    let constraints = use_persistent(format!("constraints_{}", uuid), Constraints::default);
+    let settings = use_persistent(format!("settings_{}", uuid), Settings::default);
+    let attributes = use_persistent(format!("attributes_{}", uuid), Attributes::default);
+    let roles = use_persistent(format!("roles_{}", uuid), Roles::default);
+    let updated_at = use_persistent(format!("updated_at_{}", uuid), || 0i64);
+
+    let confirm_delete = use_signal(|| false);
+
+    // Hooks cannot be called from here, so the copy is written to the storage directly
+    let duplicate = move |_: ()| {
+        let copy = Uuid::new_v4();
+        store(copy, "name", &format!("{} (copy)", name()));
+        store(copy, "users", &users());
+        store(copy, "teams", &teams());
+        store(copy, "teams_size", &teams_size());
+        store(copy, "preferences", &preferences());
+        store(copy, "constraints", &constraints());
+        store(copy, "settings", &settings());
+        store(copy, "attributes", &attributes());
+        store(copy, "roles", &roles());
+        store(copy, "updated_at", &now());
+        store(copy, "organiser_key", &Some(Uuid::new_v4()));
+        known.with_mut(|known| known.push(copy));
+    };
+
+    let delete = move |_: ()| {
+        forget_session(known, uuid);
+        let credentials = Credentials {
+            identity: identity().map(|identity| identity.key),
+            ..Credentials::default()
+        };
+        spawn(async move {
+            if let Err(err) = api::delete_session(uuid, credentials).await {
+                warn!("Could not delete session {} from the backend: {}", uuid, err);
+            }
+        });
+    };
+
+    rsx! {
+        tr {
+            td { {name()} }
+            td {
+                if updated_at() > 0 {
+                    {format_time(updated_at())}
+                } else {
+                    "-"
+                }
+            }
+            td { {users().len().to_string()} }
+            td { {teams().len().to_string()} }
+            td {
+                SessionActions {
+                    on_open: move |_| { nav.push(Route::Teams { uuid }); },
+                    on_duplicate: duplicate,
+                    on_delete: delete,
+                    confirm_delete,
+                }
+            }
+        }
+    }
+}
+
+/// A session only known to the backend, e.g. created from another browser.
+#[component]
+fn RemoteSession(summary: SessionSummary, on_change: EventHandler) -> Element {
+    let nav = navigator();
+    let identity = use_identity();
+    let uuid = summary.id;
+    let organiser_key = use_persistent(format!("organiser_key_{}", uuid), || None::<Uuid>);
+
+    let confirm_delete = use_signal(|| false);
+
+    rsx! {
+        tr {
+            td { "☁️ {summary.name}" }
+            td { {format_time(summary.updated_at)} }
+            td { {summary.users.to_string()} }
+            td { {summary.teams.to_string()} }
+            td {
+                SessionActions {
+                    on_open: move |_| { nav.push(Route::Teams { uuid }); },
+                    on_duplicate: move |_| {
+                        let credentials = Credentials {
+                            identity: identity().map(|identity| identity.key),
+                            organiser_key: organiser_key(),
+                        };
+                        let copy_key = Uuid::new_v4();
+                        spawn(async move {
+                            match api::duplicate_session(uuid, copy_key, credentials).await {
+                                Ok(copy) => {
+                                    store(copy.id, "organiser_key", &Some(copy_key));
+                                    on_change.call(());
+                                }
+                                Err(err) => warn!("Could not duplicate session {}: {}", uuid, err),
+                            }
+                        });
+                    },
+                    on_delete: move |_| {
+                        let credentials = Credentials {
+                            identity: identity().map(|identity| identity.key),
+                            ..Credentials::default()
+                        };
+                        spawn(async move {
+                            match api::delete_session(uuid, credentials).await {
+                                Ok(_) => on_change.call(()),
+                                Err(err) => warn!("Could not delete session {}: {}", uuid, err),
+                            }
+                        });
+                    },
+                    confirm_delete,
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SessionActions(
+    on_open: EventHandler,
+    on_duplicate: EventHandler,
+    on_delete: EventHandler,
+    confirm_delete: Signal<bool>,
+) -> Element {
+    rsx! {
+        div { class: "buttons is-right",
+            button {
+                class: "button is-small is-link",
+                onclick: move |_| on_open.call(()),
+                "Open"
+            }
+            button {
+                class: "button is-small",
+                onclick: move |_| on_duplicate.call(()),
+                "Duplicate"
+            }
+            if confirm_delete() {
+                button {
+                    class: "button is-small is-danger",
+                    onclick: move |_| on_delete.call(()),
+                    "Confirm"
+                }
+                button {
+                    class: "button is-small is-light",
+                    onclick: move |_| confirm_delete.set(false),
+                    "Cancel"
+                }
+            } else {
+                button {
+                    class: "button is-small is-danger is-outlined",
+                    onclick: move |_| confirm_delete.set(true),
+                    "🗑️"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use dioxus::prelude::*;
+use solver::{Mechanism, Proposing, Settings, TieBreaking};
+
+/// Options of the sorting, for the organiser.
+#[component]
+pub(crate) fn SettingsPanel(
+    settings: Settings,
+    /// Names of the attributes of the users, which may order them.
+    attributes: Vec<String>,
+    on_change: EventHandler<Settings>,
+) -> Element {
+    let mechanism = settings.mechanism;
+    let tie_breaking = settings.tie_breaking;
+    let proposing = settings.proposing;
+
+    rsx! {
+        div { class: "field is-grouped is-grouped-centered mt-3",
+            div { class: "control", "style": "max-width: 24em",
+                label { class: "label", "Mechanism" }
+                div { class: "select",
+                    select {
+                        onchange: {
+                            let settings = settings.clone();
+                            move |e: FormEvent| {
+                                let mechanism = Mechanism::ALL
+                                    .into_iter()
+                                    .find(|option| format!("{option:?}") == e.value())
+                                    .unwrap_or_default();
+                                on_change.call(Settings { mechanism, ..settings.clone() });
+                            }
+                        },
+                        {Mechanism::ALL.into_iter().map(|option| rsx! {
+                            option {
+                                value: format!("{option:?}"),
+                                selected: option == mechanism,
+                                {option.label()}
+                            }
+                        })}
+                    }
+                }
+                p { class: "help", {mechanism.explanation()} }
+            }
+            if mechanism == Mechanism::PrioritySerialDictatorship {
+                div { class: "control",
+                    label { class: "label", "Order of the users" }
+                    div { class: "select",
+                        select {
+                            onchange: {
+                                let settings = settings.clone();
+                                move |e: FormEvent| {
+                                    let order_by = Some(e.value()).filter(|name| !name.is_empty());
+                                    on_change.call(Settings { order_by, ..settings.clone() });
+                                }
+                            },
+                            option { value: "", selected: settings.order_by.is_none(), "As listed" }
+                            {attributes.iter().map(|name| rsx! {
+                                option {
+                                    value: name.clone(),
+                                    selected: settings.order_by.as_ref() == Some(name),
+                                    {name.clone()}
+                                }
+                            })}
+                        }
+                    }
+                    p { class: "help", "Users with the lowest value choose first." }
+                }
+            }
+            div { class: "control",
+                label { class: "label", "Ties between users" }
+                div { class: "select",
+                    select {
+                        onchange: {
+                            let settings = settings.clone();
+                            move |e: FormEvent| {
+                                let tie_breaking = TieBreaking::ALL
+                                    .into_iter()
+                                    .find(|option| format!("{option:?}") == e.value())
+                                    .unwrap_or_default();
+                                on_change.call(Settings { tie_breaking, ..settings.clone() });
+                            }
+                        },
+                        {TieBreaking::ALL.into_iter().map(|option| rsx! {
+                            option {
+                                value: format!("{option:?}"),
+                                selected: option == tie_breaking,
+                                {option.label()}
+                            }
+                        })}
+                    }
+                }
+                p { class: "help",
+                    if tie_breaking.is_random() {
+                        "Users who gave a team the same rank are ordered by a lottery, whose seed is kept with each run."
+                    } else {
+                        "Users who gave a team the same rank are ordered as listed."
+                    }
+                }
+            }
+            if mechanism == Mechanism::GaleShapley {
+                div { class: "control",
+                    label { class: "label", "Stable matching" }
+                    div { class: "select",
+                        select {
+                            onchange: {
+                                let settings = settings.clone();
+                                move |e: FormEvent| {
+                                    let proposing = Proposing::ALL
+                                        .into_iter()
+                                        .find(|option| format!("{option:?}") == e.value())
+                                        .unwrap_or_default();
+                                    on_change.call(Settings { proposing, ..settings.clone() });
+                                }
+                            },
+                            {Proposing::ALL.into_iter().map(|option| rsx! {
+                                option {
+                                    value: format!("{option:?}"),
+                                    selected: option == proposing,
+                                    {option.label()}
+                                }
+                            })}
+                        }
+                    }
+                    p { class: "help",
+                        if proposing == Proposing::Users {
+                            "Every user gets the best team they can in a stable matching."
+                        } else {
+                            "Every team gets the users it prefers most in a stable matching."
+                        }
+                    }
+                }
+                div { class: "control",
+                    label { class: "label", "Roles" }
+                    label { class: "checkbox",
+                        input {
+                            r#type: "checkbox",
+                            class: "mr-2",
+                            checked: settings.fill_roles,
+                            onchange: {
+                                let settings = settings.clone();
+                                move |e: FormEvent| {
+                                    on_change.call(Settings { fill_roles: e.checked(), ..settings.clone() });
+                                }
+                            },
+                        }
+                        "Fill the roles required by teams first"
+                    }
+                    p { class: "help", "Users with a required skill take the role slots, then everyone else the remaining slots." }
+                }
+            }
+            div { class: "control",
+                label { class: "label", "Waitlists" }
+                label { class: "checkbox",
+                    input {
+                        r#type: "checkbox",
+                        class: "mr-2",
+                        checked: settings.waitlists,
+                        onchange: {
+                            let settings = settings.clone();
+                            move |e: FormEvent| {
+                                on_change.call(Settings { waitlists: e.checked(), ..settings.clone() });
+                            }
+                        },
+                    }
+                    "Keep waitlists once the results are published"
+                }
+                p { class: "help", "When someone withdraws, the first user waiting for their team takes the seat, and so on." }
+            }
+        }
+    }
+}
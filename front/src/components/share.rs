@@ -0,0 +1,107 @@
+use dioxus::prelude::*;
+use qrcode::render::svg;
+use qrcode::QrCode;
+use uuid::Uuid;
+
+use crate::routes::Route;
+
+/// Links, join code and QR codes to share a session.
+#[component]
+pub(crate) fn SharePanel(uuid: Uuid, join_code: Option<String>, organiser_key: Option<Uuid>) -> Element {
+    let participant = match &join_code {
+        Some(code) => Route::Join { code: code.clone() },
+        None => Route::Teams { uuid },
+    };
+
+    rsx! {
+        div { class: "box",
+            h2 { class: "title is-4", "Share" }
+            if let Some(code) = join_code {
+                p { class: "mb-4",
+                    "Join code: "
+                    span { class: "tag is-info is-large is-family-monospace", {code} }
+                }
+            }
+            div { class: "columns",
+                div { class: "column",
+                    ShareLink {
+                        label: "Participant link",
+                        help: "Participants can fill in their preferences.",
+                        route: participant,
+                    }
+                }
+                if let Some(organiser_key) = organiser_key {
+                    div { class: "column",
+                        ShareLink {
+                            label: "Organiser link",
+                            help: "Organisers can also edit the teams and sort them. Keep it private!",
+                            route: Route::Organise { uuid, organiser_key },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A link with a button to copy it and its QR code.
+#[component]
+pub(crate) fn ShareLink(label: String, help: String, route: Route) -> Element {
+    let link = absolute_url(&route);
+    let mut show_qr = use_signal(|| false);
+    let mut copied = use_signal(|| false);
+
+    rsx! {
+        label { class: "label", {label} }
+        div { class: "field has-addons",
+            div { class: "control is-expanded",
+                input { class: "input", readonly: true, value: link.clone() }
+            }
+            div { class: "control",
+                button {
+                    class: "button",
+                    onclick: {
+                        let link = link.clone();
+                        move |_| {
+                            _ = document::eval(&format!("navigator.clipboard.writeText({:?})", link));
+                            copied.set(true);
+                        }
+                    },
+                    if copied() { "Copied!" } else { "Copy" }
+                }
+            }
+            div { class: "control",
+                button {
+                    class: if show_qr() { "button is-info" } else { "button" },
+                    onclick: move |_| show_qr.set(!show_qr()),
+                    "QR code"
+                }
+            }
+        }
+        p { class: "help", {help} }
+        if show_qr() {
+            div { class: "mt-3 qr-code", dangerous_inner_html: qr_svg(&link) }
+        }
+    }
+}
+
+/// Full URL of a route, to be opened from another device.
+fn absolute_url(route: &Route) -> String {
+    let origin = web_sys::window()
+        .and_then(|window| window.location().origin().ok())
+        .unwrap_or_default();
+    format!("{}{}", origin, route)
+}
+
+/// QR code of `data`, rendered as an SVG image.
+fn qr_svg(data: &str) -> String {
+    match QrCode::new(data.as_bytes()) {
+        Ok(code) => code
+            .render::<svg::Color>()
+            .min_dimensions(200, 200)
+            .dark_color(svg::Color("#000000"))
+            .light_color(svg::Color("#ffffff"))
+            .build(),
+        Err(err) => format!("<p>Could not generate the QR code: {}</p>", err),
+    }
+}
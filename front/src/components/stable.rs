@@ -0,0 +1,90 @@
+use dioxus::prelude::*;
+use solver::{evaluate, has_lattice, median_stable_matching, stable_matchings, Assignment, Session, StableMatchings};
+
+// Beyond this, listing the matchings would not help picking one
+const LIMIT: usize = 50;
+
+/// Every stable matching of the session, from the best for users to the best for
+/// teams, for the organiser to pick a compromise.
+#[component]
+pub(crate) fn StableExplorer(
+    session: Session,
+    /// Seed of the tie-breaking lottery.
+    seed: u64,
+    /// Use a matching, with whether it is the median one.
+    on_pick: EventHandler<(Assignment, bool)>,
+) -> Element {
+    let mut found = use_signal(|| None::<(StableMatchings, Assignment)>);
+
+    let explore = {
+        let session = session.clone();
+        move |_: MouseEvent| {
+            let stable = stable_matchings(&session, seed, LIMIT);
+            let median = median_stable_matching(&session, seed, &stable);
+            found.set(Some((stable, median)));
+        }
+    };
+
+    rsx! {
+        div { class: "box",
+            div { class: "is-flex is-justify-content-space-between is-align-items-center",
+                h2 { class: "title is-4 mb-0", "Stable matchings" }
+                button { class: "button is-small", disabled: !has_lattice(&session), onclick: explore,
+                    if found().is_some() { "Refresh" } else { "Explore" }
+                }
+            }
+            p { class: "my-3 has-text-grey",
+                "When teams rank users, there may be several stable matchings: the first one is the best for users, the last one the best for teams, and the median one a compromise."
+            }
+            if !has_lattice(&session) {
+                p { class: "mb-3 has-text-warning-dark",
+                    "The stable matchings cannot be listed while users are grouped or kept apart, or teams follow balance rules or require roles."
+                }
+            } else if let Some((stable, median)) = found() {
+                if !stable.complete {
+                    p { class: "mb-3 has-text-warning-dark",
+                        {format!("Only the first {LIMIT} stable matchings are listed, and the median is taken over them.")}
+                    }
+                }
+                table { class: "table is-striped is-hoverable is-fullwidth",
+                    thead {
+                        tr {
+                            th { "#" }
+                            th { "Average choice" }
+                            th { "First choices" }
+                            th { "Unassigned" }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        {stable.matchings.iter().enumerate().map(|(idx, assignment)| {
+                            let metrics = evaluate(&session, assignment);
+                            let is_median = *assignment == median;
+                            let picked = assignment.clone();
+                            rsx! {
+                                tr { class: if is_median { "is-selected" } else { "" },
+                                    td {
+                                        "{idx + 1}"
+                                        if is_median {
+                                            span { class: "tag is-info ml-2", "median" }
+                                        }
+                                    }
+                                    td { {format!("{:.2}", metrics.average_rank)} }
+                                    td { "{metrics.first_choices()}" }
+                                    td { "{metrics.unassigned}" }
+                                    td {
+                                        button {
+                                            class: "button is-small is-link",
+                                            onclick: move |_| on_pick.call((picked.clone(), is_median)),
+                                            "Use"
+                                        }
+                                    }
+                                }
+                            }
+                        })}
+                    }
+                }
+            }
+        }
+    }
+}
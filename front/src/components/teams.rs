@@ -1,10 +1,35 @@
 #![allow(non_snake_case)]
 
 use dioxus_sdk_storage::use_persistent;
+use std::rc::Rc;
+
 use dioxus::prelude::*;
-use dioxus_logger::tracing::info;
+use dioxus_logger::tracing::{info, warn};
+use futures_util::StreamExt;
+use solver::{
+    ops, rebase, validate, Aggregates, Assignment, Attributes, Change, Constraints, Disruption, Mechanism, Promotion, Role, Roles, Run, Session, Settings, Severity, Validation,
+    MANUAL, MEDIAN_STABLE, MINIMAL_DISRUPTION, STABLE, Op, SessionResource,
+};
 use uuid::Uuid;
 
+use crate::api::{self, ApiError, Credentials};
+use crate::components::attributes::AttributesPanel;
+use crate::components::audit::AuditTimeline;
+use crate::components::conflict::{Conflict, ConflictPrompt};
+use crate::components::groups::GroupsPanel;
+use crate::components::heatmap::Heatmap;
+use crate::components::history::RunHistory;
+use crate::components::not_found::UnknownSession;
+use crate::components::priorities::PrioritiesPanel;
+use crate::components::results::Results;
+use crate::components::roles::RolesPanel;
+use crate::components::settings::SettingsPanel;
+use crate::components::share::{ShareLink, SharePanel};
+use crate::components::stable::StableExplorer;
+use crate::identity::use_identity;
+use crate::routes::Route;
+use crate::storage::{now, use_known_sessions};
+
 #[derive(PartialEq, Props, Clone)]
 pub(crate) struct TeamProps {
     uuid: Uuid,
@@ -31,71 +56,284 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
         format!("preferences_{}", props.uuid),
         || vec![vec![0; 2]; 2]
     );
-    let is_valid = use_signal(|| false);
-    let is_valid_message = use_signal(|| "Invalid dataset".to_string());
+    let mut constraints = use_persistent(
+        format!("constraints_{}", props.uuid),
+        Constraints::default
+    );
+    let mut settings = use_persistent(
+        format!("settings_{}", props.uuid),
+        Settings::default
+    );
+    let mut members = use_persistent(
+        format!("members_{}", props.uuid),
+        Vec::<Option<Uuid>>::new
+    );
+    let mut attributes = use_persistent(
+        format!("attributes_{}", props.uuid),
+        Attributes::default
+    );
+    let mut roles = use_persistent(
+        format!("roles_{}", props.uuid),
+        Roles::default
+    );
+    let mut runs = use_persistent(
+        format!("runs_{}", props.uuid),
+        Vec::<Run>::new
+    );
+    let mut updated_at = use_persistent(
+        format!("updated_at_{}", props.uuid),
+        || 0i64
+    );
+    let mut organiser_key = use_persistent(
+        format!("organiser_key_{}", props.uuid),
+        || None::<Uuid>
+    );
+    let mut known = use_known_sessions();
+    let identity = use_identity();
+    let validation = use_signal(Validation::default);
+    // Last state of the session sent to the backend or loaded from it
+    let mut saved = use_signal(|| None::<Session>);
+    // Last state of the session got from the backend, which the edits to send are
+    // found from, even after the page was closed offline
+    let mut synced = use_persistent(
+        format!("synced_{}", props.uuid),
+        || None::<Session>
+    );
+    // Version of the synced session, which edits are sent against
+    let mut version = use_persistent(
+        format!("version_{}", props.uuid),
+        || None::<i64>
+    );
+    // Edits clashing with the edits made elsewhere, until the user picks which to keep
+    let mut conflict = use_signal(|| None::<Conflict>);
+    let mut join_code = use_signal(|| None::<String>);
+    // Preferences of a private session, as the backend shows them to its organiser
+    let mut aggregates = use_signal(|| None::<Aggregates>);
+    let mut sort_error = use_signal(|| None::<String>);
+    // Seed typed in to reproduce a run
+    let mut seed_input = use_signal(String::new);
+    // Sessions unknown to this browser are looked up on the backend first
+    let mut checking = use_signal(|| !known.peek().contains(&props.uuid));
+    // Set when the session does not exist, to whether the backend was unreachable
+    let mut missing = use_signal(|| None::<bool>);
 
-    // Helper: Validate preferences
-    let mut validate_preferences = {
+    // Helper: Keys sent to the backend
+    let credentials = move || Credentials {
+        identity: identity.peek().as_ref().map(|identity| identity.key),
+        organiser_key: *organiser_key.peek(),
+    };
+
+    // Helper: Current state of the session
+    let session = {
+        let name = name.to_owned();
         let users = users.to_owned();
         let teams = teams.to_owned();
         let preferences = preferences.to_owned();
         let teams_size = teams_size.to_owned();
-        let mut is_valid = is_valid.to_owned();
-        let mut is_valid_message = is_valid_message.to_owned();
+        let constraints = constraints.to_owned();
+        let settings = settings.to_owned();
+        let members = members.to_owned();
+        let attributes = attributes.to_owned();
+        let roles = roles.to_owned();
+        move || Session {
+            name: name(),
+            users: users(),
+            teams: teams(),
+            teams_size: teams_size(),
+            preferences: preferences(),
+            constraints: constraints(),
+            settings: settings(),
+            members: members(),
+            attributes: attributes(),
+            roles: roles(),
+        }
+    };
+
+    // Helper: Validate preferences
+    let mut validate_preferences = {
+        let mut validation = validation.to_owned();
         move || {
-            let users = users();
-            let teams = teams();
-            let preferences = preferences();
-            for i in 0..users.len() {
-                for j in 0..teams.len() {
-                    // Check if preference is in range
-                    if preferences[i][j] < 1 || preferences[i][j] > teams.len() {
-                        is_valid.set(false);
-                        is_valid_message
-                            .set(format!("{} has no preference for {}", users[i], teams[j]));
-                        return;
-                    }
-                    // Check if preference is unique
-                    for k in 0..teams.len() {
-                        if preferences[i][j] == preferences[i][k] && j != k {
-                            is_valid.set(false);
-                            is_valid_message.set(format!(
-                                "{} has duplicate preferences for {} and {}",
-                                users[i], teams[j], teams[k]
-                            ));
-                            return;
-                        }
-                    }
-                }
-            }
-            for i in 0..teams.len() {
-                // Check if team size is valid
-                if teams_size()[i] < 1 || teams_size()[i] > users.len() {
-                    is_valid.set(false);
-                    is_valid_message.set(format!("{} has invalid team size", teams[i]));
-                    return;
+            validation.set(validate(&session()));
+        }
+    };
+
+    // Helper: Replace the whole session, e.g. with the one from the backend
+    let mut load_session = {
+        let mut name = name.to_owned();
+        let mut users = users.to_owned();
+        let mut teams = teams.to_owned();
+        let mut teams_size = teams_size.to_owned();
+        let mut preferences = preferences.to_owned();
+        let mut constraints = constraints.to_owned();
+        let mut settings = settings.to_owned();
+        let mut members = members.to_owned();
+        let mut attributes = attributes.to_owned();
+        let mut roles = roles.to_owned();
+        let mut validate_preferences = validate_preferences;
+        move |session: Session| {
+            saved.set(Some(session.clone()));
+            name.set(session.name);
+            users.set(session.users);
+            teams.set(session.teams);
+            teams_size.set(session.teams_size);
+            preferences.set(session.preferences);
+            constraints.set(session.constraints);
+            settings.set(session.settings);
+            members.set(session.members);
+            attributes.set(session.attributes);
+            roles.set(session.roles);
+
+            validate_preferences();
+        }
+    };
+
+    // Helper: Add the runs recorded from other browsers
+    let mut merge_runs = move |remote: Vec<Run>| {
+        runs.with_mut(|runs| {
+            for run in remote {
+                if !runs.iter().any(|known| known.id == run.id) {
+                    runs.push(run);
                 }
             }
-            let total_size: usize = teams_size().iter().sum();
-            if total_size < users.len() {
-                is_valid.set(false);
-                is_valid_message.set(format!(
-                    "Total team size ({}) is less than number of users ({})",
-                    total_size,
-                    users.len()
-                ));
+            runs.sort_by_key(|run| run.created_at);
+        })
+    };
+
+    // Helper: Take a session got from the backend as the synced one, showing it
+    // with `edits` on top
+    let mut rebase_onto = move |remote: SessionResource, edits: Vec<Op>| {
+        let mut shown = remote.session.clone();
+        ops::apply(&mut shown, &edits);
+        let pending = shown != remote.session;
+        version.set(Some(remote.version));
+        synced.set(Some(remote.session.clone()));
+        load_session(shown);
+        if pending {
+            // Saved as any other edit
+            saved.set(Some(remote.session));
+        }
+    };
+
+    // Helper: Edits not sent to the backend yet
+    let pending_edits = move || match synced.peek().as_ref() {
+        Some(base) => ops::diff(base, &session()),
+        None => Vec::new(),
+    };
+
+    // Helper: Show a session got from the backend, with the edits not sent yet on
+    // top, unless they clash with the edits made elsewhere: the user is then asked
+    // which to keep
+    let mut load_synced = move |remote: SessionResource| {
+        let edits = pending_edits();
+        if let Some(base) = synced.peek().clone() {
+            let clashes = ops::conflicts(&edits, &ops::diff(&base, &remote.session));
+            if !clashes.is_empty() {
+                conflict.set(Some(Conflict { remote, clashes }));
                 return;
             }
-            is_valid.set(true);
-            is_valid_message.set("Preferences are valid!".to_string());
         }
+        rebase_onto(remote, edits);
     };
 
+    // Helper: Settle a conflict, keeping the given edits of this page
+    let mut resolve = move |keep: fn(&[Op], &Conflict) -> Vec<Op>| {
+        let Some(conflict) = conflict.take() else {
+            return;
+        };
+        let edits = keep(&pending_edits(), &conflict);
+        rebase_onto(conflict.remote, edits);
+    };
+
+    // Keep the session in sync with the backend, one request at a time: edits are
+    // sent as operations merging with the edits made from other browsers, which
+    // are loaded again when they are made, e.g. participants filling in their
+    // preferences while the organiser watches
+    let sync = use_coroutine(move |mut messages: UnboundedReceiver<SyncEvent>| async move {
+        while let Some(message) = messages.next().await {
+            let uuid = props.uuid;
+            match message {
+                // Sent once the user picked which edits to keep
+                SyncEvent::Edited if conflict.peek().is_some() => {}
+                SyncEvent::Edited => {
+                    let sent = session();
+                    let against = *version.peek();
+                    let result = match synced.peek().clone() {
+                        Some(base) => {
+                            let edits = ops::diff(&base, &sent);
+                            if edits.is_empty() {
+                                continue;
+                            }
+                            api::edit_session(uuid, &edits, against, credentials()).await
+                        }
+                        // Not shared yet, or saved before edits were sent as operations
+                        None => api::put_session(uuid, &sent, against, credentials()).await,
+                    };
+                    match result {
+                        Ok(resource) => {
+                            join_code.set(Some(resource.join_code));
+                            aggregates.set(resource.aggregates);
+                            // The edits made meanwhile are sent next
+                            let shown = rebase(&sent, &session(), resource.session.clone());
+                            version.set(Some(resource.version));
+                            synced.set(Some(resource.session));
+                            if shown != session() {
+                                load_session(shown);
+                            }
+                        }
+                        // Edited from elsewhere before this page heard of it
+                        Err(ApiError::Conflict(remote)) => {
+                            aggregates.set(remote.aggregates.clone());
+                            load_synced(*remote);
+                        }
+                        // Sent again with the next edit
+                        Err(err) => warn!("Could not save session {}: {}", uuid, err),
+                    }
+                }
+                SyncEvent::Changed(Some(change)) if change.is_final() => missing.set(Some(false)),
+                SyncEvent::Changed(change) => {
+                    let runs_only = matches!(change, Some(Change::RunRecorded { .. } | Change::Withdrawn { .. }));
+                    if !runs_only {
+                        match api::get_session(uuid, credentials()).await {
+                            Ok(Some(resource)) => {
+                                aggregates.set(resource.aggregates.clone());
+                                load_synced(resource);
+                            }
+                            Ok(None) => missing.set(Some(false)),
+                            Err(err) => warn!("Could not reload session {}: {}", uuid, err),
+                        }
+                    }
+                    if runs_only || change.is_none() {
+                        match api::list_runs(uuid, credentials()).await {
+                            Ok(remote) => merge_runs(remote),
+                            Err(err) => warn!("Could not reload runs of session {}: {}", uuid, err),
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    // Save every edit locally and to the backend
+    use_effect(move || {
+        let session = session();
+        let first_run = saved.peek().is_none();
+        if saved.peek().as_ref() == Some(&session) {
+            return;
+        }
+        saved.set(Some(session.clone()));
+        if first_run {
+            return;
+        }
+        updated_at.set(now());
+        sync.send(SyncEvent::Edited);
+    });
+
     // Add user
     let mut add_user = {
         let mut users = users.to_owned();
         let mut preferences = preferences.to_owned();
         let teams = teams.to_owned();
+        let settings = settings.to_owned();
         let mut validate_preferences = validate_preferences;
         move || {
             let mut usr = users();
@@ -103,8 +341,9 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
             usr.push(format!("User {}", len + 1));
             users.set(usr);
 
+            // The preferences of a private session are filled in by the participants only
             let mut pref = preferences();
-            pref.push(vec![0; teams().len()]);
+            pref.push(if settings().private_preferences { Vec::new() } else { vec![0; teams().len()] });
             preferences.set(pref);
 
             validate_preferences();
@@ -128,7 +367,8 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
             teams_size.set(tms_size);
 
             let mut pref = preferences();
-            for p in pref.iter_mut() {
+            // Hidden rows of a private session stay empty, the backend extends them
+            for p in pref.iter_mut().filter(|p| !p.is_empty()) {
                 p.push(0);
             }
             preferences.set(pref);
@@ -169,6 +409,8 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
     let mut remove_user = {
         let mut users = users.to_owned();
         let mut preferences = preferences.to_owned();
+        let mut constraints = constraints.to_owned();
+        let mut members = members.to_owned();
         let mut validate_preferences = validate_preferences;
         move |idx: usize| {
             let mut usr = users();
@@ -179,6 +421,15 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
             pref.remove(idx);
             preferences.set(pref);
 
+            constraints.with_mut(|constraints| constraints.remove_user(idx));
+            attributes.with_mut(|attributes| attributes.remove_user(idx));
+            roles.with_mut(|roles| roles.remove_user(idx));
+            members.with_mut(|members| {
+                if idx < members.len() {
+                    members.remove(idx);
+                }
+            });
+
             validate_preferences();
         }
     };
@@ -186,6 +437,7 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
         let mut teams = teams.to_owned();
         let mut teams_size = teams_size.to_owned();
         let mut preferences = preferences.to_owned();
+        let mut constraints = constraints.to_owned();
         let mut validate_preferences = validate_preferences;
         move |idx: usize| {
             let mut tms = teams();
@@ -197,100 +449,340 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
             teams_size.set(tms_size);
 
             let mut pref = preferences();
-            for p in pref.iter_mut() {
+            for p in pref.iter_mut().filter(|p| idx < p.len()) {
                 p.remove(idx);
             }
             preferences.set(pref);
 
+            constraints.with_mut(|constraints| constraints.remove_team(idx));
+            roles.with_mut(|roles| roles.remove_team(idx));
+
+            validate_preferences();
+        }
+    };
+
+    // Pin a user to a team, or unpin them if already pinned there
+    let mut toggle_pin = {
+        let mut validate_preferences = validate_preferences;
+        move |user: usize, team: usize| {
+            constraints.with_mut(|constraints| {
+                let pin = if constraints.pin(user) == Some(team) { None } else { Some(team) };
+                constraints.set_pin(user, pin);
+            });
+
             validate_preferences();
         }
     };
+    let mut toggle_forbidden = {
+        let mut validate_preferences = validate_preferences;
+        move |user: usize, team: usize| {
+            constraints.with_mut(|constraints| {
+                let forbidden = !constraints.is_forbidden(user, team);
+                constraints.set_forbidden(user, team, forbidden);
+            });
+
+            validate_preferences();
+        }
+    };
+
+    // Helper: Keep a new run locally and on the backend
+    let mut record_run = move |run: Run| {
+        runs.write().push(run.clone());
+        let uuid = props.uuid;
+        let credentials = credentials();
+        spawn(async move {
+            if let Err(err) = api::create_run(uuid, &run, credentials).await {
+                warn!("Could not save run {}: {}", run.id, err);
+            }
+        });
+    };
 
     // Gale-Shapley algorithm
-    let gale_shapley_results = use_signal(|| vec![vec![]]);
+    let mut gale_shapley_results = use_signal(Assignment::default);
+    // Run shown in the results, which may have been edited since
+    let mut shown_run = use_signal(|| None::<Run>);
 
     let mut gale_shapley_loading = use_signal(|| false);
     let mut show_result_modal = use_signal(|| false);
+    // What the shown run changed from the run it was re-matched from
+    let mut disrupted = use_signal(|| None::<Disruption>);
 
     let mut gale_shapley = {
-        let users = users.to_owned();
-        let teams = teams.to_owned();
-        let teams_size = teams_size.to_owned();
-        let preferences = preferences.to_owned();
         let mut gale_shapley_results = gale_shapley_results.to_owned();
 
-        move || {
+        // Sort with a seed, or draw a new one. A run sorted again keeps the
+        // settings it was sorted with, to reproduce it.
+        move |seed: Option<u64>, rerun: Option<Run>| {
+            let mut session = session();
+            if let Some(run) = &rerun {
+                session.settings = run.settings(&session.settings);
+            }
+            sort_error.set(None);
+            disrupted.set(None);
+            if session.settings.private_preferences {
+                // Only the backend knows the preferences of a private session
+                gale_shapley_loading.set(true);
+                let uuid = props.uuid;
+                let credentials = credentials();
+                spawn(async move {
+                    match api::solve(uuid, seed, None, rerun.map(|run| run.id), credentials).await {
+                        Ok(run) => {
+                            runs.write().push(run.clone());
+                            gale_shapley_results.set(run.assignment.clone());
+                            shown_run.set(Some(run));
+                            show_result_modal.set(true);
+                        }
+                        Err(err) => sort_error.set(Some(err.to_string())),
+                    }
+                    gale_shapley_loading.set(false);
+                });
+                return;
+            }
             gale_shapley_loading.set(true);
-            let users = users();
-            let teams = teams();
-            let teams_size = teams_size();
-            let preferences = preferences();
-
-            let n_users = users.len();
-            let n_teams = teams.len();
-
-            // Build user preference lists (sorted by preference)
-            let user_prefs: Vec<Vec<usize>> = preferences
-                .iter()
-                .map(|prefs| {
-                    let mut team_indices: Vec<usize> = (0..n_teams).collect();
-                    team_indices.sort_by_key(|&t| prefs[t]);
-                    team_indices
-                })
-                .collect();
-
-            // For each team, store the indices of matched users
-            let mut team_matches: Vec<Vec<usize>> = vec![Vec::new(); n_teams];
-
-            // For each user, track which team to propose to next
-            let mut next_proposal = vec![0; n_users];
-
-            let mut free_users: Vec<usize> = (0..n_users).collect();
-
-            while let Some(&user) = free_users.first() {
-                // Propose to the next team on the user's list
-                let team = user_prefs[user][next_proposal[user]];
-                next_proposal[user] += 1;
-
-                // Add user to the team's proposals
-                team_matches[team].push(user);
-
-                // If team is over capacity, reject the least preferred
-                if team_matches[team].len() > teams_size[team] {
-                    // Sort team members by their preference for the team
-                    team_matches[team].sort_by_key(|&u| preferences[u][team]);
-                    // Remove the least preferred (last in sorted order)
-                    let rejected = team_matches[team].pop().unwrap();
-                    if rejected != user {
-                        // If the rejected user is not the current proposer, keep current user as matched
-                        free_users[0] = rejected;
-                    } else {
-                        // Current user was rejected, so they remain free
-                        continue;
+            let seed = seed.unwrap_or_else(|| Uuid::new_v4().as_u64_pair().0);
+            let assignment = solver::solve(&session, seed);
+            let recorded_seed = session.settings.uses_seed().then_some(seed);
+            let solver = session.settings.solver();
+            let run = Run::new(Uuid::new_v4(), now(), solver, recorded_seed, &session, assignment.clone());
+            record_run(run.clone());
+            shown_run.set(Some(run));
+            gale_shapley_results.set(assignment);
+            gale_shapley_loading.set(false);
+            show_result_modal.set(true);
+        }
+    };
+
+    // Re-match from the latest run, moving as few users as possible
+    let rematch_last = move |_: MouseEvent| {
+        let Some(previous) = runs().last().cloned() else {
+            return;
+        };
+        let session = session();
+        sort_error.set(None);
+        let seed = seed_input().trim().parse::<u64>().ok();
+        if session.settings.private_preferences {
+            // Only the backend knows the preferences of a private session
+            gale_shapley_loading.set(true);
+            let uuid = props.uuid;
+            let credentials = credentials();
+            spawn(async move {
+                match api::solve(uuid, seed, Some(previous.id), None, credentials).await {
+                    Ok(run) => {
+                        runs.write().push(run.clone());
+                        disrupted.set(Some(solver::disruption(&session, &previous, &run.assignment)));
+                        gale_shapley_results.set(run.assignment.clone());
+                        shown_run.set(Some(run));
+                        show_result_modal.set(true);
                     }
-                } else {
-                    // User is matched, remove from free_users
-                    free_users.remove(0);
+                    Err(err) => sort_error.set(Some(err.to_string())),
                 }
+                gale_shapley_loading.set(false);
+            });
+            return;
+        }
+        let seed = seed.unwrap_or_else(|| Uuid::new_v4().as_u64_pair().0);
+        let assignment = solver::rematch(&session, seed, &previous);
+        let recorded_seed = session.settings.tie_breaking.is_random().then_some(seed);
+        let run = Run::new(Uuid::new_v4(), now(), MINIMAL_DISRUPTION, recorded_seed, &session, assignment.clone());
+        record_run(run.clone());
+        disrupted.set(Some(solver::disruption(&session, &previous, &assignment)));
+        shown_run.set(Some(run));
+        gale_shapley_results.set(assignment);
+        show_result_modal.set(true);
+    };
+
+    // Turn the privacy on or off, then show the session as the backend does
+    let toggle_privacy = move |_: FormEvent| {
+        let mut session = session();
+        session.settings.private_preferences = !session.settings.private_preferences;
+        if session.settings.private_preferences {
+            // The organiser cannot see the preferences anymore, the backend keeps them
+            for prefs in session.preferences.iter_mut() {
+                prefs.clear();
+            }
+        }
+        load_session(session.clone());
+        updated_at.set(now());
+        let uuid = props.uuid;
+        let credentials = credentials();
+        spawn(async move {
+            // Whatever changed meanwhile, the backend keeping the preferences hidden here
+            match api::put_session(uuid, &session, None, credentials).await {
+                Ok(resource) => {
+                    aggregates.set(resource.aggregates);
+                    load_session(resource.session.clone());
+                    version.set(Some(resource.version));
+                    synced.set(Some(resource.session));
+                }
+                Err(err) => warn!("Could not save session {}: {}", uuid, err),
             }
+        });
+    };
 
-            // Store the results in the signal
-            gale_shapley_results.set(
-                team_matches
-                    .iter()
-                    .map(|members| members.iter().map(|&u| users[u].clone()).collect())
-                    .collect(),
-            );
+    // Claim a user, to fill in their preferences
+    let mut claim_user = move |user: usize| {
+        let Some(key) = identity.peek().as_ref().map(|identity| identity.key) else {
+            return;
+        };
+        members.with_mut(|members| {
+            if members.len() <= user {
+                members.resize(user + 1, None);
+            }
+            members[user] = Some(key);
+        });
+        // The row of a private session was hidden until now
+        preferences.with_mut(|preferences| {
+            if let Some(prefs) = preferences.get_mut(user).filter(|prefs| prefs.is_empty()) {
+                *prefs = vec![0; teams.peek().len()];
+            }
+        });
+        validate_preferences();
+    };
 
-            gale_shapley_loading.set(false);
-            show_result_modal.set(true);
-        }
+    // Save the results edited by hand as a new run
+    let save_edited_run = move |_: MouseEvent| {
+        let run = Run::new(Uuid::new_v4(), now(), MANUAL, None, &session(), gale_shapley_results());
+        record_run(run.clone());
+        shown_run.set(Some(run));
+    };
+
+    // Use a stable matching picked by the organiser as a new run
+    let pick_stable = move |(assignment, is_median): (Assignment, bool)| {
+        let session = session();
+        let seed = seed_input().trim().parse::<u64>().unwrap_or(0);
+        let recorded_seed = session.settings.tie_breaking.is_random().then_some(seed);
+        let solver = if is_median { MEDIAN_STABLE } else { STABLE };
+        disrupted.set(None);
+        let run = Run::new(Uuid::new_v4(), now(), solver, recorded_seed, &session, assignment.clone());
+        record_run(run.clone());
+        shown_run.set(Some(run));
+        gale_shapley_results.set(assignment);
+        show_result_modal.set(true);
+    };
+
+    // Show a past run in the results
+    let show_run = move |run: Run| {
+        disrupted.set(None);
+        gale_shapley_results.set(run.assignment.clone());
+        shown_run.set(Some(run));
+        show_result_modal.set(true);
     };
+    let edited = shown_run()
+        .is_some_and(|run| run.assignment != gale_shapley_results());
 
     use_hook(|| {
+        let uuid = props.uuid;
+        let known_locally = known.peek().contains(&uuid);
+        spawn(async move {
+            match api::get_session(uuid, credentials()).await {
+                Ok(Some(resource)) => {
+                    // Sessions opened from a code or from another browser only exist on the backend
+                    if !known_locally {
+                        known.write().push(uuid);
+                    }
+                    // Private sessions are only shown as the backend redacts them, and
+                    // the edits made here offline are merged into the session
+                    aggregates.set(resource.aggregates.clone());
+                    join_code.set(Some(resource.join_code.clone()));
+                    let role = resource.role;
+                    if !known_locally || resource.session.settings.private_preferences {
+                        load_session(resource.session.clone());
+                        version.set(Some(resource.version));
+                        synced.set(Some(resource.session));
+                        checking.set(false);
+                    } else if synced.peek().is_some() {
+                        load_synced(resource);
+                    }
+                    // Trust the backend on who organises the session
+                    if role == Role::Participant && organiser_key.peek().is_some() {
+                        organiser_key.set(None);
+                    }
+                }
+                Ok(None) if known_locally => {
+                    // Not shared yet: this browser created the session
+                    if organiser_key.peek().is_none() {
+                        organiser_key.set(Some(Uuid::new_v4()));
+                    }
+                    version.set(None);
+                    synced.set(None);
+                    sync.send(SyncEvent::Edited);
+                }
+                Ok(None) => {
+                    missing.set(Some(false));
+                    checking.set(false);
+                }
+                Err(err) => {
+                    warn!("Could not load session {}: {}", uuid, err);
+                    if !known_locally {
+                        missing.set(Some(true));
+                        checking.set(false);
+                    }
+                }
+            }
+            // Runs may have been recorded from another browser
+            match api::list_runs(uuid, credentials()).await {
+                Ok(remote) => merge_runs(remote),
+                Err(err) => warn!("Could not load runs of session {}: {}", uuid, err),
+            }
+        });
         validate_preferences();
     });
 
+    // Kept for the life of the page, the subscription closing when dropped
+    use_hook(move || {
+        Rc::new(api::follow_session(props.uuid, move |event| {
+            sync.send(SyncEvent::Changed(event.map(|event| event.change)))
+        }))
+    });
+
+    let nav = navigator();
+
+    // Helper: Create the missing session from its code
+    let create_missing = move |_: ()| {
+        organiser_key.set(Some(Uuid::new_v4()));
+        known.write().push(props.uuid);
+        missing.set(None);
+        version.set(None);
+        synced.set(None);
+        sync.send(SyncEvent::Edited);
+    };
+
+    if checking() {
+        return rsx! {
+            div { class: "container has-text-centered py-6",
+                p { class: "is-size-4", "Loading…" }
+            }
+        };
+    }
+    if let Some(unreachable) = missing() {
+        return rsx! {
+            div { class: "container py-6",
+                UnknownSession {
+                    code: props.uuid.to_string(),
+                    unreachable,
+                    on_create: create_missing,
+                    on_back: move |_| {
+                        nav.push(Route::Home {});
+                    },
+                }
+            }
+        };
+    }
+
+    let is_organiser = organiser_key().is_some();
+    let private = settings().private_preferences;
+    // User claimed by the signed-in participant
+    let own = identity().and_then(|identity| members().iter().position(|&member| member == Some(identity.key)));
+    // Participants of a private session only see their own row
+    let visible = move |idx: usize| !private || is_organiser || Some(idx) == own;
+    // Seats the signed-in participant got from a waitlist after a withdrawal
+    let promoted: Vec<Promotion> = runs()
+        .iter()
+        .filter_map(|run| run.withdrawal.as_ref())
+        .flat_map(|withdrawal| withdrawal.promotions.iter().copied())
+        .filter(|promotion| Some(promotion.user) == own)
+        .collect();
+
     // Render
     rsx! {
         div { class: "container is-fluid",
@@ -299,22 +791,80 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
                 p { "Define your teams and users, then set each user's preferences for the teams. Once everything is set, click on 'Sort teams' to see the optimal assignment based on the Gale-Shapley algorithm." }
                 p { "You can add or remove users and teams using the '➕' and '🗑️' buttons respectively. Make sure that each user has unique preferences for the teams and that team sizes are appropriate." }
                 p { "There should be at least as many total team slots as users." }
+                p { "The organiser can pin a user to a team with '📌' or forbid a team to a user with '⛔': pinned users take a slot of their team before the sorting." }
+                p { "The organiser can keep users in the same team as others, or never with someone, in the groups below." }
+                p { "Users can also be described by attributes, which the organiser can balance between teams." }
+                p { "Besides Gale-Shapley, the organiser can pick serial dictatorship, Boston, top trading cycles or probabilistic serial, each explained in the settings; with probabilistic serial, the results show each user's chances for every team." }
+                p { "When users join or leave late, 'Re-match' keeps everyone else in their team where possible and tells who had to move." }
+                p { "Once the results are published, participants can withdraw from their page, and the first users waiting for the freed seat move up if the organiser keeps waitlists." }
+                if !is_organiser {
+                    p { class: "has-text-info mt-2", "You joined as a participant: only the organiser can edit the teams and sort them." }
+                }
+                if is_organiser {
+                    label { class: "checkbox mt-3",
+                        input {
+                            r#type: "checkbox",
+                            class: "mr-2",
+                            checked: private,
+                            onchange: toggle_privacy,
+                        }
+                        "Private preferences: participants only see and fill in their own row, and you only see aggregates."
+                    }
+                    SettingsPanel {
+                        settings: settings(),
+                        attributes: attributes().names,
+                        on_change: move |new: Settings| settings.set(new),
+                    }
+                }
             },
+            if !is_organiser && own.is_none() {
+                div { class: "notification is-info mb-5 has-text-centered",
+                    p { class: "mb-3",
+                        if private { "Preferences are private in this session: " }
+                        "Pick the user you are to fill in your own preferences."
+                    }
+                    if identity().is_none() {
+                        p { "Sign in on the home page first, so that only you can edit them." }
+                    } else {
+                        div { class: "buttons is-centered",
+                            {users().iter().enumerate()
+                                .filter(|(idx, _)| members().get(*idx).copied().flatten().is_none())
+                                .map(|(idx, user)| rsx! {
+                                    button {
+                                        class: "button is-info is-light",
+                                        onclick: move |_| claim_user(idx),
+                                        {format!("I am {user}")}
+                                    }
+                                })}
+                        }
+                    }
+                }
+            }
+            {promoted.iter().map(|promotion| rsx! {
+                div { class: "notification is-success mb-5 has-text-centered",
+                    {format!(
+                        "⬆️ A seat freed up: you moved up from the waitlist to {}.",
+                        teams().get(promotion.to).cloned().unwrap_or_default()
+                    )}
+                }
+            })}
             div { class: "table-container mb-6",
                 table { class: "table is-striped is-hoverable m-auto",
                     thead {
                         tr {
                             th { class: "has-text-centered is-vcentered", "/" }
                             {teams().iter().enumerate().map(|(idx, team)| rsx! {
-                                th {
+                                th { class: background_class(validation().team(idx)),
                                     div { class: "field is-flex is-grouped",
                                         button {
                                             class: "button is-danger is-small",
+                                            disabled: !is_organiser,
                                             onclick: move |_| remove_team(idx),
                                             "🗑️"
                                         }
                                         input {
-                                            class: "input is-static",
+                                            class: "input is-static {input_class(validation().team_name(idx))}",
+                                            readonly: !is_organiser,
                                             placeholder: "Team name",
                                             oninput: move |e| rename_team(idx, e.value()),
                                             value: team.as_str()
@@ -325,6 +875,7 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
                             th { "style": "width: min-content",
                                 button {
                                     class: "button is-primary is-small",
+                                    disabled: !is_organiser,
                                     onclick: move |_| add_team(),
                                     "➕"
                                 }
@@ -335,9 +886,10 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
                         tr {
                             th { class: "has-text-centered is-vcentered", "Team size" }
                             {teams().iter().enumerate().map(|(team_idx, _)| rsx! {
-                                th {
+                                th { class: background_class(validation().team(team_idx)),
                                     input {
-                                        class: "input is-static",
+                                        class: "input is-static {input_class(validation().team_size(team_idx))}",
+                                        readonly: !is_organiser,
                                         r#type: "number",
                                         placeholder: "0",
                                         min: "1",
@@ -358,17 +910,19 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
                                 }
                             })}
                         }
-                        {users().iter().enumerate().map(|(idx, user)| rsx! {
+                        {users().iter().enumerate().filter(|&(idx, _)| visible(idx)).map(|(idx, user)| rsx! {
                             tr {
-                                td {
+                                td { class: background_class(validation().user(idx)),
                                     div { class: "field is-flex is-grouped",
                                         button {
                                             class: "button is-danger is-small",
+                                            disabled: !is_organiser,
                                             onclick: move |_| remove_user(idx),
                                             "🗑️"
                                         }
                                         input {
-                                            class: "input is-static",
+                                            class: "input is-static {input_class(validation().user_name(idx))}",
+                                            readonly: !is_organiser,
                                             placeholder: "User name",
                                             oninput: move |e| rename_user(idx, e.value()),
                                             value: user.as_str()
@@ -376,24 +930,48 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
                                     }
                                 }
                                 {teams().iter().enumerate().map(|(team_idx, _)| rsx! {
-                                    td {
-                                        input {
-                                            class: "input is-static",
-                                            r#type: "number",
-                                            placeholder: "0",
-                                            min: "1",
-                                            max: teams().len().to_string(),
-                                            value: preferences()[idx][team_idx].to_string(),
-                                            oninput: move |e| {
-                                                if let Ok(num) = e.value().parse::<usize>() {
-                                                    let mut pref = preferences();
-                                                    pref[idx][team_idx] = num;
-                                                    preferences.set(pref);
-
-                                                    validate_preferences();
-                                                } else {
-                                                    info!("Invalid input: {}", e.value());
+                                    td { class: constraint_class(&constraints(), idx, team_idx),
+                                        div { class: "field is-flex is-grouped",
+                                            if let Some(rank) = preferences().get(idx).and_then(|prefs| prefs.get(team_idx)).copied() {
+                                                input {
+                                                    class: "input is-static {input_class(validation().cell(idx, team_idx))}",
+                                                    r#type: "number",
+                                                    placeholder: "0",
+                                                    min: "1",
+                                                    max: teams().len().to_string(),
+                                                    value: rank.to_string(),
+                                                    // Participants only fill in the row of the user they claimed
+                                                    readonly: !is_organiser && Some(idx) != own,
+                                                    oninput: move |e| {
+                                                        if let Ok(num) = e.value().parse::<usize>() {
+                                                            let mut pref = preferences();
+                                                            if let Some(rank) = pref.get_mut(idx).and_then(|prefs| prefs.get_mut(team_idx)) {
+                                                                *rank = num;
+                                                            }
+                                                            preferences.set(pref);
+
+                                                            validate_preferences();
+                                                        } else {
+                                                            info!("Invalid input: {}", e.value());
+                                                        }
+                                                    }
                                                 }
+                                            } else {
+                                                span { class: "input is-static has-text-grey", title: "Hidden: preferences are private", "🔒" }
+                                            }
+                                            button {
+                                                class: if constraints().pin(idx) == Some(team_idx) {"button is-small is-info"} else {"button is-small is-white"},
+                                                disabled: !is_organiser,
+                                                title: "Pin to this team",
+                                                onclick: move |_| toggle_pin(idx, team_idx),
+                                                "📌"
+                                            }
+                                            button {
+                                                class: if constraints().is_forbidden(idx, team_idx) {"button is-small is-dark"} else {"button is-small is-white"},
+                                                disabled: !is_organiser,
+                                                title: "Forbid this team",
+                                                onclick: move |_| toggle_forbidden(idx, team_idx),
+                                                "⛔"
                                             }
                                         }
                                     }
@@ -405,6 +983,7 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
                         td {
                             button {
                                 class: "button is-primary is-small",
+                                disabled: !is_organiser,
                                 onclick: move |_| add_user(),
                                 "➕"
                             }
@@ -412,16 +991,180 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
                     }
                 }
             }
+            if let Some(aggregates) = aggregates().filter(|_| private && is_organiser) {
+                div { class: "box",
+                    h2 { class: "title is-4", "Preferences overview" }
+                    p { class: "mb-3", {format!("{}/{} users filled in their preferences.", aggregates.filled, users().len())} }
+                    table { class: "table is-striped is-fullwidth",
+                        thead {
+                            tr {
+                                th { "Team" }
+                                th { "First choices" }
+                                th { "Average rank" }
+                            }
+                        }
+                        tbody {
+                            {teams().iter().zip(aggregates.teams.iter()).map(|(team, aggregate)| rsx! {
+                                tr {
+                                    td { {team.clone()} }
+                                    td { "{aggregate.first_choices}" }
+                                    td { {format!("{:.2}", aggregate.average_rank)} }
+                                }
+                            })}
+                        }
+                    }
+                }
+            }
             div {
-                class: if is_valid() {"notification is-success mb-5"} else {"notification is-danger mb-5"},
+                class: if !validation().is_valid() {"notification is-danger mb-5"} else if validation().warning_count() > 0 {"notification is-warning mb-5"} else {"notification is-success mb-5"},
                 div { class: "is-flex is-flex-direction-row is-justify-content-center",
-                    p { class: "is-size-4 mr-5", {is_valid_message()} }
+                    p { class: "is-size-4 mr-5",
+                        if validation().issues.is_empty() {
+                            "Preferences are valid!"
+                        } else {
+                            "{validation().error_count()} error(s), {validation().warning_count()} warning(s)"
+                        }
+                    }
+                    if is_organiser && settings().uses_seed() {
+                        input {
+                            class: "input mr-2",
+                            "style": "width: 14em",
+                            r#type: "number",
+                            min: "0",
+                            placeholder: "Seed (random if empty)",
+                            value: seed_input(),
+                            oninput: move |e| seed_input.set(e.value()),
+                        }
+                    }
                     button {
-                        disabled: !is_valid(),
+                        disabled: !validation().is_valid() || !is_organiser,
                         class: if gale_shapley_loading() {"button is-loading is-link"} else {"button is-link"},
-                        onclick: move |_| gale_shapley(),
+                        onclick: move |_| gale_shapley(seed_input().trim().parse::<u64>().ok(), None),
                         "Sort teams"
                     }
+                    button {
+                        disabled: !validation().is_valid() || !is_organiser || runs().is_empty(),
+                        class: "button is-link is-light ml-2",
+                        title: "Keep everyone in their team from the last run where possible, placing users who joined since",
+                        onclick: rematch_last,
+                        "Re-match"
+                    }
+                }
+                if let Some(error) = sort_error() {
+                    p { class: "mt-3 has-text-centered", {format!("❌ The teams could not be sorted: {error}")} }
+                }
+                if !validation().issues.is_empty() {
+                    ul { class: "mt-3",
+                        {
+                            let session = Session {
+                                users: users(),
+                                teams: teams(),
+                                teams_size: teams_size(),
+                                constraints: constraints(),
+                                attributes: attributes(),
+                                roles: roles(),
+                                ..Session::default()
+                            };
+                            validation().issues.into_iter().map(move |issue| rsx! {
+                                li {
+                                    if issue.severity() == Severity::Error { "❌ " } else { "⚠️ " }
+                                    {issue.message(&session)}
+                                }
+                            })
+                        }
+                    }
+                }
+            }
+            GroupsPanel {
+                users: users(),
+                constraints: constraints(),
+                // Only the organiser sets the constraints
+                editable: is_organiser,
+                on_change: move |new: Constraints| {
+                    constraints.set(new);
+                    validate_preferences();
+                },
+            }
+            AttributesPanel {
+                users: users(),
+                teams: teams(),
+                attributes: attributes(),
+                constraints: constraints(),
+                // Participants describe the user they claimed only
+                rows: (0..users().len()).filter(|&idx| is_organiser || Some(idx) == own).collect::<Vec<_>>(),
+                editable: is_organiser || own.is_some(),
+                is_organiser,
+                on_change: move |(new_attributes, new_constraints): (Attributes, Constraints)| {
+                    attributes.set(new_attributes);
+                    constraints.set(new_constraints);
+                    validate_preferences();
+                },
+            }
+            RolesPanel {
+                users: users(),
+                teams: teams(),
+                roles: roles(),
+                rows: (0..users().len()).filter(|&idx| is_organiser || Some(idx) == own).collect::<Vec<_>>(),
+                editable: is_organiser || own.is_some(),
+                is_organiser,
+                on_change: move |new: Roles| {
+                    roles.set(new);
+                    validate_preferences();
+                },
+            }
+            if is_organiser {
+                PrioritiesPanel {
+                    users: users(),
+                    teams: teams(),
+                    constraints: constraints(),
+                    on_change: move |new: Constraints| {
+                        constraints.set(new);
+                        validate_preferences();
+                    },
+                }
+            }
+            // Only the backend knows the preferences of a private session
+            if is_organiser && !private && validation().is_valid() && !constraints().has_groups() && constraints().balance.is_empty() && (!settings().fill_roles || roles().is_empty()) && settings().mechanism == Mechanism::GaleShapley {
+                StableExplorer {
+                    session: session(),
+                    seed: seed_input().trim().parse::<u64>().unwrap_or(0),
+                    on_pick: pick_stable,
+                }
+            }
+            RunHistory {
+                uuid: props.uuid,
+                session: session(),
+                runs: runs(),
+                on_show: show_run,
+                on_rerun: move |run: Run| gale_shapley(run.seed, Some(run)),
+            }
+            SharePanel {
+                uuid: props.uuid,
+                join_code: join_code(),
+                organiser_key: organiser_key(),
+            }
+            if is_organiser {
+                AuditTimeline {
+                    uuid: props.uuid,
+                    users: users(),
+                    teams: teams(),
+                    credentials: credentials(),
+                }
+            }
+            if let Some(conflict) = conflict() {
+                ConflictPrompt {
+                    clashes: conflict.clashes,
+                    on_keep_mine: move |_| resolve(|edits, _| edits.to_vec()),
+                    on_keep_theirs: move |_| {
+                        resolve(|edits, conflict| {
+                            edits
+                                .iter()
+                                .filter(|edit| !conflict.clashes.iter().any(|(mine, _)| mine == *edit))
+                                .cloned()
+                                .collect()
+                        })
+                    },
+                    on_discard: move |_| resolve(|_, _| Vec::new()),
                 }
             }
             if show_result_modal() {
@@ -430,32 +1173,61 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
                         class: "modal-background",
                         onclick: move |_| show_result_modal.set(false),
                     }
-                    div { class: "modal-content",
+                    div { class: "modal-content", "style": "width: 90%",
                         div { class: "box",
-                            h2 { class: "title is-4", "Team ordering results" }
-                            table { class: "table is-striped is-hoverable is-fullwidth",
-                                thead {
-                                    tr {
-                                        {teams().iter().map(|team| rsx! {
-                                            th { {team.as_str()} }
-                                        })}
+                            div { class: "is-flex is-justify-content-space-between is-align-items-center mb-4",
+                                h2 { class: "title is-4 mb-0", "Team ordering results" }
+                                if is_organiser {
+                                    button {
+                                        class: "button is-primary",
+                                        disabled: !edited,
+                                        onclick: save_edited_run,
+                                        "Save as new run"
                                     }
                                 }
-                                tbody {
-                                    {
-                                        // Transpose the result matrix
-                                        let max_team_size = gale_shapley_results().iter().map(|team| team.len()).max().unwrap_or(0);
-                                        (0..max_team_size).map(|i| rsx! {
-                                            tr {
-                                                {gale_shapley_results().iter().map(|team| rsx! {
-                                                    td {
-                                                        if let Some(user) = team.get(i) {
-                                                            {user.as_str()}
-                                                        }
-                                                    }
-                                                })}
-                                            }
-                                        })
+                            }
+                            if is_organiser {
+                                p { class: "mb-4 has-text-grey", "Drag users between teams to adjust the results by hand." }
+                            }
+                            if let Some(disruption) = disrupted() {
+                                div { class: "notification is-info is-light",
+                                    p {
+                                        {format!(
+                                            "Re-matched from the last run: {} joined, {} left, {} had to move.",
+                                            disruption.joined.len(),
+                                            disruption.left.len(),
+                                            disruption.moved.len()
+                                        )}
+                                    }
+                                    {disruption.moved.iter().map(|m| {
+                                        let team = |team: Option<usize>| match team {
+                                            Some(team) => teams().get(team).cloned().unwrap_or_default(),
+                                            None => "no team".to_string(),
+                                        };
+                                        rsx! {
+                                            p { {format!("↪️ {}: {} → {}", users().get(m.user).cloned().unwrap_or_default(), team(m.from), team(m.to))} }
+                                        }
+                                    })}
+                                }
+                            }
+                            Results {
+                                session: session(),
+                                assignment: gale_shapley_results,
+                                editable: is_organiser,
+                            }
+                            if let Some(probabilities) = shown_run().and_then(|run| run.probabilities) {
+                                Heatmap {
+                                    session: session(),
+                                    probabilities,
+                                    assignment: gale_shapley_results(),
+                                }
+                            }
+                            if let Some(run) = shown_run() {
+                                if !edited {
+                                    ShareLink {
+                                        label: "Results page",
+                                        help: "Participants only see who is in which team, not the preferences.",
+                                        route: Route::PublishedResults { uuid: props.uuid, run: run.id },
                                     }
                                 }
                             }
@@ -471,3 +1243,40 @@ pub(crate) fn Teams(props: TeamProps) -> Element {
         }
     }
 }
+
+// Bulma modifier for an input holding a validation issue
+fn input_class(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Error) => "is-danger",
+        Some(Severity::Warning) => "is-warning",
+        None => "",
+    }
+}
+
+// Bulma background for a row or column holding a validation issue
+fn background_class(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Error) => "has-background-danger-light",
+        Some(Severity::Warning) => "has-background-warning-light",
+        None => "",
+    }
+}
+
+// Bulma background for a preference cell holding a constraint
+fn constraint_class(constraints: &Constraints, user: usize, team: usize) -> &'static str {
+    if constraints.pin(user) == Some(team) {
+        "has-background-info-light"
+    } else if constraints.is_forbidden(user, team) {
+        "has-background-grey-lighter"
+    } else {
+        ""
+    }
+}
+
+// What the task keeping the session in sync with the backend is told
+enum SyncEvent {
+    // The session was edited on this page
+    Edited,
+    // The session changed on the backend, `None` when changes were missed
+    Changed(Option<Change>),
+}
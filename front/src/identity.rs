@@ -0,0 +1,19 @@
+use dioxus::prelude::*;
+use dioxus_sdk_storage::use_persistent;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A signed-in user.
+///
+/// There are no passwords: the account key is a random UUID, which can be
+/// copied to sign in from another browser and find the same sessions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Identity {
+    pub key: Uuid,
+    pub name: String,
+}
+
+/// The signed-in user of this browser, if any.
+pub(crate) fn use_identity() -> Signal<Option<Identity>> {
+    use_persistent("identity", || None)
+}
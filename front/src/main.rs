@@ -1,7 +1,10 @@
 #![allow(non_snake_case)]
 
+mod api;
 mod components;
+mod identity;
 mod routes;
+mod storage;
 
 use crate::routes::Route;
 use dioxus::prelude::*;
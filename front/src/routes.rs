@@ -2,6 +2,9 @@ use dioxus::prelude::*;
 use uuid::Uuid;
 use crate::components::teams::Teams;
 use crate::components::home::Home;
+use crate::components::join::{Join, Organise};
+use crate::components::not_found::NotFound;
+use crate::components::published::PublishedResults;
 
 #[derive(Clone, Routable, Debug, PartialEq)]
 pub(crate) enum Route {
@@ -9,4 +12,12 @@ pub(crate) enum Route {
     Home {},
     #[route("/teams/:uuid")]
     Teams { uuid: Uuid },
+    #[route("/teams/:uuid/results/:run")]
+    PublishedResults { uuid: Uuid, run: Uuid },
+    #[route("/teams/:uuid/organise/:organiser_key")]
+    Organise { uuid: Uuid, organiser_key: Uuid },
+    #[route("/join/:code")]
+    Join { code: String },
+    #[route("/:..segments")]
+    NotFound { segments: Vec<String> },
 }
@@ -0,0 +1,77 @@
+//! Sessions kept in the browser's local storage.
+//!
+//! Each field of a session is persisted under its own `<field>_<uuid>` key;
+//! the `sessions` key keeps the list of the sessions known to this browser.
+
+use dioxus::prelude::*;
+use dioxus_sdk_storage::{use_persistent, SessionStorage, StorageBacking};
+use gloo_storage::Storage;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Where [`use_persistent`] keeps its values, written directly where hooks cannot
+/// be called, e.g. in event handlers.
+type Backing = SessionStorage;
+
+/// Fields persisted for each session.
+const SESSION_FIELDS: [&str; 13] = [
+    "name",
+    "users",
+    "teams",
+    "teams_size",
+    "preferences",
+    "constraints",
+    "settings",
+    "members",
+    "attributes",
+    "roles",
+    "runs",
+    "updated_at",
+    "organiser_key",
+];
+
+/// Sessions known to this browser, most recently registered last.
+pub(crate) fn use_known_sessions() -> Signal<Vec<Uuid>> {
+    use_persistent("sessions", Vec::new)
+}
+
+/// Register a session created in this browser, which then organises it.
+pub(crate) fn register_session(uuid: Uuid) {
+    new_organiser_key(uuid);
+    let key = "sessions".to_string();
+    let mut known: Vec<Uuid> = Backing::get(&key).unwrap_or_default();
+    known.push(uuid);
+    Backing::set(key, &known);
+}
+
+/// Generate and keep the organiser key of a session created in this browser.
+pub(crate) fn new_organiser_key(uuid: Uuid) -> Uuid {
+    let key = Uuid::new_v4();
+    store(uuid, "organiser_key", &Some(key));
+    key
+}
+
+/// Write a field of a session, as [`use_persistent`] would.
+pub(crate) fn store<T: Serialize + Send + Sync + Clone + 'static>(uuid: Uuid, field: &str, value: &T) {
+    Backing::set(format!("{field}_{uuid}"), value);
+}
+
+/// Remove every trace of a session from the local storage.
+pub(crate) fn forget_session(mut known: Signal<Vec<Uuid>>, uuid: Uuid) {
+    known.with_mut(|known| known.retain(|&known| known != uuid));
+    for field in SESSION_FIELDS {
+        gloo_storage::SessionStorage::delete(format!("{field}_{uuid}"));
+    }
+}
+
+/// Current time in milliseconds since the Unix epoch.
+pub(crate) fn now() -> i64 {
+    js_sys::Date::now() as i64
+}
+
+/// Format a timestamp in milliseconds with the browser's locale.
+pub(crate) fn format_time(timestamp: i64) -> String {
+    js_sys::Date::new(&(timestamp as f64).into())
+        .to_locale_string("default", &js_sys::Object::new())
+        .into()
+}
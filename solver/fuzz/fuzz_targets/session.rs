@@ -0,0 +1,35 @@
+//! Sessions parsed from arbitrary JSON, as the backend reads them from requests
+//! and the database, must be validated and sorted by every mechanism without
+//! panicking, and valid ones without overfilling a team.
+//!
+//! Run with `cargo +nightly fuzz run session` from `solver/`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solver::{evaluate, solve, validate, Mechanism, Session};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(mut session) = serde_json::from_slice::<Session>(data) else {
+        return;
+    };
+    // Keep each input fast to sort
+    if session.users.len() > 64 || session.teams.len() > 16 {
+        return;
+    }
+
+    let validation = validate(&session);
+    for issue in &validation.issues {
+        issue.message(&session);
+    }
+    session.input_hash();
+
+    for mechanism in Mechanism::ALL {
+        session.settings.mechanism = mechanism;
+        let assignment = solve(&session, 0);
+        let metrics = evaluate(&session, &assignment);
+        if validation.is_valid() {
+            assert!(metrics.over_capacity.is_empty(), "{mechanism:?} overfilled {:?}", metrics.over_capacity);
+        }
+    }
+});
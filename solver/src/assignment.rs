@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// A user whose team differs between two assignments, `None` meaning unassigned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    pub user: usize,
+    pub from: Option<usize>,
+    pub to: Option<usize>,
+}
+
+/// The outcome of a matching: which users ended up in which team.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Assignment {
+    /// `teams[team]` lists the users assigned to the team.
+    pub teams: Vec<Vec<usize>>,
+    /// Users that could not be assigned to any team, e.g. because every team was forbidden.
+    pub unassigned: Vec<usize>,
+}
+
+impl Assignment {
+    /// The team a user was assigned to, if any.
+    pub fn team_of(&self, user: usize) -> Option<usize> {
+        self.teams.iter().position(|members| members.contains(&user))
+    }
+
+    /// Whether the assignment only refers to the first `users` users and `teams` teams.
+    pub fn fits(&self, users: usize, teams: usize) -> bool {
+        self.teams.len() <= teams && self.teams.iter().flatten().chain(&self.unassigned).all(|&user| user < users)
+    }
+
+    /// Move a user to a team, or out of every team with `None`.
+    pub fn move_user(&mut self, user: usize, team: Option<usize>) {
+        for members in self.teams.iter_mut() {
+            members.retain(|&member| member != user);
+        }
+        self.unassigned.retain(|&member| member != user);
+        match team.and_then(|team| self.teams.get_mut(team)) {
+            Some(members) => members.push(user),
+            None => {
+                self.unassigned.push(user);
+                self.unassigned.sort_unstable();
+            }
+        }
+    }
+
+    /// Users whose team changed from this assignment to `other`.
+    pub fn diff(&self, other: &Assignment) -> Vec<Move> {
+        let users = self
+            .teams
+            .iter()
+            .chain(&other.teams)
+            .flatten()
+            .chain(&self.unassigned)
+            .chain(&other.unassigned)
+            .map(|&user| user + 1)
+            .max()
+            .unwrap_or(0);
+        (0..users)
+            .map(|user| Move {
+                user,
+                from: self.team_of(user),
+                to: other.team_of(user),
+            })
+            .filter(|m| m.from != m.to)
+            .collect()
+    }
+}
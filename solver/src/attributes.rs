@@ -0,0 +1,135 @@
+//! Attributes of the users, e.g. their skill level or department, and the rules
+//! balancing them between teams.
+
+use serde::{Deserialize, Serialize};
+
+/// Free-form attributes of the users.
+///
+/// `values` is indexed by user and may be shorter than the users of the session,
+/// missing or empty values meaning that the attribute was not set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attributes {
+    /// Names of the attributes, e.g. "Department".
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// `values[user][attribute]` is the value of the attribute for the user.
+    #[serde(default)]
+    pub values: Vec<Vec<String>>,
+}
+
+impl Attributes {
+    /// The value of an attribute for a user, if set.
+    pub fn value(&self, user: usize, attribute: usize) -> Option<&str> {
+        self.values
+            .get(user)
+            .and_then(|values| values.get(attribute))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+
+    pub fn set_value(&mut self, user: usize, attribute: usize, value: String) {
+        if self.values.len() <= user {
+            self.values.resize(user + 1, Vec::new());
+        }
+        let values = &mut self.values[user];
+        if values.len() <= attribute {
+            values.resize(attribute + 1, String::new());
+        }
+        values[attribute] = value;
+    }
+
+    /// Keep the values in line with an attribute removed.
+    pub fn remove(&mut self, attribute: usize) {
+        if attribute < self.names.len() {
+            self.names.remove(attribute);
+        }
+        for values in self.values.iter_mut() {
+            if attribute < values.len() {
+                values.remove(attribute);
+            }
+        }
+    }
+
+    /// Keep the values in line with a user removed from the session.
+    pub fn remove_user(&mut self, user: usize) {
+        if user < self.values.len() {
+            self.values.remove(user);
+        }
+    }
+
+    /// How many of `members` have each value of an attribute, most common first.
+    pub fn distribution(&self, members: &[usize], attribute: usize) -> Vec<(String, usize)> {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for value in members.iter().filter_map(|&user| self.value(user, attribute)) {
+            match counts.iter_mut().find(|(known, _)| known == value) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((value.to_string(), 1)),
+            }
+        }
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+}
+
+/// A rule on the attributes of the members of a team.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceRule {
+    /// Team the rule applies to, or every team.
+    #[serde(default)]
+    pub team: Option<usize>,
+    pub attribute: usize,
+    pub kind: RuleKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleKind {
+    /// At least `count` members have the value.
+    AtLeast { value: String, count: usize },
+    /// No value is held by more than `percent`% of the members, a value held by a
+    /// single member being always allowed.
+    AtMostShare { percent: usize },
+}
+
+impl BalanceRule {
+    pub fn applies_to(&self, team: usize) -> bool {
+        self.team.is_none_or(|rule_team| rule_team == team)
+    }
+
+    /// How far the members of a team are from following the rule, 0 if they do.
+    pub fn shortfall(&self, attributes: &Attributes, members: &[usize]) -> usize {
+        match &self.kind {
+            RuleKind::AtLeast { value, count } => {
+                let have = members
+                    .iter()
+                    .filter(|&&user| attributes.value(user, self.attribute) == Some(value.trim()))
+                    .count();
+                count.saturating_sub(have)
+            }
+            RuleKind::AtMostShare { percent } => {
+                let allowed = (percent * members.len() / 100).max(1);
+                attributes
+                    .distribution(members, self.attribute)
+                    .iter()
+                    .map(|(_, count)| count.saturating_sub(allowed))
+                    .sum()
+            }
+        }
+    }
+
+    /// Human-readable description of the rule.
+    pub fn describe(&self, attributes: &Attributes, teams: &[String]) -> String {
+        let attribute = attributes.names.get(self.attribute).map_or("?", String::as_str);
+        let scope = match self.team {
+            Some(team) => teams.get(team).map_or("?", String::as_str).to_string(),
+            None => "Every team".to_string(),
+        };
+        match &self.kind {
+            RuleKind::AtLeast { value, count } => {
+                format!("{scope}: at least {count} with {attribute} {value}")
+            }
+            RuleKind::AtMostShare { percent } => {
+                format!("{scope}: at most {percent}% sharing the same {attribute}")
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Audit log of the sessions: who changed what, and when.
+//!
+//! The backend records an [`AuditEntry`] for every change of a session, with
+//! the values edited found by [`edits`], so that organisers can tell who
+//! changed a preference when results are contested.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::ops::{diff, Op, Target};
+use crate::session::{Change, Role, Session};
+
+/// A change of a session, as recorded in its audit log.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Time of the change, in milliseconds since the Unix epoch.
+    pub at: i64,
+    pub actor: Actor,
+    #[serde(flatten)]
+    pub change: Change,
+    /// Values edited, for the changes of the session itself.
+    #[serde(default)]
+    pub edits: Vec<AuditEdit>,
+}
+
+/// Who made a change.
+///
+/// Account keys let their holder sign in, so they are never recorded: actors are
+/// told apart by their role and the user they claimed in the session.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub role: Role,
+    /// User of the session claimed by the actor, if any.
+    pub user: Option<String>,
+}
+
+impl Actor {
+    pub fn new(session: &Session, role: Role, identity: Option<Uuid>) -> Self {
+        Self {
+            role,
+            user: identity
+                .and_then(|identity| session.member_of(identity))
+                .and_then(|user| session.users.get(user).cloned()),
+        }
+    }
+}
+
+/// A value edited, with the user and team it is about, by name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEdit {
+    pub field: Field,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub team: Option<String>,
+    /// The value before, `None` when there was none, e.g. for a user added.
+    #[serde(default)]
+    pub before: Option<String>,
+    #[serde(default)]
+    pub after: Option<String>,
+}
+
+/// What an [`AuditEdit`] is about. Constraints, attributes, roles and settings are
+/// recorded whole, as JSON.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Name,
+    /// A user added, removed or renamed.
+    User,
+    /// A user claimed or released by a participant.
+    Member,
+    /// A team added, removed or renamed.
+    Team,
+    Size,
+    Rank,
+    /// Preferences of a private session, which are not recorded.
+    Preferences,
+    Constraints,
+    Attributes,
+    Roles,
+    Settings,
+}
+
+impl AuditEdit {
+    fn new(field: Field, before: Option<String>, after: Option<String>) -> Self {
+        Self {
+            field,
+            user: None,
+            team: None,
+            before,
+            after,
+        }
+    }
+
+    fn user(self, user: &str) -> Self {
+        Self {
+            user: Some(user.to_string()),
+            ..self
+        }
+    }
+
+    fn team(self, team: &str) -> Self {
+        Self {
+            team: Some(team.to_string()),
+            ..self
+        }
+    }
+
+    /// Whether the edit is about the user named `name`, under this name or
+    /// another one it was renamed from or to.
+    pub fn is_about_user(&self, name: &str) -> bool {
+        self.user.as_deref() == Some(name)
+            || (self.field == Field::User && [&self.before, &self.after].iter().any(|value| value.as_deref() == Some(name)))
+    }
+
+    /// Whether the edit is about the team named `name`, see [`AuditEdit::is_about_user`].
+    pub fn is_about_team(&self, name: &str) -> bool {
+        self.team.as_deref() == Some(name)
+            || (self.field == Field::Team && [&self.before, &self.after].iter().any(|value| value.as_deref() == Some(name)))
+    }
+}
+
+impl AuditEntry {
+    /// The entry with the edits about `user` and `team` only, if any: the changes
+    /// made by a user, or of their seat, are kept whole when filtering by user.
+    pub fn filtered(mut self, user: Option<&str>, team: Option<&str>) -> Option<Self> {
+        if let (Some(user), None) = (user, team) {
+            let withdrawn = matches!(&self.change, Change::Withdrawn { user: withdrawn, .. } if withdrawn == user);
+            if withdrawn || self.actor.user.as_deref() == Some(user) {
+                return Some(self);
+            }
+        }
+        if user.is_none() && team.is_none() {
+            return Some(self);
+        }
+        self.edits.retain(|edit| {
+            user.is_none_or(|user| edit.is_about_user(user)) && team.is_none_or(|team| edit.is_about_team(team))
+        });
+        (!self.edits.is_empty()).then_some(self)
+    }
+}
+
+/// The values edited from `before` to `after`, in the order of [`diff`].
+///
+/// The ranks of private sessions are not recorded, only which users changed
+/// their preferences, since the organiser reading the log may not see them.
+pub fn edits(before: &Session, after: &Session) -> Vec<AuditEdit> {
+    let private = before.settings.private_preferences || after.settings.private_preferences;
+    let claimed = |member: Option<Uuid>| member.map(|_| "claimed".to_string());
+    let rank = |rank: usize| (rank > 0).then(|| rank.to_string());
+    let mut working = before.clone();
+    let mut edits: Vec<AuditEdit> = Vec::new();
+    for op in diff(before, after) {
+        let user = |target: &Target| target.find(&working.users);
+        let team = |target: &Target| target.find(&working.teams);
+        let edit = match &op {
+            Op::SetName { name } => AuditEdit::new(Field::Name, Some(working.name.clone()), Some(name.clone())),
+            Op::AddUser { name } => AuditEdit::new(Field::User, None, Some(name.clone())).user(name),
+            Op::RemoveUser { user } => AuditEdit::new(Field::User, Some(user.name.clone()), None).user(&user.name),
+            Op::RenameUser { user, name } => {
+                AuditEdit::new(Field::User, Some(user.name.clone()), Some(name.clone())).user(&user.name)
+            }
+            Op::SetMember { user: target, member } => {
+                let before = user(target).and_then(|user| working.members.get(user).copied().flatten());
+                AuditEdit::new(Field::Member, claimed(before), claimed(*member)).user(&target.name)
+            }
+            Op::AddTeam { name, .. } => AuditEdit::new(Field::Team, None, Some(name.clone())).team(name),
+            Op::RemoveTeam { team } => AuditEdit::new(Field::Team, Some(team.name.clone()), None).team(&team.name),
+            Op::RenameTeam { team, name } => {
+                AuditEdit::new(Field::Team, Some(team.name.clone()), Some(name.clone())).team(&team.name)
+            }
+            Op::SetSize { team: target, size } => {
+                let before = team(target).and_then(|team| working.teams_size.get(team).copied());
+                AuditEdit::new(Field::Size, before.map(|size| size.to_string()), Some(size.to_string())).team(&target.name)
+            }
+            Op::SetRank { user: target, .. } if private => AuditEdit::new(Field::Preferences, None, None).user(&target.name),
+            Op::SetRank { user: user_target, team: team_target, rank: after } => {
+                let before = user(user_target)
+                    .zip(team(team_target))
+                    .and_then(|(user, team)| working.preferences.get(user)?.get(team).copied())
+                    .unwrap_or_default();
+                AuditEdit::new(Field::Rank, rank(before), rank(*after))
+                    .user(&user_target.name)
+                    .team(&team_target.name)
+            }
+            Op::SetConstraints { constraints, .. } => {
+                AuditEdit::new(Field::Constraints, json(&working.constraints), json(constraints))
+            }
+            Op::SetAttributes { attributes, .. } => {
+                AuditEdit::new(Field::Attributes, json(&working.attributes), json(attributes))
+            }
+            Op::SetRoles { roles, .. } => AuditEdit::new(Field::Roles, json(&working.roles), json(roles)),
+            Op::SetSettings { settings } => AuditEdit::new(Field::Settings, json(&working.settings), json(settings)),
+        };
+        op.apply(&mut working);
+        // Private ranks of the same user are told once
+        if !edits.contains(&edit) {
+            edits.push(edit);
+        }
+    }
+    edits
+}
+
+fn json<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_string(value).ok()
+}
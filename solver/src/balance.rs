@@ -0,0 +1,126 @@
+//! Moving users between teams until the balance rules on their attributes hold.
+//!
+//! Gale-Shapley ignores the attributes, so its assignment is repaired afterwards:
+//! each step makes the single move or swap of two users that most reduces the
+//! shortfall of the rules, and the fewest ranks lost among those. Pinned users
+//! and users in a group stay where they are.
+
+use serde::{Deserialize, Serialize};
+
+use crate::assignment::Assignment;
+use crate::session::Session;
+
+/// A balance rule that a team does not follow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleViolation {
+    pub rule: usize,
+    pub team: usize,
+}
+
+/// The balance rules broken by an assignment.
+pub fn violations(session: &Session, assignment: &Assignment) -> Vec<RuleViolation> {
+    let mut violations = Vec::new();
+    for (rule_idx, rule) in session.constraints.balance.iter().enumerate() {
+        for (team, members) in assignment.teams.iter().enumerate() {
+            if rule.applies_to(team) && rule.shortfall(&session.attributes, members) > 0 {
+                violations.push(RuleViolation { rule: rule_idx, team });
+            }
+        }
+    }
+    violations
+}
+
+fn shortfall(session: &Session, assignment: &Assignment) -> usize {
+    session
+        .constraints
+        .balance
+        .iter()
+        .map(|rule| {
+            assignment
+                .teams
+                .iter()
+                .enumerate()
+                .filter(|&(team, _)| rule.applies_to(team))
+                .map(|(_, members)| rule.shortfall(&session.attributes, members))
+                .sum::<usize>()
+        })
+        .sum()
+}
+
+/// Repair an assignment so that it follows the balance rules of the session, as
+/// much as single moves and swaps allow.
+pub fn balance(session: &Session, mut assignment: Assignment) -> Assignment {
+    if session.constraints.balance.is_empty() {
+        return assignment;
+    }
+    let constraints = &session.constraints;
+    let n_teams = session.teams.len();
+    // Rank of a team for a user, unassigned users and unknown ranks counting as last
+    let cost = |user: usize, team: Option<usize>| {
+        team.and_then(|team| session.preferences.get(user)?.get(team).copied())
+            .filter(|&rank| rank > 0)
+            .unwrap_or(n_teams + 1)
+    };
+    let movable = |user: usize| constraints.pin(user).is_none() && constraints.group_of(user).is_none();
+    // Whether a user may join the members of a team, `leaving` leaving it
+    let allowed = |user: usize, team: usize, members: &[usize], leaving: usize| {
+        !constraints.is_forbidden(user, team)
+            && !members.iter().any(|&other| other != leaving && constraints.is_apart(user, other))
+    };
+    let size = |team: usize| session.teams_size.get(team).copied().unwrap_or(0);
+
+    let mut current = shortfall(session, &assignment);
+    while current > 0 {
+        let users: Vec<usize> = (0..session.users.len()).filter(|&user| movable(user)).collect();
+        // Best candidate so far, by shortfall then by ranks lost
+        let mut best: Option<(usize, isize, Assignment)> = None;
+        let mut consider = |candidate: Assignment, lost: isize| {
+            let after = shortfall(session, &candidate);
+            let better = best
+                .as_ref()
+                .is_none_or(|(shortfall, best_lost, _)| (after, lost) < (*shortfall, *best_lost));
+            if after < current && better {
+                best = Some((after, lost, candidate));
+            }
+        };
+
+        for &user in &users {
+            let from = assignment.team_of(user);
+            for team in (0..n_teams).filter(|&team| Some(team) != from) {
+                let members = &assignment.teams[team];
+                if members.len() < size(team) && allowed(user, team, members, user) {
+                    let mut candidate = assignment.clone();
+                    candidate.move_user(user, Some(team));
+                    consider(candidate, cost(user, Some(team)) as isize - cost(user, from) as isize);
+                }
+            }
+        }
+        for (i, &a) in users.iter().enumerate() {
+            for &b in &users[i + 1..] {
+                let (Some(team_a), Some(team_b)) = (assignment.team_of(a), assignment.team_of(b)) else {
+                    continue;
+                };
+                if team_a == team_b
+                    || !allowed(a, team_b, &assignment.teams[team_b], b)
+                    || !allowed(b, team_a, &assignment.teams[team_a], a)
+                {
+                    continue;
+                }
+                let mut candidate = assignment.clone();
+                candidate.move_user(a, Some(team_b));
+                candidate.move_user(b, Some(team_a));
+                let lost = cost(a, Some(team_b)) + cost(b, Some(team_a));
+                consider(candidate, lost as isize - (cost(a, Some(team_a)) + cost(b, Some(team_b))) as isize);
+            }
+        }
+
+        match best {
+            Some((after, _, candidate)) => {
+                assignment = candidate;
+                current = after;
+            }
+            None => break,
+        }
+    }
+    assignment
+}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+
+use crate::attributes::BalanceRule;
+
+/// Manual overrides set by the organiser before matching.
+///
+/// Pins and forbidden teams are indexed by user, priorities by team, and all may
+/// be shorter than the session: missing entries mean that no constraint was set.
+/// Groups and pairs kept apart are declared by the users themselves.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Constraints {
+    /// `pins[user]` is the team the user is assigned to, whatever their preferences.
+    #[serde(default)]
+    pub pins: Vec<Option<usize>>,
+    /// `forbidden[user][team]` is set when the user must not be assigned to the team.
+    #[serde(default)]
+    pub forbidden: Vec<Vec<bool>>,
+    /// `priorities[team][user]` is the rank the team gives the user, 1 being the
+    /// user it wants most, or 0 if unranked. Teams prefer the users they ranked,
+    /// then the users that ranked them higher.
+    #[serde(default)]
+    pub priorities: Vec<Vec<usize>>,
+    /// Users who asked to be in the same team, at least two per group, each user
+    /// being in one group at most.
+    #[serde(default)]
+    pub groups: Vec<Vec<usize>>,
+    /// Pairs of users who asked not to be in the same team, the lower index first.
+    #[serde(default)]
+    pub apart: Vec<(usize, usize)>,
+    /// Rules on the attributes of the members of teams.
+    #[serde(default)]
+    pub balance: Vec<BalanceRule>,
+}
+
+impl Constraints {
+    /// The team a user is pinned to, if any.
+    pub fn pin(&self, user: usize) -> Option<usize> {
+        self.pins.get(user).copied().flatten()
+    }
+
+    pub fn is_forbidden(&self, user: usize, team: usize) -> bool {
+        self.forbidden
+            .get(user)
+            .and_then(|teams| teams.get(team))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// The rank a team gives a user, if it ranked them.
+    pub fn priority(&self, team: usize, user: usize) -> Option<usize> {
+        self.priorities
+            .get(team)
+            .and_then(|users| users.get(user))
+            .copied()
+            .filter(|&rank| rank > 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pins.iter().all(Option::is_none)
+            && self.forbidden.iter().flatten().all(|&f| !f)
+            && self.priorities.iter().flatten().all(|&rank| rank == 0)
+            && !self.has_groups()
+            && self.balance.is_empty()
+    }
+
+    /// Whether some users asked to be together or apart.
+    pub fn has_groups(&self) -> bool {
+        !self.groups.is_empty() || !self.apart.is_empty()
+    }
+
+    /// The group of a user, if they are in one.
+    pub fn group_of(&self, user: usize) -> Option<usize> {
+        self.groups.iter().position(|group| group.contains(&user))
+    }
+
+    pub fn is_apart(&self, a: usize, b: usize) -> bool {
+        self.apart.contains(&(a.min(b), a.max(b)))
+    }
+
+    /// Pin a user to a team, or unpin them with `None`.
+    pub fn set_pin(&mut self, user: usize, team: Option<usize>) {
+        if self.pins.len() <= user {
+            self.pins.resize(user + 1, None);
+        }
+        self.pins[user] = team;
+    }
+
+    pub fn set_forbidden(&mut self, user: usize, team: usize, forbidden: bool) {
+        if self.forbidden.len() <= user {
+            self.forbidden.resize(user + 1, Vec::new());
+        }
+        let teams = &mut self.forbidden[user];
+        if teams.len() <= team {
+            teams.resize(team + 1, false);
+        }
+        teams[team] = forbidden;
+    }
+
+    /// Set the rank a team gives a user, or unrank them with `None`.
+    pub fn set_priority(&mut self, team: usize, user: usize, rank: Option<usize>) {
+        if self.priorities.len() <= team {
+            self.priorities.resize(team + 1, Vec::new());
+        }
+        let users = &mut self.priorities[team];
+        if users.len() <= user {
+            users.resize(user + 1, 0);
+        }
+        users[user] = rank.unwrap_or(0);
+    }
+
+    /// Put a user in the same group as another one, merging their groups.
+    pub fn join(&mut self, user: usize, other: usize) {
+        if user == other {
+            return;
+        }
+        match (self.group_of(user), self.group_of(other)) {
+            (Some(a), Some(b)) if a == b => {}
+            (Some(a), Some(b)) => {
+                let merged = self.groups.remove(a.max(b));
+                self.groups[a.min(b)].extend(merged);
+            }
+            (Some(group), None) => self.groups[group].push(other),
+            (None, Some(group)) => self.groups[group].push(user),
+            (None, None) => self.groups.push(vec![user, other]),
+        }
+    }
+
+    /// Take a user out of their group, which is dropped if they were only two.
+    pub fn leave_group(&mut self, user: usize) {
+        for group in self.groups.iter_mut() {
+            group.retain(|&member| member != user);
+        }
+        self.groups.retain(|group| group.len() > 1);
+    }
+
+    pub fn set_apart(&mut self, a: usize, b: usize, apart: bool) {
+        let pair = (a.min(b), a.max(b));
+        self.apart.retain(|&other| other != pair);
+        if apart && a != b {
+            self.apart.push(pair);
+        }
+    }
+
+    /// Keep the constraints in line with a user removed from the session.
+    pub fn remove_user(&mut self, user: usize) {
+        if user < self.pins.len() {
+            self.pins.remove(user);
+        }
+        if user < self.forbidden.len() {
+            self.forbidden.remove(user);
+        }
+        for users in self.priorities.iter_mut() {
+            if user < users.len() {
+                users.remove(user);
+            }
+        }
+        self.leave_group(user);
+        let shift = |other: usize| if other > user { other - 1 } else { other };
+        for member in self.groups.iter_mut().flatten() {
+            *member = shift(*member);
+        }
+        self.apart.retain(|&(a, b)| a != user && b != user);
+        for (a, b) in self.apart.iter_mut() {
+            (*a, *b) = (shift(*a), shift(*b));
+        }
+    }
+
+    /// Keep the constraints in line with a team removed from the session.
+    pub fn remove_team(&mut self, team: usize) {
+        for pin in self.pins.iter_mut() {
+            *pin = match *pin {
+                Some(t) if t == team => None,
+                Some(t) if t > team => Some(t - 1),
+                pin => pin,
+            };
+        }
+        for teams in self.forbidden.iter_mut() {
+            if team < teams.len() {
+                teams.remove(team);
+            }
+        }
+        if team < self.priorities.len() {
+            self.priorities.remove(team);
+        }
+        self.balance.retain(|rule| rule.team != Some(team));
+        for rule in self.balance.iter_mut() {
+            rule.team = rule.team.map(|t| if t > team { t - 1 } else { t });
+        }
+    }
+
+    /// Keep the balance rules in line with an attribute removed from the session.
+    pub fn remove_attribute(&mut self, attribute: usize) {
+        self.balance.retain(|rule| rule.attribute != attribute);
+        for rule in self.balance.iter_mut() {
+            if rule.attribute > attribute {
+                rule.attribute -= 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::assignment::Assignment;
+use crate::balance::balance;
+use crate::constraints::Constraints;
+use crate::market::Market;
+use crate::roles::roles_first;
+use crate::session::Session;
+
+/// Name of the solver, as recorded in runs.
+pub const GALE_SHAPLEY: &str = "Gale-Shapley";
+
+/// Which side proposes in Gale-Shapley, and so gets its best stable matching.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Proposing {
+    /// User-optimal: every user gets the best team they can in a stable matching.
+    #[default]
+    Users,
+    /// Team-optimal: every team gets the best users it can in a stable matching.
+    Teams,
+}
+
+impl Proposing {
+    pub const ALL: [Proposing; 2] = [Proposing::Users, Proposing::Teams];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Proposing::Users => "Users propose (best for users)",
+            Proposing::Teams => "Teams propose (best for teams)",
+        }
+    }
+
+    /// Name of the solver, as recorded in runs.
+    pub fn solver(self) -> &'static str {
+        match self {
+            Proposing::Users => GALE_SHAPLEY,
+            Proposing::Teams => "Gale-Shapley (teams propose)",
+        }
+    }
+}
+
+/// Gale-Shapley (deferred acceptance), proposed by the side set in the session.
+///
+/// Teams prefer the users they gave a priority to, then the users that ranked
+/// them higher, ties being broken by the session's lottery drawn from `seed`.
+/// Pinned users are placed first and take a slot of their team, and users never
+/// join a team they are forbidden from: a user running out of teams stays
+/// unassigned.
+///
+/// When users asked to be together or apart, users propose by group whichever
+/// side was set, each group taking a slot per member. Otherwise, the roles
+/// required by teams may be filled first, see [`crate::roles`]. Users are then
+/// moved to follow the balance rules on their attributes, see [`crate::balance`].
+pub fn gale_shapley(session: &Session, seed: u64) -> Assignment {
+    let market = Market::new(session, seed);
+    balance(session, market.assignment(&propose(&market, session)))
+}
+
+/// The team of each user of the market in [`gale_shapley`], before balancing.
+pub(crate) fn propose(market: &Market, session: &Session) -> Vec<Option<usize>> {
+    if session.constraints.has_groups() {
+        blocks_propose(market, &session.constraints)
+    } else if session.settings.fill_roles && !session.roles.is_empty() {
+        roles_first(market, session)
+    } else {
+        match session.settings.proposing {
+            Proposing::Users => users_propose(market),
+            Proposing::Teams => teams_propose(market),
+        }
+    }
+}
+
+fn users_propose(market: &Market) -> Vec<Option<usize>> {
+    let mut team_of = vec![None; market.n_users];
+    // For each team, the users tentatively accepted on the remaining slots
+    let mut held: Vec<Vec<usize>> = vec![Vec::new(); market.n_teams];
+    // For each user, track which team to propose to next
+    let mut next_proposal = vec![0; market.n_users];
+    let mut free_users: VecDeque<usize> = (0..market.n_users)
+        .filter(|&user| market.pinned[user].is_none())
+        .collect();
+
+    while let Some(user) = free_users.pop_front() {
+        // Propose to the next team on the user's list, or stay unassigned
+        let Some(&team) = market.user_lists[user].get(next_proposal[user]) else {
+            continue;
+        };
+        next_proposal[user] += 1;
+        held[team].push(user);
+        team_of[user] = Some(team);
+
+        // If team is over capacity, reject the least preferred
+        if held[team].len() > market.capacity[team] {
+            held[team].sort_by_key(|&u| market.team_rank(team, u));
+            if let Some(rejected) = held[team].pop() {
+                team_of[rejected] = None;
+                free_users.push_front(rejected);
+            }
+        }
+    }
+
+    team_of
+}
+
+fn teams_propose(market: &Market) -> Vec<Option<usize>> {
+    let mut team_of: Vec<Option<usize>> = vec![None; market.n_users];
+    let mut members = vec![0; market.n_teams];
+    // For each team, track which user to propose to next
+    let mut next_proposal = vec![0; market.n_teams];
+
+    // Teams keep proposing while they have free slots, until none can
+    let mut proposed = true;
+    while proposed {
+        proposed = false;
+        for team in 0..market.n_teams {
+            while members[team] < market.capacity[team] {
+                let Some(&user) = market.team_lists[team].get(next_proposal[team]) else {
+                    break;
+                };
+                next_proposal[team] += 1;
+                proposed = true;
+                if team_of[user].is_some_and(|current| !market.user_prefers(user, team, current)) {
+                    continue;
+                }
+                // The user leaves a worse team, which gets a slot back
+                if let Some(current) = team_of[user] {
+                    members[current] -= 1;
+                }
+                team_of[user] = Some(team);
+                members[team] += 1;
+            }
+        }
+    }
+
+    team_of
+}
+
+/// Users propose by group: a group proposes to the teams all its members accept,
+/// preferring the lowest sum of their ranks, and takes a slot per member. Teams
+/// judge a group by the member they prefer, keep the groups they prefer that fit
+/// in their slots, and never keep two users who asked to be apart.
+///
+/// A stable matching may not exist anymore, so some groups may be left out even
+/// though a team had room for them.
+fn blocks_propose(market: &Market, constraints: &Constraints) -> Vec<Option<usize>> {
+    let blocks = &market.blocks;
+    let lists = market.block_lists();
+    let conflict = |a: usize, b: usize| {
+        blocks[a]
+            .iter()
+            .any(|&x| blocks[b].iter().any(|&y| constraints.is_apart(x, y)))
+    };
+
+    // For each team, the blocks tentatively accepted on the remaining slots
+    let mut held: Vec<Vec<usize>> = vec![Vec::new(); market.n_teams];
+    let mut next_proposal = vec![0; blocks.len()];
+    let mut free_blocks: VecDeque<usize> = (0..blocks.len()).collect();
+
+    while let Some(block) = free_blocks.pop_front() {
+        let Some(&team) = lists[block].get(next_proposal[block]) else {
+            continue;
+        };
+        next_proposal[block] += 1;
+
+        // The team keeps the blocks it prefers, as long as they fit and get along
+        let mut candidates = std::mem::take(&mut held[team]);
+        candidates.push(block);
+        candidates.sort_by_key(|&b| market.block_rank(team, b));
+        let mut used = 0;
+        for candidate in candidates {
+            let fits = used + blocks[candidate].len() <= market.capacity[team];
+            if fits && !held[team].iter().any(|&kept| conflict(candidate, kept)) {
+                used += blocks[candidate].len();
+                held[team].push(candidate);
+            } else {
+                free_blocks.push_front(candidate);
+            }
+        }
+    }
+
+    let mut team_of = vec![None; market.n_users];
+    for (team, kept) in held.iter().enumerate() {
+        for &user in kept.iter().flat_map(|&block| &blocks[block]) {
+            team_of[user] = Some(team);
+        }
+    }
+    team_of
+}
+
+/// Users propose to the places on their lists, which keep the users they rank
+/// best within their capacity.
+pub(crate) fn deferred_acceptance(
+    lists: &[Vec<usize>],
+    capacity: &[usize],
+    users: &[usize],
+    rank: impl Fn(usize, usize) -> usize,
+) -> Vec<Option<usize>> {
+    let mut place_of = vec![None; lists.len()];
+    let mut held: Vec<Vec<usize>> = vec![Vec::new(); capacity.len()];
+    let mut next_proposal = vec![0; lists.len()];
+    let mut free_users: VecDeque<usize> = users.iter().copied().collect();
+
+    while let Some(user) = free_users.pop_front() {
+        let Some(&place) = lists[user].get(next_proposal[user]) else {
+            continue;
+        };
+        next_proposal[user] += 1;
+        held[place].push(user);
+        place_of[user] = Some(place);
+
+        if held[place].len() > capacity[place] {
+            held[place].sort_by_key(|&u| rank(place, u));
+            if let Some(rejected) = held[place].pop() {
+                place_of[rejected] = None;
+                free_users.push_front(rejected);
+            }
+        }
+    }
+    place_of
+}
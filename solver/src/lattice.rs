@@ -0,0 +1,218 @@
+//! Every stable matching of a session, from the best one for users to the best
+//! one for teams, so that organisers can pick a compromise.
+//!
+//! Each team is split into seats, the first seats being preferred by users, which
+//! makes a one-to-one market with the same stable matchings. Starting from the
+//! user-optimal matching, breaking the marriage of a user (McVitie and Wilson)
+//! yields the next stable matchings down the lattice.
+
+use std::collections::HashSet;
+
+use crate::assignment::Assignment;
+use crate::market::Market;
+use crate::session::Session;
+
+/// Name of the solver of the median stable matching, as recorded in runs.
+pub const MEDIAN_STABLE: &str = "Median stable matching";
+/// Name of the solver of a stable matching picked from the lattice, as recorded in runs.
+pub const STABLE: &str = "Stable matching";
+
+/// Stable matchings of a session, sorted from the best for users to the best for teams.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StableMatchings {
+    pub matchings: Vec<Assignment>,
+    /// Whether every stable matching was found before reaching the limit.
+    pub complete: bool,
+}
+
+/// Seats of a team split into a one-to-one market.
+struct Seats<'a> {
+    market: &'a Market,
+    /// Team of each seat.
+    team: Vec<usize>,
+    /// Seats acceptable to each user, preferred first.
+    lists: Vec<Vec<usize>>,
+}
+
+/// A one-to-one matching between users and seats.
+#[derive(Clone)]
+struct Matching {
+    seat_of: Vec<Option<usize>>,
+    user_of: Vec<Option<usize>>,
+    /// Position of each user's seat in their list.
+    position: Vec<usize>,
+}
+
+impl<'a> Seats<'a> {
+    fn new(market: &'a Market) -> Self {
+        let mut team = Vec::new();
+        let mut first_seat = Vec::new();
+        for (t, &capacity) in market.capacity.iter().enumerate() {
+            first_seat.push(team.len());
+            team.extend(std::iter::repeat_n(t, capacity));
+        }
+        let lists = market
+            .user_lists
+            .iter()
+            .map(|teams| {
+                teams
+                    .iter()
+                    .flat_map(|&t| first_seat[t]..first_seat[t] + market.capacity[t])
+                    .collect()
+            })
+            .collect();
+        Self { market, team, lists }
+    }
+
+    fn seat_prefers(&self, seat: usize, a: usize, b: usize) -> bool {
+        self.market.team_prefers(self.team[seat], a, b)
+    }
+
+    /// The user-optimal matching, users proposing to seats.
+    fn user_optimal(&self) -> Matching {
+        let n_users = self.market.n_users;
+        let mut matching = Matching {
+            seat_of: vec![None; n_users],
+            user_of: vec![None; self.team.len()],
+            position: vec![0; n_users],
+        };
+        let mut free: Vec<usize> = (0..n_users).rev().collect();
+        while let Some(user) = free.pop() {
+            while let Some(&seat) = self.lists[user].get(matching.position[user]) {
+                match matching.user_of[seat] {
+                    Some(holder) if self.seat_prefers(seat, holder, user) => {
+                        matching.position[user] += 1;
+                    }
+                    holder => {
+                        if let Some(holder) = holder {
+                            matching.seat_of[holder] = None;
+                            matching.position[holder] += 1;
+                            free.push(holder);
+                        }
+                        matching.seat_of[user] = Some(seat);
+                        matching.user_of[seat] = Some(user);
+                        break;
+                    }
+                }
+            }
+        }
+        matching
+    }
+
+    /// The stable matching just below `matching` for `user`, if any: the user
+    /// leaves their seat, which then only accepts a user it prefers to them.
+    fn break_marriage(&self, matching: &Matching, user: usize) -> Option<Matching> {
+        let left = matching.seat_of[user]?;
+        let mut next = matching.clone();
+        next.seat_of[user] = None;
+        next.user_of[left] = None;
+
+        let mut proposer = user;
+        loop {
+            next.position[proposer] += 1;
+            let &seat = self.lists[proposer].get(next.position[proposer])?;
+            if seat == left {
+                if self.seat_prefers(left, proposer, user) {
+                    next.seat_of[proposer] = Some(left);
+                    next.user_of[left] = Some(proposer);
+                    return Some(next);
+                }
+                continue;
+            }
+            // Seats left empty stay so in every stable matching
+            let holder = next.user_of[seat]?;
+            if self.seat_prefers(seat, proposer, holder) {
+                next.seat_of[proposer] = Some(seat);
+                next.user_of[seat] = Some(proposer);
+                next.seat_of[holder] = None;
+                proposer = holder;
+            }
+        }
+    }
+
+    fn team_of(&self, matching: &Matching) -> Vec<Option<usize>> {
+        matching
+            .seat_of
+            .iter()
+            .map(|seat| seat.map(|seat| self.team[seat]))
+            .collect()
+    }
+}
+
+/// Whether the stable matchings of a session form a lattice. Groups, users asked
+/// to be apart, balance rules and required roles tie users together, and the
+/// lattice only exists without them.
+pub fn has_lattice(session: &Session) -> bool {
+    !session.constraints.has_groups() && session.constraints.balance.is_empty() && session.roles.is_empty()
+}
+
+/// Enumerate the stable matchings of a session, up to `limit` of them.
+///
+/// Sessions without a lattice (see [`has_lattice`]) are not enumerated: none of
+/// their matchings is returned.
+///
+/// `seed` draws the lottery breaking ties, as in [`crate::gale_shapley`].
+pub fn stable_matchings(session: &Session, seed: u64, limit: usize) -> StableMatchings {
+    if !has_lattice(session) {
+        return StableMatchings::default();
+    }
+    let market = Market::new(session, seed);
+    let seats = Seats::new(&market);
+
+    let mut found: Vec<Vec<Option<usize>>> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = vec![seats.user_optimal()];
+    let mut complete = true;
+    while let Some(matching) = pending.pop() {
+        let team_of = seats.team_of(&matching);
+        if !seen.insert(team_of.clone()) {
+            continue;
+        }
+        if found.len() == limit {
+            complete = false;
+            break;
+        }
+        found.push(team_of);
+        for user in 0..market.n_users {
+            if let Some(next) = seats.break_marriage(&matching, user) {
+                pending.push(next);
+            }
+        }
+    }
+
+    // Users are worse off down the lattice: sort by their total rank
+    let total_rank = |team_of: &[Option<usize>]| -> usize {
+        team_of
+            .iter()
+            .enumerate()
+            .filter_map(|(user, team)| team.map(|team| market.user_rank(user, team)))
+            .sum()
+    };
+    found.sort_by_key(|team_of| total_rank(team_of));
+
+    StableMatchings {
+        matchings: found.iter().map(|team_of| market.assignment(team_of)).collect(),
+        complete,
+    }
+}
+
+/// The median stable matching: every user gets the median of their teams over
+/// all the stable matchings, which is itself stable (Teo and Sethuraman).
+///
+/// It is only exact when every stable matching was found.
+pub fn median_stable_matching(session: &Session, seed: u64, stable: &StableMatchings) -> Assignment {
+    let market = Market::new(session, seed);
+    let team_of: Vec<Option<usize>> = (0..market.n_users)
+        .map(|user| {
+            let mut teams: Vec<usize> = stable
+                .matchings
+                .iter()
+                .filter_map(|matching| matching.team_of(user))
+                .filter(|_| market.pinned[user].is_none())
+                .collect();
+            teams.sort_by_key(|&team| market.user_rank(user, team));
+            teams.get(teams.len().saturating_sub(1) / 2).copied()
+        })
+        .collect();
+    market.assignment(&team_of)
+}
@@ -0,0 +1,47 @@
+//! Shared model and algorithms for Smart Teams Ordering.
+//!
+//! This crate is used by both the frontend and the backend, so it must stay
+//! free of any platform-specific dependency.
+
+pub mod assignment;
+pub mod attributes;
+pub mod audit;
+pub mod balance;
+pub mod constraints;
+pub mod gale_shapley;
+pub mod lattice;
+pub mod lottery;
+mod market;
+pub mod mechanism;
+pub mod metrics;
+pub mod ops;
+pub mod privacy;
+pub mod probabilistic;
+pub mod rematch;
+pub mod roles;
+pub mod run;
+pub mod session;
+pub mod settings;
+pub mod validation;
+pub mod waitlist;
+
+pub use assignment::{Assignment, Move};
+pub use attributes::{Attributes, BalanceRule, RuleKind};
+pub use audit::{Actor, AuditEdit, AuditEntry, Field};
+pub use balance::{balance, violations, RuleViolation};
+pub use constraints::Constraints;
+pub use gale_shapley::{gale_shapley, Proposing, GALE_SHAPLEY};
+pub use lattice::{has_lattice, median_stable_matching, stable_matchings, StableMatchings, MEDIAN_STABLE, STABLE};
+pub use lottery::{Lottery, Rng, TieBreaking};
+pub use mechanism::{solve, Mechanism};
+pub use metrics::{evaluate, BlockingPair, Metrics};
+pub use ops::{conflicts, rebase, Op, Target};
+pub use privacy::{aggregate, merge, redact, redact_run, Aggregates, TeamAggregate, HIDDEN_MEMBER};
+pub use probabilistic::{probabilistic_serial, sample, Fractional, PROBABILISTIC_SERIAL};
+pub use rematch::{disruption, rematch, Disruption, MINIMAL_DISRUPTION};
+pub use roles::{MissingRole, Requirement, Roles, ROLES_FIRST};
+pub use run::{PublishedPromotion, PublishedRun, PublishedTeam, Run, MANUAL};
+pub use session::{Change, Role, Session, SessionEvent, SessionResource, SessionSummary};
+pub use settings::Settings;
+pub use validation::{validate, Issue, IssueKind, Severity, Validation};
+pub use waitlist::{waitlists, withdraw, Promotion, Withdrawal, WAITLIST};
@@ -0,0 +1,102 @@
+//! Reproducible lotteries to break ties.
+//!
+//! Teams prefer the users that ranked them higher, so two users giving a team
+//! the same rank are tied for it. The lottery decides which one the team
+//! prefers, from a seed recorded with the run so that it can be replayed.
+
+use serde::{Deserialize, Serialize};
+
+/// How ties between users are broken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TieBreaking {
+    /// Users listed first win ties, without any randomness.
+    #[default]
+    Order,
+    /// Single tie-breaking: one lottery over users shared by every team.
+    Single,
+    /// Multiple tie-breaking: an independent lottery for each team.
+    Multiple,
+}
+
+impl TieBreaking {
+    pub const ALL: [TieBreaking; 3] = [TieBreaking::Order, TieBreaking::Single, TieBreaking::Multiple];
+
+    /// Whether the outcome depends on a seed.
+    pub fn is_random(self) -> bool {
+        self != TieBreaking::Order
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TieBreaking::Order => "Users order",
+            TieBreaking::Single => "Single lottery (STB)",
+            TieBreaking::Multiple => "Lottery per team (MTB)",
+        }
+    }
+}
+
+/// SplitMix64, a small generator whose output only depends on the seed, on
+/// every platform.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, `n` being greater than 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Position of each user in the lottery of each team, lower positions winning ties.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lottery {
+    positions: Vec<Vec<usize>>,
+}
+
+impl Lottery {
+    pub fn new(tie_breaking: TieBreaking, n_users: usize, n_teams: usize, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let mut draw = || {
+            let mut order: Vec<usize> = (0..n_users).collect();
+            rng.shuffle(&mut order);
+            let mut positions = vec![0; n_users];
+            for (position, user) in order.into_iter().enumerate() {
+                positions[user] = position;
+            }
+            positions
+        };
+        let positions = match tie_breaking {
+            TieBreaking::Order => vec![(0..n_users).collect(); n_teams],
+            TieBreaking::Single => vec![draw(); n_teams],
+            TieBreaking::Multiple => (0..n_teams).map(|_| draw()).collect(),
+        };
+        Self { positions }
+    }
+
+    pub fn position(&self, team: usize, user: usize) -> usize {
+        self.positions
+            .get(team)
+            .and_then(|positions| positions.get(user))
+            .copied()
+            .unwrap_or(user)
+    }
+}
@@ -0,0 +1,202 @@
+use crate::assignment::Assignment;
+use crate::lottery::Lottery;
+use crate::session::Session;
+
+/// The strict preferences of both sides of a session, as the matching algorithms see them.
+///
+/// Pinned users are out of the market and only take a slot of their team, along
+/// with the rest of their group. Forbidden teams, and teams where a user is pinned
+/// that another one asked to be apart from, are left out of the lists. Teams prefer the users they gave a
+/// priority to, then the users that ranked them higher, ties being broken by the
+/// lottery.
+pub(crate) struct Market {
+    pub n_users: usize,
+    pub n_teams: usize,
+    /// Slots left in each team once pinned users took theirs.
+    pub capacity: Vec<usize>,
+    pub pinned: Vec<Option<usize>>,
+    /// Users who are not pinned, by group: the users out of any group are alone in theirs.
+    pub blocks: Vec<Vec<usize>>,
+    /// Teams acceptable to each user, preferred first.
+    pub user_lists: Vec<Vec<usize>>,
+    /// Users each team may get, preferred first.
+    pub team_lists: Vec<Vec<usize>>,
+    /// `user_rank[user][team]`: position of the team in the user's list.
+    user_rank: Vec<Vec<usize>>,
+    /// `team_rank[team][user]`: position of the user in the team's list.
+    team_rank: Vec<Vec<usize>>,
+}
+
+/// Groups of a session, those naming users out of the session being left out.
+fn groups(session: &Session) -> Vec<&Vec<usize>> {
+    let n_users = session.users.len();
+    session
+        .constraints
+        .groups
+        .iter()
+        .filter(|group| group.iter().all(|&user| user < n_users))
+        .collect()
+}
+
+/// The team each user ends in whatever the sorting: the team they are pinned to,
+/// or the one a member of their group is pinned to.
+pub(crate) fn pinned(session: &Session) -> Vec<Option<usize>> {
+    let n_teams = session.teams.len();
+    let constraints = &session.constraints;
+    let mut pinned: Vec<Option<usize>> = (0..session.users.len())
+        .map(|user| constraints.pin(user).filter(|&team| team < n_teams))
+        .collect();
+    for group in groups(session) {
+        if let Some(team) = group.iter().find_map(|&user| pinned[user]) {
+            for &user in group.iter() {
+                pinned[user] = Some(team);
+            }
+        }
+    }
+    pinned
+}
+
+impl Market {
+    pub fn new(session: &Session, seed: u64) -> Self {
+        let n_users = session.users.len();
+        let n_teams = session.teams.len();
+        let constraints = &session.constraints;
+        let lottery = Lottery::new(session.settings.tie_breaking, n_users, n_teams, seed);
+        let rank = |user: usize, team: usize| {
+            session
+                .preferences
+                .get(user)
+                .and_then(|prefs| prefs.get(team))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        let pinned = pinned(session);
+        let groups = groups(session);
+        let mut blocks: Vec<Vec<usize>> = groups
+            .iter()
+            .filter(|group| group.iter().all(|&user| pinned[user].is_none()))
+            .map(|group| group.to_vec())
+            .collect();
+        blocks.extend(
+            (0..n_users)
+                .filter(|&user| pinned[user].is_none() && !groups.iter().any(|group| group.contains(&user)))
+                .map(|user| vec![user]),
+        );
+        let mut capacity: Vec<usize> = (0..n_teams)
+            .map(|team| session.teams_size.get(team).copied().unwrap_or(0))
+            .collect();
+        // Teams with more pinned users than slots are reported by the validation,
+        // which keeps the session from being sorted, and take nobody else
+        for &team in pinned.iter().flatten() {
+            capacity[team] = capacity[team].saturating_sub(1);
+        }
+
+        let acceptable = |user: usize, team: usize| {
+            pinned[user].is_none()
+                && !constraints.is_forbidden(user, team)
+                && !(0..n_users).any(|other| pinned[other] == Some(team) && constraints.is_apart(user, other))
+        };
+        let user_lists: Vec<Vec<usize>> = (0..n_users)
+            .map(|user| {
+                let mut teams: Vec<usize> = (0..n_teams).filter(|&team| acceptable(user, team)).collect();
+                teams.sort_by_key(|&team| rank(user, team));
+                teams
+            })
+            .collect();
+        let team_lists: Vec<Vec<usize>> = (0..n_teams)
+            .map(|team| {
+                let mut users: Vec<usize> = (0..n_users).filter(|&user| acceptable(user, team)).collect();
+                users.sort_by_key(|&user| {
+                    (
+                        constraints.priority(team, user).unwrap_or(usize::MAX),
+                        rank(user, team),
+                        lottery.position(team, user),
+                    )
+                });
+                users
+            })
+            .collect();
+
+        let mut user_rank = vec![vec![usize::MAX; n_teams]; n_users];
+        for (user, teams) in user_lists.iter().enumerate() {
+            for (position, &team) in teams.iter().enumerate() {
+                user_rank[user][team] = position;
+            }
+        }
+        let mut team_rank = vec![vec![usize::MAX; n_users]; n_teams];
+        for (team, users) in team_lists.iter().enumerate() {
+            for (position, &user) in users.iter().enumerate() {
+                team_rank[team][user] = position;
+            }
+        }
+
+        Self {
+            n_users,
+            n_teams,
+            capacity,
+            pinned,
+            blocks,
+            user_lists,
+            team_lists,
+            user_rank,
+            team_rank,
+        }
+    }
+
+    /// Whether `user` would rather be in team `a` than in team `b`.
+    pub fn user_prefers(&self, user: usize, a: usize, b: usize) -> bool {
+        self.user_rank[user][a] < self.user_rank[user][b]
+    }
+
+    /// Whether `team` would rather have user `a` than user `b`.
+    pub fn team_prefers(&self, team: usize, a: usize, b: usize) -> bool {
+        self.team_rank[team][a] < self.team_rank[team][b]
+    }
+
+    pub fn user_rank(&self, user: usize, team: usize) -> usize {
+        self.user_rank[user][team]
+    }
+
+    pub fn team_rank(&self, team: usize, user: usize) -> usize {
+        self.team_rank[team][user]
+    }
+
+    /// Teams acceptable to every member of each block, preferred first by the
+    /// lowest sum of their ranks.
+    pub fn block_lists(&self) -> Vec<Vec<usize>> {
+        self.blocks
+            .iter()
+            .map(|block| {
+                let mut teams: Vec<usize> = (0..self.n_teams)
+                    .filter(|&team| block.iter().all(|&user| self.user_rank[user][team] != usize::MAX))
+                    .collect();
+                teams.sort_by_key(|&team| block.iter().map(|&user| self.user_rank[user][team]).sum::<usize>());
+                teams
+            })
+            .collect()
+    }
+
+    /// How much a team wants a block: the position of the member it prefers.
+    pub fn block_rank(&self, team: usize, block: usize) -> usize {
+        self.blocks[block]
+            .iter()
+            .map(|&user| self.team_rank[team][user])
+            .min()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// The assignment where each user of the market is in `team_of[user]`, with
+    /// the pinned users in their team.
+    pub fn assignment(&self, team_of: &[Option<usize>]) -> Assignment {
+        let mut teams = vec![Vec::new(); self.n_teams];
+        let mut unassigned = Vec::new();
+        for user in 0..self.n_users {
+            match self.pinned[user].or(team_of.get(user).copied().flatten()) {
+                Some(team) => teams[team].push(user),
+                None => unassigned.push(user),
+            }
+        }
+        Assignment { teams, unassigned }
+    }
+}
@@ -0,0 +1,271 @@
+//! How the teams are sorted: Gale-Shapley, or a mechanism for organisations
+//! with other policies, e.g. first-come-first-served, seniority, or school-choice
+//! style assignments.
+//!
+//! Every mechanism but probabilistic serial places the users by block, a group
+//! taking a slot per member in a team all its members accept, and never puts
+//! users who asked to be apart in the same team. Probabilistic serial draws each
+//! user on their own, ignoring both, which [`crate::validate`] warns about. Users
+//! are then moved to follow the balance rules on their attributes, as after
+//! Gale-Shapley.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::assignment::Assignment;
+use crate::balance::balance;
+use crate::constraints::Constraints;
+use crate::gale_shapley::{propose, GALE_SHAPLEY};
+use crate::lottery::Rng;
+use crate::market::Market;
+use crate::probabilistic::{probabilistic_serial, sample, PROBABILISTIC_SERIAL};
+use crate::session::Session;
+
+pub const RANDOM_SERIAL_DICTATORSHIP: &str = "Random serial dictatorship";
+pub const PRIORITY_SERIAL_DICTATORSHIP: &str = "Priority serial dictatorship";
+pub const BOSTON: &str = "Boston (immediate acceptance)";
+pub const TOP_TRADING_CYCLES: &str = "Top trading cycles";
+
+/// How the teams are sorted, chosen per session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mechanism {
+    #[default]
+    GaleShapley,
+    RandomSerialDictatorship,
+    PrioritySerialDictatorship,
+    Boston,
+    TopTradingCycles,
+    ProbabilisticSerial,
+}
+
+impl Mechanism {
+    pub const ALL: [Mechanism; 6] = [
+        Mechanism::GaleShapley,
+        Mechanism::RandomSerialDictatorship,
+        Mechanism::PrioritySerialDictatorship,
+        Mechanism::Boston,
+        Mechanism::TopTradingCycles,
+        Mechanism::ProbabilisticSerial,
+    ];
+
+    /// Name of the mechanism, as recorded in runs. Gale-Shapley is named after
+    /// its own settings, see [`crate::Settings::solver`].
+    pub fn label(self) -> &'static str {
+        match self {
+            Mechanism::GaleShapley => GALE_SHAPLEY,
+            Mechanism::RandomSerialDictatorship => RANDOM_SERIAL_DICTATORSHIP,
+            Mechanism::PrioritySerialDictatorship => PRIORITY_SERIAL_DICTATORSHIP,
+            Mechanism::Boston => BOSTON,
+            Mechanism::TopTradingCycles => TOP_TRADING_CYCLES,
+            Mechanism::ProbabilisticSerial => PROBABILISTIC_SERIAL,
+        }
+    }
+
+    /// What the mechanism does, and what it guarantees, for the organiser.
+    pub fn explanation(self) -> &'static str {
+        match self {
+            Mechanism::GaleShapley => {
+                "Users propose to teams, which keep the users they prefer. No user and team would rather be together, and when users propose, no user gains by lying about their preferences."
+            }
+            Mechanism::RandomSerialDictatorship => {
+                "Users pick their favourite team with room left, one after another in an order drawn by lot. Simple and fair in expectation, but teams' priorities are ignored."
+            }
+            Mechanism::PrioritySerialDictatorship => {
+                "Users pick their favourite team with room left, one after another, in the order of the attribute chosen below (e.g. a sign-up date or seniority), or the order of the list. First come, first served."
+            }
+            Mechanism::Boston => {
+                "Teams take in priority order the users who ranked them first, then second, and so on, and never give a seat back. Many users get their first choice, but ranking a popular team first is risky, so users gain by lying about their preferences."
+            }
+            Mechanism::TopTradingCycles => {
+                "Users point to their favourite team with room left, and teams to the user they give the highest priority; users in a cycle get the team they point to. No group of users could trade seats to do better, but users and teams may still rather be together."
+            }
+            Mechanism::ProbabilisticSerial => {
+                "Users eat from the share of their favourite team left, all at the same speed, which gives each of them a probability of ending in each team; the teams are then drawn by lot with these probabilities. No user envies the probabilities of another, but groups and apart pairs are ignored."
+            }
+        }
+    }
+
+    /// Whether the mechanism draws lots, whose seed is then kept with each run.
+    pub fn is_random(self) -> bool {
+        matches!(self, Mechanism::RandomSerialDictatorship | Mechanism::ProbabilisticSerial)
+    }
+}
+
+/// Sort the teams of a session with its mechanism, `seed` drawing the lottery.
+pub fn solve(session: &Session, seed: u64) -> Assignment {
+    let market = Market::new(session, seed);
+    let constraints = &session.constraints;
+    let team_of = match session.settings.mechanism {
+        Mechanism::GaleShapley => propose(&market, session),
+        Mechanism::RandomSerialDictatorship => {
+            let mut order: Vec<usize> = (0..market.blocks.len()).collect();
+            Rng::new(seed).shuffle(&mut order);
+            serial_dictatorship(&market, constraints, &order)
+        }
+        Mechanism::PrioritySerialDictatorship => {
+            serial_dictatorship(&market, constraints, &priority_order(&market, session))
+        }
+        Mechanism::Boston => boston(&market, constraints),
+        Mechanism::TopTradingCycles => top_trading_cycles(&market, constraints),
+        Mechanism::ProbabilisticSerial => {
+            let sampled = sample(&probabilistic_serial(session), market.n_teams, seed);
+            (0..market.n_users).map(|user| sampled.team_of(user)).collect()
+        }
+    };
+    balance(session, market.assignment(&team_of))
+}
+
+/// Whether a block would join someone its members asked to be apart from.
+fn clashes(market: &Market, constraints: &Constraints, team_of: &[Option<usize>], block: usize, team: usize) -> bool {
+    market.blocks[block].iter().any(|&user| {
+        (0..market.n_users).any(|other| team_of[other] == Some(team) && constraints.is_apart(user, other))
+    })
+}
+
+/// Whether a block can join a team: it has room for every member, and no one
+/// they asked to be apart from.
+fn fits(market: &Market, constraints: &Constraints, team_of: &[Option<usize>], left: &[usize], block: usize, team: usize) -> bool {
+    left[team] >= market.blocks[block].len() && !clashes(market, constraints, team_of, block, team)
+}
+
+fn place(market: &Market, team_of: &mut [Option<usize>], left: &mut [usize], block: usize, team: usize) {
+    left[team] -= market.blocks[block].len();
+    for &user in &market.blocks[block] {
+        team_of[user] = Some(team);
+    }
+}
+
+/// Blocks in the order of the attribute named in the settings, lowest value
+/// first, numbers being compared as such. Users without a value come last, and
+/// ties keep the order of the list. A block comes at the place of its first member.
+fn priority_order(market: &Market, session: &Session) -> Vec<usize> {
+    let attribute = session
+        .settings
+        .order_by
+        .as_ref()
+        .and_then(|name| session.attributes.names.iter().position(|other| other == name));
+    let value = |user: usize| attribute.and_then(|attribute| session.attributes.value(user, attribute));
+    let compare = |a: usize, b: usize| {
+        let by_value = match (value(a), value(b)) {
+            (Some(x), Some(y)) => match (x.parse::<f64>(), y.parse::<f64>()) {
+                (Ok(x), Ok(y)) => x.total_cmp(&y),
+                _ => x.cmp(y),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        by_value.then(a.cmp(&b))
+    };
+    let first = |block: usize| {
+        market.blocks[block]
+            .iter()
+            .copied()
+            .min_by(|&a, &b| compare(a, b))
+            .unwrap_or(0)
+    };
+    let mut order: Vec<usize> = (0..market.blocks.len()).collect();
+    order.sort_by(|&a, &b| compare(first(a), first(b)));
+    order
+}
+
+/// Blocks pick, in turn, the team they prefer among those they fit in.
+fn serial_dictatorship(market: &Market, constraints: &Constraints, order: &[usize]) -> Vec<Option<usize>> {
+    let lists = market.block_lists();
+    let mut team_of = vec![None; market.n_users];
+    let mut left = market.capacity.clone();
+    for &block in order {
+        let team = lists[block]
+            .iter()
+            .copied()
+            .find(|&team| fits(market, constraints, &team_of, &left, block, team));
+        if let Some(team) = team {
+            place(market, &mut team_of, &mut left, block, team);
+        }
+    }
+    team_of
+}
+
+/// In round `k`, teams accept for good the blocks that put them `k`-th on their
+/// list, in their priority order, while they fit.
+fn boston(market: &Market, constraints: &Constraints) -> Vec<Option<usize>> {
+    let lists = market.block_lists();
+    let mut team_of = vec![None; market.n_users];
+    let mut left = market.capacity.clone();
+    let mut placed = vec![false; market.blocks.len()];
+    let rounds = lists.iter().map(Vec::len).max().unwrap_or(0);
+    for round in 0..rounds {
+        for team in 0..market.n_teams {
+            let mut applicants: Vec<usize> = (0..market.blocks.len())
+                .filter(|&block| !placed[block] && lists[block].get(round) == Some(&team))
+                .collect();
+            applicants.sort_by_key(|&block| market.block_rank(team, block));
+            for block in applicants {
+                if fits(market, constraints, &team_of, &left, block, team) {
+                    place(market, &mut team_of, &mut left, block, team);
+                    placed[block] = true;
+                }
+            }
+        }
+    }
+    team_of
+}
+
+/// Blocks point to the team they prefer among those they fit in, and teams to
+/// the block they prefer among those that fit; the blocks on a cycle get the team
+/// they point to, and the others point again. Blocks fitting nowhere stay out.
+fn top_trading_cycles(market: &Market, constraints: &Constraints) -> Vec<Option<usize>> {
+    let lists = market.block_lists();
+    let n_blocks = market.blocks.len();
+    let mut team_of = vec![None; market.n_users];
+    let mut left = market.capacity.clone();
+    let mut done = vec![false; n_blocks];
+    loop {
+        let block_points: Vec<Option<usize>> = (0..n_blocks)
+            .map(|block| {
+                if done[block] {
+                    return None;
+                }
+                lists[block]
+                    .iter()
+                    .copied()
+                    .find(|&team| fits(market, constraints, &team_of, &left, block, team))
+            })
+            .collect();
+        for block in 0..n_blocks {
+            done[block] |= block_points[block].is_none();
+        }
+        let Some(start) = (0..n_blocks).find(|&block| !done[block]) else {
+            break;
+        };
+        // Every team pointed to fits the block pointing to it, so it points to a block too
+        let team_points: Vec<Option<usize>> = (0..market.n_teams)
+            .map(|team| {
+                (0..n_blocks)
+                    .filter(|&block| !done[block] && lists[block].contains(&team))
+                    .filter(|&block| fits(market, constraints, &team_of, &left, block, team))
+                    .min_by_key(|&block| market.block_rank(team, block))
+            })
+            .collect();
+
+        // Follow the pointers until a block comes back
+        let mut path: Vec<usize> = Vec::new();
+        let mut block = start;
+        while !path.contains(&block) {
+            path.push(block);
+            let Some(next) = block_points[block].and_then(|team| team_points[team]) else {
+                break;
+            };
+            block = next;
+        }
+        let cycle_start = path.iter().position(|&other| other == block).unwrap_or(0);
+        for &block in &path[cycle_start..] {
+            if let Some(team) = block_points[block] {
+                place(market, &mut team_of, &mut left, block, team);
+                done[block] = true;
+            }
+        }
+    }
+    team_of
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+use crate::assignment::Assignment;
+use crate::balance::{violations, RuleViolation};
+use crate::roles::MissingRole;
+use crate::session::Session;
+
+/// A user and a team that would both rather be together than keep the assignment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockingPair {
+    pub user: usize,
+    pub team: usize,
+}
+
+/// How good an assignment is, and which guarantees it breaks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+    pub blocking_pairs: Vec<BlockingPair>,
+    /// Teams with more members than slots.
+    pub over_capacity: Vec<usize>,
+    /// Users assigned against their pin or to a team they are forbidden from.
+    pub constraint_violations: Vec<usize>,
+    /// Groups whose members are not all in the same team.
+    #[serde(default)]
+    pub split_groups: Vec<usize>,
+    /// Pairs of users who asked to be apart but are in the same team.
+    #[serde(default)]
+    pub apart_together: Vec<(usize, usize)>,
+    /// Balance rules on the users' attributes that teams do not follow.
+    #[serde(default)]
+    pub rule_violations: Vec<RuleViolation>,
+    /// Roles required by teams that their members do not fill.
+    #[serde(default)]
+    pub missing_roles: Vec<MissingRole>,
+    /// `rank_counts[r]` is the number of users assigned to their choice `r + 1`.
+    pub rank_counts: Vec<usize>,
+    /// Average rank of the assigned users' teams, 1 being everyone's first choice.
+    pub average_rank: f64,
+    pub unassigned: usize,
+}
+
+impl Metrics {
+    /// An assignment is stable when it has no blocking pair and breaks no rule.
+    pub fn is_stable(&self) -> bool {
+        self.blocking_pairs.is_empty()
+            && self.over_capacity.is_empty()
+            && self.constraint_violations.is_empty()
+            && self.split_groups.is_empty()
+            && self.apart_together.is_empty()
+            && self.rule_violations.is_empty()
+            && self.missing_roles.is_empty()
+    }
+
+    pub fn first_choices(&self) -> usize {
+        self.rank_counts.first().copied().unwrap_or(0)
+    }
+}
+
+/// Measure an assignment of a session, which may have been edited by hand.
+///
+/// Teams prefer the users they gave a priority to, then the users that ranked
+/// them higher, and pinned users cannot be displaced, as in [`crate::gale_shapley`].
+/// Users in a group cannot leave it alone, so they are in no blocking pair, and
+/// users do not block with a team holding someone they asked to be apart from.
+pub fn evaluate(session: &Session, assignment: &Assignment) -> Metrics {
+    let n_teams = session.teams.len();
+    let constraints = &session.constraints;
+    let rank = |user: usize, team: usize| {
+        session
+            .preferences
+            .get(user)
+            .and_then(|prefs| prefs.get(team))
+            .copied()
+            .unwrap_or(0)
+    };
+    // How much a team wants a user, lower being better
+    let priority = |team: usize, user: usize| {
+        (constraints.priority(team, user).unwrap_or(usize::MAX), rank(user, team))
+    };
+    let size = |team: usize| session.teams_size.get(team).copied().unwrap_or(0);
+    // Pins to a team out of the session are ignored, as when sorting
+    let pin = |user: usize| constraints.pin(user).filter(|&team| team < n_teams);
+
+    let mut metrics = Metrics {
+        rank_counts: vec![0; n_teams],
+        ..Metrics::default()
+    };
+    let mut assigned = 0;
+    let mut total_rank = 0;
+
+    for (team, members) in assignment.teams.iter().enumerate() {
+        if members.len() > size(team) {
+            metrics.over_capacity.push(team);
+        }
+        for &user in members {
+            let rank = rank(user, team);
+            if let Some(count) = rank.checked_sub(1).and_then(|r| metrics.rank_counts.get_mut(r)) {
+                *count += 1;
+            }
+            assigned += 1;
+            total_rank += rank;
+            let pinned_elsewhere = pin(user).is_some_and(|pin| pin != team);
+            if pinned_elsewhere || constraints.is_forbidden(user, team) {
+                metrics.constraint_violations.push(user);
+            }
+        }
+    }
+    for &user in &assignment.unassigned {
+        if pin(user).is_some() {
+            metrics.constraint_violations.push(user);
+        }
+    }
+    metrics.constraint_violations.sort_unstable();
+    for (idx, group) in constraints.groups.iter().enumerate() {
+        let mut teams = group.iter().map(|&user| assignment.team_of(user));
+        let first = teams.next().flatten();
+        if teams.any(|team| team != first) {
+            metrics.split_groups.push(idx);
+        }
+    }
+    for &(a, b) in &constraints.apart {
+        if assignment.team_of(a).is_some() && assignment.team_of(a) == assignment.team_of(b) {
+            metrics.apart_together.push((a, b));
+        }
+    }
+    metrics.rule_violations = violations(session, assignment);
+    metrics.missing_roles = session.roles.missing(assignment);
+    metrics.unassigned = assignment.unassigned.len();
+    metrics.average_rank = if assigned > 0 {
+        total_rank as f64 / assigned as f64
+    } else {
+        0.0
+    };
+
+    for user in 0..session.users.len() {
+        let current = assignment.team_of(user);
+        // Users left out of the assignment, e.g. who withdrew, look for no team
+        if current.is_none() && !assignment.unassigned.contains(&user) {
+            continue;
+        }
+        // Pinned users do not look for another team
+        if pin(user).is_some() && pin(user) == current {
+            continue;
+        }
+        if constraints.group_of(user).is_some() {
+            continue;
+        }
+        for team in 0..n_teams {
+            if Some(team) == current || constraints.is_forbidden(user, team) {
+                continue;
+            }
+            let members = assignment.teams.get(team).map_or(&[][..], Vec::as_slice);
+            if members.iter().any(|&other| constraints.is_apart(user, other)) {
+                continue;
+            }
+            if current.is_some_and(|current| rank(user, current) <= rank(user, team)) {
+                continue;
+            }
+            let has_room = members.len() < size(team);
+            let would_replace = members.iter().any(|&other| {
+                pin(other) != Some(team) && priority(team, other) > priority(team, user)
+            });
+            if has_room || would_replace {
+                metrics.blocking_pairs.push(BlockingPair { user, team });
+            }
+        }
+    }
+
+    metrics
+}
@@ -0,0 +1,399 @@
+//! Edits of a session as operations, so that concurrent edits merge.
+//!
+//! Browsers edit whole copies of a session, offline or while others edit it
+//! too. Sending the whole session back would undo the edits made elsewhere in
+//! the meantime, so a browser sends what it changed instead: the operations
+//! found by [`diff`] between the last state it got from the backend and its
+//! own. The backend applies them to the current state in the order they come
+//! with [`apply`], and the browser applies the operations it did not send yet
+//! to the state it gets back (see [`rebase`]).
+//!
+//! Operations name the users and teams they are about, so that they still
+//! apply to the right ones after others were added, removed or renamed, and
+//! they are dropped when what they are about is gone. Operations on the same
+//! value are resolved by the order they are applied in, the last one winning,
+//! unless the browser asks which to keep, the clashing ones being found by
+//! [`conflicts`].
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::attributes::Attributes;
+use crate::constraints::Constraints;
+use crate::roles::Roles;
+use crate::session::Session;
+use crate::settings::Settings;
+
+/// A user or team an operation is about.
+///
+/// It is found by name, or at the same position if it was renamed meanwhile
+/// while there are as many users or teams as when the operation was made.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    pub name: String,
+    pub index: usize,
+    /// Number of users or teams when the operation was made.
+    pub count: usize,
+}
+
+impl Target {
+    fn new(names: &[String], index: usize) -> Self {
+        Self {
+            name: names[index].clone(),
+            index,
+            count: names.len(),
+        }
+    }
+
+    pub(crate) fn find(&self, names: &[String]) -> Option<usize> {
+        // The position comes first, for names given twice
+        if names.get(self.index) == Some(&self.name) {
+            return Some(self.index);
+        }
+        names
+            .iter()
+            .position(|name| *name == self.name)
+            .or_else(|| (names.len() == self.count && self.index < names.len()).then_some(self.index))
+    }
+}
+
+/// An edit of a session.
+///
+/// Constraints, attributes and roles refer to users and teams by position, so
+/// they are replaced as a whole, and only if there are still as many users and
+/// teams as when they were edited.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Op {
+    SetName { name: String },
+    AddUser { name: String },
+    RemoveUser { user: Target },
+    RenameUser { user: Target, name: String },
+    /// Claim a user for a participant, or release them.
+    SetMember { user: Target, member: Option<Uuid> },
+    AddTeam { name: String, size: usize },
+    RemoveTeam { team: Target },
+    RenameTeam { team: Target, name: String },
+    SetSize { team: Target, size: usize },
+    /// Set the rank given by a user to a team, 0 to unset it.
+    SetRank { user: Target, team: Target, rank: usize },
+    SetConstraints { constraints: Constraints, users: usize, teams: usize },
+    SetAttributes { attributes: Attributes, users: usize },
+    SetRoles { roles: Roles, users: usize, teams: usize },
+    SetSettings { settings: Settings },
+}
+
+impl Op {
+    /// Apply the operation, returning whether it applied: it does not when what
+    /// it is about is gone.
+    pub fn apply(&self, session: &mut Session) -> bool {
+        let same_shape = |session: &Session, users: usize, teams: usize| {
+            session.users.len() == users && session.teams.len() == teams
+        };
+        match self {
+            Op::SetName { name } => session.name.clone_from(name),
+            Op::AddUser { name } => session.add_user(name.clone()),
+            Op::RemoveUser { user } => match user.find(&session.users) {
+                Some(user) => session.remove_user(user),
+                None => return false,
+            },
+            Op::RenameUser { user, name } => match user.find(&session.users) {
+                Some(user) => session.users[user].clone_from(name),
+                None => return false,
+            },
+            Op::SetMember { user, member } => {
+                let Some(user) = user.find(&session.users) else {
+                    return false;
+                };
+                if session.members.len() <= user {
+                    session.members.resize(user + 1, None);
+                }
+                session.members[user] = *member;
+            }
+            Op::AddTeam { name, size } => session.add_team(name.clone(), *size),
+            Op::RemoveTeam { team } => match team.find(&session.teams) {
+                Some(team) => session.remove_team(team),
+                None => return false,
+            },
+            Op::RenameTeam { team, name } => match team.find(&session.teams) {
+                Some(team) => session.teams[team].clone_from(name),
+                None => return false,
+            },
+            Op::SetSize { team, size } => {
+                let Some(team) = team.find(&session.teams) else {
+                    return false;
+                };
+                if session.teams_size.len() <= team {
+                    session.teams_size.resize(session.teams.len(), 0);
+                }
+                session.teams_size[team] = *size;
+            }
+            Op::SetRank { user, team, rank } => {
+                let (Some(user), Some(team)) = (user.find(&session.users), team.find(&session.teams)) else {
+                    return false;
+                };
+                let n_teams = session.teams.len();
+                if session.preferences.len() <= user {
+                    session.preferences.resize(user + 1, Vec::new());
+                }
+                let prefs = &mut session.preferences[user];
+                // Hidden rows, and rows of users added since, are filled in as needed
+                if prefs.len() < n_teams {
+                    prefs.resize(n_teams, 0);
+                }
+                prefs[team] = *rank;
+            }
+            Op::SetConstraints { constraints, users, teams } => {
+                if !same_shape(session, *users, *teams) {
+                    return false;
+                }
+                session.constraints.clone_from(constraints);
+            }
+            Op::SetAttributes { attributes, users } => {
+                if session.users.len() != *users {
+                    return false;
+                }
+                session.attributes.clone_from(attributes);
+            }
+            Op::SetRoles { roles, users, teams } => {
+                if !same_shape(session, *users, *teams) {
+                    return false;
+                }
+                session.roles.clone_from(roles);
+            }
+            Op::SetSettings { settings } => session.settings.clone_from(settings),
+        }
+        true
+    }
+}
+
+/// Apply operations in order, returning how many applied.
+pub fn apply(session: &mut Session, ops: &[Op]) -> usize {
+    ops.iter().filter(|op| op.apply(session)).count()
+}
+
+/// The operations turning `base` into `edited`.
+///
+/// Applied to `base`, they give `edited`, but for the users left unclaimed at the
+/// end of the members. Empty rows of preferences in `edited`, i.e. hidden ones,
+/// are left as they are.
+pub fn diff(base: &Session, edited: &Session) -> Vec<Op> {
+    let mut diff = Diff {
+        ops: Vec::new(),
+        working: base.clone(),
+    };
+    if edited.name != base.name {
+        diff.push(Op::SetName {
+            name: edited.name.clone(),
+        });
+    }
+    // First, as the privacy tells how users are added
+    if edited.settings != diff.working.settings {
+        let settings = edited.settings.clone();
+        diff.push(Op::SetSettings { settings });
+    }
+    diff.users(&edited.users);
+    diff.teams(&edited.teams, &edited.teams_size);
+
+    for (idx, &size) in edited.teams_size.iter().enumerate().take(edited.teams.len()) {
+        if diff.working.teams_size.get(idx) != Some(&size) {
+            let team = Target::new(&diff.working.teams, idx);
+            diff.push(Op::SetSize { team, size });
+        }
+    }
+    for (user, prefs) in edited.preferences.iter().enumerate().take(edited.users.len()) {
+        if prefs.is_empty() {
+            continue;
+        }
+        for team in 0..edited.teams.len() {
+            let rank = prefs.get(team).copied().unwrap_or(0);
+            let current = diff.working.preferences.get(user).and_then(|prefs| prefs.get(team));
+            if current != Some(&rank) {
+                let user = Target::new(&diff.working.users, user);
+                let team = Target::new(&diff.working.teams, team);
+                diff.push(Op::SetRank { user, team, rank });
+            }
+        }
+    }
+    for user in 0..edited.users.len() {
+        let member = edited.members.get(user).copied().flatten();
+        if diff.working.members.get(user).copied().flatten() != member {
+            let user = Target::new(&diff.working.users, user);
+            diff.push(Op::SetMember { user, member });
+        }
+    }
+
+    let (users, teams) = (edited.users.len(), edited.teams.len());
+    if edited.constraints != diff.working.constraints {
+        let constraints = edited.constraints.clone();
+        diff.push(Op::SetConstraints { constraints, users, teams });
+    }
+    if edited.attributes != diff.working.attributes {
+        let attributes = edited.attributes.clone();
+        diff.push(Op::SetAttributes { attributes, users });
+    }
+    if edited.roles != diff.working.roles {
+        let roles = edited.roles.clone();
+        diff.push(Op::SetRoles { roles, users, teams });
+    }
+    diff.ops
+}
+
+/// The edits of both sides changing the same value, or something removed by the
+/// other side, differently.
+pub fn conflicts(ours: &[Op], theirs: &[Op]) -> Vec<(Op, Op)> {
+    let mut conflicts = Vec::new();
+    for our in ours {
+        for their in theirs.iter().filter(|their| *their != our) {
+            let clash = subjects(our)
+                .iter()
+                .any(|a| subjects(their).iter().any(|b| a.clashes_with(b)));
+            if clash {
+                conflicts.push((our.clone(), their.clone()));
+            }
+        }
+    }
+    conflicts
+}
+
+/// Replay the edits made from `base` to `ours` on `theirs`, a newer state of
+/// the session edited elsewhere.
+pub fn rebase(base: &Session, ours: &Session, mut theirs: Session) -> Session {
+    apply(&mut theirs, &diff(base, ours));
+    theirs
+}
+
+/// What an operation changes, by name.
+#[derive(PartialEq)]
+enum Subject<'a> {
+    Name,
+    /// The user as a whole, renamed or removed.
+    User(&'a str),
+    Member(&'a str),
+    Team(&'a str),
+    Size(&'a str),
+    Rank(&'a str, &'a str),
+    Constraints,
+    Attributes,
+    Roles,
+    Settings,
+}
+
+impl Subject<'_> {
+    fn clashes_with(&self, other: &Subject) -> bool {
+        self == other
+            || match (self, other) {
+                (Subject::User(user), other) | (other, Subject::User(user)) => other.user() == Some(user),
+                (Subject::Team(team), other) | (other, Subject::Team(team)) => other.team() == Some(team),
+                _ => false,
+            }
+    }
+
+    fn user(&self) -> Option<&str> {
+        match *self {
+            Subject::Member(user) | Subject::Rank(user, _) => Some(user),
+            _ => None,
+        }
+    }
+
+    fn team(&self) -> Option<&str> {
+        match *self {
+            Subject::Size(team) | Subject::Rank(_, team) => Some(team),
+            _ => None,
+        }
+    }
+}
+
+fn subjects(op: &Op) -> Vec<Subject<'_>> {
+    match op {
+        Op::SetName { .. } => vec![Subject::Name],
+        Op::AddUser { .. } | Op::AddTeam { .. } => Vec::new(),
+        Op::RemoveUser { user } | Op::RenameUser { user, .. } => vec![Subject::User(&user.name)],
+        Op::SetMember { user, .. } => vec![Subject::Member(&user.name)],
+        Op::RemoveTeam { team } | Op::RenameTeam { team, .. } => vec![Subject::Team(&team.name)],
+        Op::SetSize { team, .. } => vec![Subject::Size(&team.name)],
+        Op::SetRank { user, team, .. } => vec![Subject::Rank(&user.name, &team.name)],
+        Op::SetConstraints { .. } => vec![Subject::Constraints],
+        Op::SetAttributes { .. } => vec![Subject::Attributes],
+        Op::SetRoles { .. } => vec![Subject::Roles],
+        Op::SetSettings { .. } => vec![Subject::Settings],
+    }
+}
+
+/// Operations found so far, and the session they give, which the next ones are
+/// found from.
+struct Diff {
+    ops: Vec<Op>,
+    working: Session,
+}
+
+impl Diff {
+    fn push(&mut self, op: Op) {
+        op.apply(&mut self.working);
+        self.ops.push(op);
+    }
+
+    fn users(&mut self, edited: &[String]) {
+        let (removed, added) = changes(&self.working.users, edited);
+        for user in removed.into_iter().rev() {
+            let user = Target::new(&self.working.users, user);
+            self.push(Op::RemoveUser { user });
+        }
+        for idx in added {
+            self.push(Op::AddUser {
+                name: edited[idx].clone(),
+            });
+        }
+        for (idx, name) in edited.iter().enumerate() {
+            if self.working.users[idx] != *name {
+                let user = Target::new(&self.working.users, idx);
+                self.push(Op::RenameUser {
+                    user,
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+
+    fn teams(&mut self, edited: &[String], sizes: &[usize]) {
+        let (removed, added) = changes(&self.working.teams, edited);
+        for team in removed.into_iter().rev() {
+            let team = Target::new(&self.working.teams, team);
+            self.push(Op::RemoveTeam { team });
+        }
+        for idx in added {
+            self.push(Op::AddTeam {
+                name: edited[idx].clone(),
+                size: sizes.get(idx).copied().unwrap_or(0),
+            });
+        }
+        for (idx, name) in edited.iter().enumerate() {
+            if self.working.teams[idx] != *name {
+                let team = Target::new(&self.working.teams, idx);
+                self.push(Op::RenameTeam {
+                    team,
+                    name: name.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Positions of the names removed from `old`, and of the names added to `new`.
+///
+/// When there are as many names, those which differ were renamed rather.
+fn changes(old: &[String], new: &[String]) -> (Vec<usize>, Vec<usize>) {
+    if old.len() == new.len() {
+        return (Vec::new(), Vec::new());
+    }
+    let mut kept = vec![false; new.len()];
+    let mut removed = Vec::new();
+    for (idx, name) in old.iter().enumerate() {
+        match (0..new.len()).find(|&other| !kept[other] && new[other] == *name) {
+            Some(other) => kept[other] = true,
+            None => removed.push(idx),
+        }
+    }
+    let added = (0..new.len()).filter(|&idx| !kept[idx]).collect();
+    (removed, added)
+}
@@ -0,0 +1,209 @@
+//! Field-level visibility of private sessions.
+//!
+//! The backend sends each caller a redacted copy of the session, and merges
+//! what they send back into the stored session, so that the hidden fields are
+//! neither leaked nor overwritten.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::constraints::Constraints;
+use crate::run::Run;
+use crate::session::{Role, Session};
+
+/// Stands for the identity key of a member hidden from the caller: identity keys
+/// are enough to sign in, so only their owner may see them.
+pub const HIDDEN_MEMBER: Uuid = Uuid::nil();
+
+/// What the organiser of a private session sees of the preferences.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Aggregates {
+    /// Number of users who ranked every team.
+    pub filled: usize,
+    pub teams: Vec<TeamAggregate>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TeamAggregate {
+    /// Number of users who ranked the team first.
+    pub first_choices: usize,
+    /// Average rank given to the team, 0 if no user ranked every team yet.
+    pub average_rank: f64,
+}
+
+/// Aggregate the preferences of the users who ranked every team.
+pub fn aggregate(session: &Session) -> Aggregates {
+    let n_teams = session.teams.len();
+    let filled: Vec<&Vec<usize>> = session
+        .preferences
+        .iter()
+        .take(session.users.len())
+        .filter(|prefs| {
+            prefs.len() >= n_teams && prefs[..n_teams].iter().all(|&rank| (1..=n_teams).contains(&rank))
+        })
+        .collect();
+    let teams = (0..n_teams)
+        .map(|team| TeamAggregate {
+            first_choices: filled.iter().filter(|prefs| prefs[team] == 1).count(),
+            average_rank: if filled.is_empty() {
+                0.0
+            } else {
+                filled.iter().map(|prefs| prefs[team]).sum::<usize>() as f64 / filled.len() as f64
+            },
+        })
+        .collect();
+    Aggregates {
+        filled: filled.len(),
+        teams,
+    }
+}
+
+/// The session as the caller may see it.
+///
+/// Members other than the caller are replaced by [`HIDDEN_MEMBER`]. In a private
+/// session, preference rows other than the caller's own are emptied, and
+/// participants do not see the constraints set by the organiser nor the
+/// attributes and skills of other users.
+pub fn redact(session: &Session, role: Role, identity: Option<Uuid>) -> Session {
+    let mut session = session.clone();
+    let own = identity.and_then(|identity| session.member_of(identity));
+    for member in session.members.iter_mut() {
+        if member.is_some() && *member != identity {
+            *member = Some(HIDDEN_MEMBER);
+        }
+    }
+    if session.settings.private_preferences {
+        for (user, prefs) in session.preferences.iter_mut().enumerate() {
+            if Some(user) != own {
+                prefs.clear();
+            }
+        }
+        if role == Role::Participant {
+            session.constraints = Constraints::default();
+            for (user, values) in session.attributes.values.iter_mut().enumerate() {
+                if Some(user) != own {
+                    values.clear();
+                }
+            }
+            for (user, skills) in session.roles.skills.iter_mut().enumerate() {
+                if Some(user) != own {
+                    skills.clear();
+                }
+            }
+        }
+    }
+    session
+}
+
+/// A run as the caller may see it.
+///
+/// Participants, and the organiser of a private session, do not see the metrics
+/// naming users nor the ranks they were given, which would tell the preferences
+/// apart, nor the probabilities of each user to end in each team.
+pub fn redact_run(run: &Run, session: &Session, role: Role) -> Run {
+    let mut run = run.clone();
+    if role == Role::Participant || session.settings.private_preferences {
+        run.metrics.blocking_pairs.clear();
+        run.metrics.constraint_violations.clear();
+        run.metrics.apart_together.clear();
+        run.metrics.rank_counts.clear();
+        run.metrics.average_rank = 0.0;
+        run.probabilities = None;
+    }
+    run
+}
+
+/// Merge a session sent by a caller, who saw it redacted, into the stored one.
+///
+/// Organisers may change anything but the preferences of a private session, which
+/// are restored from the stored session, following the users and teams the
+/// organiser renamed, removed or reordered. They are cleared when the organiser
+/// turns the privacy off, so that they are never shown. Nor may they claim users
+/// of a private session, only release them, so as not to read their preferences.
+/// Participants may only
+/// claim a user nobody claimed yet and edit the preferences, attributes and skills
+/// of the user they claimed, whether the session is private or not.
+pub fn merge(stored: &Session, mut incoming: Session, role: Role, identity: Option<Uuid>) -> Session {
+    for user in 0..incoming.members.len() {
+        if incoming.members[user] == Some(HIDDEN_MEMBER) {
+            incoming.members[user] = stored_index(&stored.users, &incoming.users, user)
+                .and_then(|stored_user| stored.members.get(stored_user).copied().flatten());
+        }
+    }
+
+    match role {
+        Role::Organiser if !stored.settings.private_preferences => incoming,
+        Role::Organiser => {
+            for user in 0..incoming.members.len() {
+                if incoming.members[user].is_some() {
+                    incoming.members[user] = stored_index(&stored.users, &incoming.users, user)
+                        .and_then(|stored_user| stored.members.get(stored_user).copied().flatten());
+                }
+            }
+            let n_teams = incoming.teams.len();
+            let public = !incoming.settings.private_preferences;
+            incoming.preferences = (0..incoming.users.len())
+                .map(|user| {
+                    let stored_prefs = stored_index(&stored.users, &incoming.users, user)
+                        .and_then(|stored_user| stored.preferences.get(stored_user))
+                        .filter(|_| !public);
+                    (0..n_teams)
+                        .map(|team| {
+                            stored_index(&stored.teams, &incoming.teams, team)
+                                .zip(stored_prefs)
+                                .and_then(|(stored_team, stored_prefs)| stored_prefs.get(stored_team))
+                                .copied()
+                                .unwrap_or(0)
+                        })
+                        .collect()
+                })
+                .collect();
+            incoming
+        }
+        Role::Participant => {
+            let mut merged = stored.clone();
+            let Some(identity) = identity else {
+                return merged;
+            };
+            let Some(own) = incoming.member_of(identity) else {
+                return merged;
+            };
+            let Some(user) = stored_index(&stored.users, &incoming.users, own) else {
+                return merged;
+            };
+            let claimed_by = stored.members.get(user).copied().flatten();
+            if claimed_by.is_some_and(|member| member != identity) {
+                return merged;
+            }
+            if merged.members.len() <= user {
+                merged.members.resize(user + 1, None);
+            }
+            merged.members[user] = Some(identity);
+            if let (Some(prefs), Some(own_prefs)) =
+                (merged.preferences.get_mut(user), incoming.preferences.get(own))
+            {
+                *prefs = own_prefs.clone();
+            }
+            if let Some(values) = incoming.attributes.values.get(own) {
+                let n_attributes = merged.attributes.names.len();
+                for (attribute, value) in values.iter().take(n_attributes).enumerate() {
+                    merged.attributes.set_value(user, attribute, value.clone());
+                }
+            }
+            if let Some(skills) = incoming.roles.skills.get(own) {
+                merged.roles.set_skills(user, skills.clone());
+            }
+            merged
+        }
+    }
+}
+
+/// The stored index of a user or team sent back by a caller: they are matched by
+/// name, or by position if the organiser renamed them.
+fn stored_index(stored: &[String], names: &[String], idx: usize) -> Option<usize> {
+    let name = names.get(idx)?;
+    stored
+        .iter()
+        .position(|stored_name| stored_name == name)
+        .or_else(|| (stored.len() == names.len()).then_some(idx))
+}
@@ -0,0 +1,202 @@
+//! The probabilistic serial (eating) mechanism, which gives each user a
+//! probability of ending in each team, and a sampler drawing an assignment from it.
+//!
+//! Every user eats from the team they prefer among those with a share left, all
+//! at the same speed, for one unit of time; the share of a team a user ate is
+//! their probability of joining it. No user would rather have the probabilities
+//! of another, which makes it a reference for fairness audits.
+//!
+//! Groups and apart pairs are not taken into account: users eat alone.
+
+use serde::{Deserialize, Serialize};
+
+use crate::assignment::Assignment;
+use crate::lottery::Rng;
+use crate::market::Market;
+use crate::session::Session;
+
+pub const PROBABILISTIC_SERIAL: &str = "Probabilistic serial";
+
+/// Below this, a share is considered empty and a probability 0 or 1.
+const EPSILON: f64 = 1e-9;
+
+/// The probability of each user to end in each team.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fractional {
+    /// `probabilities[user][team]`, each row summing to 1 at most.
+    pub probabilities: Vec<Vec<f64>>,
+}
+
+impl Fractional {
+    /// The probability of a user to end in no team.
+    pub fn unassigned(&self, user: usize) -> f64 {
+        let assigned: f64 = self.probabilities.get(user).map_or(0.0, |row| row.iter().sum());
+        (1.0 - assigned).max(0.0)
+    }
+}
+
+/// Run the eating algorithm on a session. Pinned users end in their team for sure.
+pub fn probabilistic_serial(session: &Session) -> Fractional {
+    let market = Market::new(session, 0);
+    let mut probabilities = vec![vec![0.0; market.n_teams]; market.n_users];
+    for (user, pin) in market.pinned.iter().enumerate() {
+        if let Some(team) = pin {
+            probabilities[user][*team] = 1.0;
+        }
+    }
+    let mut left: Vec<f64> = market.capacity.iter().map(|&capacity| capacity as f64).collect();
+    let mut eating: Vec<usize> = (0..market.n_users).filter(|&user| market.pinned[user].is_none()).collect();
+    let mut time = 0.0;
+
+    while time < 1.0 - EPSILON {
+        // Each user eats from the team they prefer among those with a share left
+        let eaten: Vec<(usize, usize)> = eating
+            .iter()
+            .filter_map(|&user| {
+                let team = market.user_lists[user].iter().copied().find(|&team| left[team] > EPSILON)?;
+                Some((user, team))
+            })
+            .collect();
+        if eaten.is_empty() {
+            break;
+        }
+        eating = eaten.iter().map(|&(user, _)| user).collect();
+        let mut eaters = vec![0usize; market.n_teams];
+        for &(_, team) in &eaten {
+            eaters[team] += 1;
+        }
+        // Until a team runs out, or the time is up
+        let step = (0..market.n_teams)
+            .filter(|&team| eaters[team] > 0)
+            .map(|team| left[team] / eaters[team] as f64)
+            .fold(1.0 - time, f64::min);
+        for &(user, team) in &eaten {
+            probabilities[user][team] += step;
+            left[team] -= step;
+        }
+        time += step;
+    }
+    Fractional { probabilities }
+}
+
+/// Draw an assignment with the probabilities of `fractional`, from `seed`.
+///
+/// Dependent rounding keeps every probability exactly, while giving each user one
+/// team at most and each team no more users than it could expect, rounded up,
+/// hence no more than its size.
+pub fn sample(fractional: &Fractional, n_teams: usize, seed: u64) -> Assignment {
+    let mut rng = Rng::new(seed);
+    let n_users = fractional.probabilities.len();
+    // The last column stands for no team, so that every row sums to 1
+    let unassigned = n_teams;
+    let mut x: Vec<Vec<f64>> = (0..n_users)
+        .map(|user| {
+            let mut row: Vec<f64> = (0..n_teams)
+                .map(|team| fractional.probabilities[user].get(team).copied().unwrap_or(0.0))
+                .collect();
+            row.push(fractional.unassigned(user));
+            row
+        })
+        .collect();
+    let fractional_edge = |x: &[Vec<f64>], user: usize, column: usize| {
+        x[user][column] > EPSILON && x[user][column] < 1.0 - EPSILON
+    };
+
+    // Vertices are users, then columns; each step makes at least one edge whole
+    while let Some((start_user, start_column)) = (0..n_users)
+        .flat_map(|user| (0..=n_teams).map(move |column| (user, column)))
+        .find(|&(user, column)| fractional_edge(&x, user, column))
+    {
+        let path = walk(&x, n_users, n_teams, start_user, start_column, &fractional_edge);
+        let (mut up, mut down) = (f64::INFINITY, f64::INFINITY);
+        for (idx, &(user, column)) in path.iter().enumerate() {
+            let value = x[user][column];
+            // Even edges go up by `up` and odd ones down, or the other way by `down`
+            if idx % 2 == 0 {
+                up = up.min(1.0 - value);
+                down = down.min(value);
+            } else {
+                up = up.min(value);
+                down = down.min(1.0 - value);
+            }
+        }
+        let go_up = (rng.next_u64() as f64 / u64::MAX as f64) < down / (up + down);
+        for (idx, &(user, column)) in path.iter().enumerate() {
+            let delta = if go_up { up } else { -down };
+            let delta = if idx % 2 == 0 { delta } else { -delta };
+            let value = &mut x[user][column];
+            *value = (*value + delta).clamp(0.0, 1.0);
+            if *value < EPSILON {
+                *value = 0.0;
+            } else if *value > 1.0 - EPSILON {
+                *value = 1.0;
+            }
+        }
+    }
+
+    let mut assignment = Assignment {
+        teams: vec![Vec::new(); n_teams],
+        unassigned: Vec::new(),
+    };
+    for (user, row) in x.iter().enumerate() {
+        let column = (0..=n_teams)
+            .max_by(|&a, &b| row[a].total_cmp(&row[b]))
+            .unwrap_or(unassigned);
+        match assignment.teams.get_mut(column) {
+            Some(members) => members.push(user),
+            None => assignment.unassigned.push(user),
+        }
+    }
+    assignment
+}
+
+/// A cycle, or a path that cannot be extended, of fractional edges through an
+/// edge, edges alternating between the two sides of every inner vertex.
+fn walk(
+    x: &[Vec<f64>],
+    n_users: usize,
+    n_teams: usize,
+    user: usize,
+    column: usize,
+    fractional_edge: &impl Fn(&[Vec<f64>], usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    // Vertices: users are 0..n_users, columns follow
+    let column_vertex = |column: usize| n_users + column;
+    let neighbours = |vertex: usize| -> Vec<usize> {
+        if vertex < n_users {
+            (0..=n_teams)
+                .filter(|&column| fractional_edge(x, vertex, column))
+                .map(column_vertex)
+                .collect()
+        } else {
+            (0..n_users)
+                .filter(|&user| fractional_edge(x, user, vertex - n_users))
+                .collect()
+        }
+    };
+    let edge = |a: usize, b: usize| if a < n_users { (a, b - n_users) } else { (b, a - n_users) };
+
+    let mut vertices = vec![user, column_vertex(column)];
+    let mut reversed = false;
+    loop {
+        let last = vertices[vertices.len() - 1];
+        let previous = vertices[vertices.len() - 2];
+        match neighbours(last).into_iter().find(|&next| next != previous) {
+            Some(next) => {
+                if let Some(position) = vertices.iter().position(|&vertex| vertex == next) {
+                    // A cycle, even as the graph is bipartite
+                    let mut cycle = vertices[position..].to_vec();
+                    cycle.push(next);
+                    return cycle.windows(2).map(|pair| edge(pair[0], pair[1])).collect();
+                }
+                vertices.push(next);
+            }
+            None if !reversed => {
+                // Extend the path from its other end
+                vertices.reverse();
+                reversed = true;
+            }
+            None => return vertices.windows(2).map(|pair| edge(pair[0], pair[1])).collect(),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Re-matching a session from a previous run when users joined or left late,
+//! moving as few users as possible.
+//!
+//! Users of the previous run keep their team while it still takes them: it still
+//! exists, they may still join it, and it has room for them, the team keeping the
+//! users it prefers otherwise. The other users propose to the seats left, as in
+//! Gale-Shapley. Users still without a team are then placed by the shortest chain
+//! of moves ending on a free seat, which moves the fewest users.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::assignment::{Assignment, Move};
+use crate::gale_shapley::deferred_acceptance;
+use crate::market::Market;
+use crate::run::Run;
+use crate::session::Session;
+
+/// Name of the solver, as recorded in runs.
+pub const MINIMAL_DISRUPTION: &str = "Minimal disruption";
+
+/// What a re-match changed from the previous run.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Disruption {
+    /// Users of the previous run whose team changed.
+    pub moved: Vec<Move>,
+    /// Users new since the previous run.
+    pub joined: Vec<usize>,
+    /// Names of the users of the previous run who left the session.
+    pub left: Vec<String>,
+}
+
+/// The team each user of the session had in a run, `None` for users new since.
+///
+/// Users and teams are followed by name, or by position for runs recorded before
+/// runs kept the names.
+fn previous_teams(session: &Session, previous: &Run) -> Vec<Option<Option<usize>>> {
+    let assignment = &previous.assignment;
+    let team = |team: usize| {
+        if previous.teams.is_empty() {
+            (team < session.teams.len()).then_some(team)
+        } else {
+            let name = previous.teams.get(team)?;
+            session.teams.iter().position(|other| other == name)
+        }
+    };
+    (0..session.users.len())
+        .map(|user| {
+            let before = if previous.users.is_empty() {
+                Some(user)
+            } else {
+                previous.users.iter().position(|name| *name == session.users[user])
+            }?;
+            match assignment.team_of(before) {
+                Some(before) => Some(team(before)),
+                None => assignment.unassigned.contains(&before).then_some(None),
+            }
+        })
+        .collect()
+}
+
+/// Re-match a session from a previous run, keeping the users where they were as
+/// much as possible. Ties are broken by the session's lottery drawn from `seed`.
+pub fn rematch(session: &Session, seed: u64, previous: &Run) -> Assignment {
+    let market = Market::new(session, seed);
+    let constraints = &session.constraints;
+    let before = previous_teams(session, previous);
+    let acceptable = |user: usize, team: usize| market.user_rank(user, team) != usize::MAX;
+
+    // Blocks keeping their team, the team keeping the users it prefers
+    let mut team_of: Vec<Option<usize>> = vec![None; market.n_users];
+    let mut left = market.capacity.clone();
+    let mut kept: Vec<&Vec<usize>> = market
+        .blocks
+        .iter()
+        .filter(|block| {
+            let team = before[block[0]].flatten();
+            team.is_some_and(|team| {
+                block
+                    .iter()
+                    .all(|&user| before[user].flatten() == Some(team) && acceptable(user, team))
+            })
+        })
+        .collect();
+    kept.sort_by_key(|block| {
+        let team = before[block[0]].flatten().unwrap_or(0);
+        block.iter().map(|&user| market.team_rank(team, user)).min()
+    });
+    let mut free: Vec<&Vec<usize>> = market.blocks.iter().filter(|block| !kept.contains(block)).collect();
+    for block in kept {
+        let team = before[block[0]].flatten().unwrap_or(0);
+        let apart = block.iter().any(|&user| {
+            (0..market.n_users).any(|other| team_of[other] == Some(team) && constraints.is_apart(user, other))
+        });
+        if left[team] >= block.len() && !apart {
+            left[team] -= block.len();
+            for &user in block {
+                team_of[user] = Some(team);
+            }
+        } else {
+            free.push(block);
+        }
+    }
+
+    // Teams a user may join, without anyone they asked to be apart from
+    let open = |team_of: &[Option<usize>], user: usize, team: usize| {
+        !(0..market.n_users).any(|other| team_of[other] == Some(team) && constraints.is_apart(user, other))
+    };
+
+    // Groups take the first team with room for all of them, then the other users propose
+    let mut singles: Vec<usize> = Vec::new();
+    for block in free {
+        if let [user] = block.as_slice() {
+            singles.push(*user);
+            continue;
+        }
+        let team = market.user_lists[block[0]].iter().copied().find(|&team| {
+            left[team] >= block.len()
+                && block.iter().all(|&user| acceptable(user, team) && open(&team_of, user, team))
+        });
+        if let Some(team) = team {
+            left[team] -= block.len();
+            for &user in block {
+                team_of[user] = Some(team);
+            }
+        }
+    }
+    let lists: Vec<Vec<usize>> = (0..market.n_users)
+        .map(|user| {
+            market.user_lists[user]
+                .iter()
+                .copied()
+                .filter(|&team| open(&team_of, user, team))
+                .collect()
+        })
+        .collect();
+    let proposed = deferred_acceptance(&lists, &left, &singles, |team, user| market.team_rank(team, user));
+    for &user in &singles {
+        if let Some(team) = proposed[user] {
+            team_of[user] = Some(team);
+            left[team] -= 1;
+        }
+    }
+
+    // Users left out take a seat through the shortest chain of moves
+    for &user in &singles {
+        if team_of[user].is_none() {
+            insert(&market, session, &mut team_of, &mut left, user);
+        }
+    }
+    market.assignment(&team_of)
+}
+
+/// Find the shortest chain of users moving to make room for `user`, each joining
+/// a team on their list, and apply it.
+///
+/// Chains are checked again once applied, hop after hop, and left out when they
+/// would put users who asked to be apart in the same team.
+fn insert(market: &Market, session: &Session, team_of: &mut [Option<usize>], left: &mut [usize], user: usize) {
+    let constraints = &session.constraints;
+    let movable = |other: usize| market.pinned[other].is_none() && constraints.group_of(other).is_none();
+    let fits = |team_of: &[Option<usize>], mover: usize, team: usize| {
+        !(0..market.n_users)
+            .any(|other| other != mover && team_of[other] == Some(team) && constraints.is_apart(mover, other))
+    };
+
+    // `reached[team]`: the user moving into the team, and the team they leave
+    let mut reached: Vec<Option<(usize, Option<usize>)>> = vec![None; market.n_teams];
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    for &team in &market.user_lists[user] {
+        if reached[team].is_none() && fits(team_of, user, team) {
+            reached[team] = Some((user, None));
+            queue.push_back(team);
+        }
+    }
+    while let Some(team) = queue.pop_front() {
+        if left[team] > 0 {
+            // From the free seat back to `user`, each mover leaving a seat for the next
+            let mut moved = team_of.to_vec();
+            let mut current = team;
+            let mut apart = false;
+            while let Some((mover, from)) = reached[current] {
+                moved[mover] = Some(current);
+                apart |= !fits(&moved, mover, current);
+                match from {
+                    Some(from) => current = from,
+                    None => break,
+                }
+            }
+            if apart {
+                continue;
+            }
+            left[team] -= 1;
+            team_of.copy_from_slice(&moved);
+            return;
+        }
+        let members: Vec<usize> = (0..market.n_users)
+            .filter(|&other| team_of[other] == Some(team) && movable(other))
+            .collect();
+        for member in members {
+            for &next in &market.user_lists[member] {
+                if next != team && reached[next].is_none() && fits(team_of, member, next) {
+                    reached[next] = Some((member, Some(team)));
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+}
+
+/// What an assignment of the session changed from a previous run.
+pub fn disruption(session: &Session, previous: &Run, assignment: &Assignment) -> Disruption {
+    let before = previous_teams(session, previous);
+    let moved = (0..session.users.len())
+        .filter_map(|user| {
+            let from = before[user]?;
+            let to = assignment.team_of(user);
+            (from != to).then_some(Move { user, from, to })
+        })
+        .collect();
+    let joined = (0..session.users.len()).filter(|&user| before[user].is_none()).collect();
+    let left = previous
+        .users
+        .iter()
+        .filter(|name| !session.users.contains(name))
+        .cloned()
+        .collect();
+    Disruption { moved, joined, left }
+}
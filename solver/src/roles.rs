@@ -0,0 +1,187 @@
+//! Roles required by teams, e.g. one designer and two backend developers, and
+//! the skills of the users who can take them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::assignment::Assignment;
+use crate::gale_shapley::deferred_acceptance;
+use crate::market::Market;
+use crate::session::Session;
+
+/// Name of the solver filling role slots first, as recorded in runs.
+pub const ROLES_FIRST: &str = "Gale-Shapley (roles first)";
+
+/// Roles a team needs, counted within its size.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Requirement {
+    pub role: String,
+    pub count: usize,
+}
+
+/// The skills of the users and the roles required by the teams.
+///
+/// Both lists may be shorter than the session, missing entries meaning that
+/// nothing was declared.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Roles {
+    /// `skills[user]` lists the roles the user can take.
+    #[serde(default)]
+    pub skills: Vec<Vec<String>>,
+    /// `requirements[team]` lists the roles the team needs.
+    #[serde(default)]
+    pub requirements: Vec<Vec<Requirement>>,
+}
+
+/// A role a team still needs in an assignment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingRole {
+    pub team: usize,
+    pub role: String,
+    pub missing: usize,
+}
+
+impl Roles {
+    pub fn is_empty(&self) -> bool {
+        self.requirements.iter().all(Vec::is_empty)
+    }
+
+    pub fn has_skill(&self, user: usize, role: &str) -> bool {
+        self.skills
+            .get(user)
+            .is_some_and(|skills| skills.iter().any(|skill| skill.trim() == role.trim()))
+    }
+
+    pub fn requirements(&self, team: usize) -> &[Requirement] {
+        self.requirements.get(team).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn set_skills(&mut self, user: usize, skills: Vec<String>) {
+        if self.skills.len() <= user {
+            self.skills.resize(user + 1, Vec::new());
+        }
+        self.skills[user] = skills;
+    }
+
+    pub fn add_requirement(&mut self, team: usize, requirement: Requirement) {
+        if self.requirements.len() <= team {
+            self.requirements.resize(team + 1, Vec::new());
+        }
+        self.requirements[team].push(requirement);
+    }
+
+    /// Keep the skills in line with a user removed from the session.
+    pub fn remove_user(&mut self, user: usize) {
+        if user < self.skills.len() {
+            self.skills.remove(user);
+        }
+    }
+
+    /// Keep the requirements in line with a team removed from the session.
+    pub fn remove_team(&mut self, team: usize) {
+        if team < self.requirements.len() {
+            self.requirements.remove(team);
+        }
+    }
+
+    /// Match the members of a team with its required roles, each member taking
+    /// one role at most, and return how many of each role are filled.
+    pub fn filled(&self, team: usize, members: &[usize]) -> Vec<usize> {
+        let requirements = self.requirements(team);
+        let mut filled = vec![0; requirements.len()];
+        let mut taken = vec![false; members.len()];
+        // Members with fewer matching skills take a role first, leaving the others free
+        let mut order: Vec<usize> = (0..members.len()).collect();
+        order.sort_by_key(|&idx| {
+            requirements
+                .iter()
+                .filter(|requirement| self.has_skill(members[idx], &requirement.role))
+                .count()
+        });
+        for (slot, requirement) in requirements.iter().enumerate() {
+            for &idx in &order {
+                if filled[slot] == requirement.count {
+                    break;
+                }
+                if !taken[idx] && self.has_skill(members[idx], &requirement.role) {
+                    taken[idx] = true;
+                    filled[slot] += 1;
+                }
+            }
+        }
+        filled
+    }
+
+    /// The roles left unfilled by an assignment.
+    pub fn missing(&self, assignment: &Assignment) -> Vec<MissingRole> {
+        let mut missing = Vec::new();
+        for (team, members) in assignment.teams.iter().enumerate() {
+            let filled = self.filled(team, members);
+            for (requirement, filled) in self.requirements(team).iter().zip(filled) {
+                if filled < requirement.count {
+                    missing.push(MissingRole {
+                        team,
+                        role: requirement.role.clone(),
+                        missing: requirement.count - filled,
+                    });
+                }
+            }
+        }
+        missing
+    }
+}
+
+/// Fill the role slots of every team first, then the rest of the teams, with
+/// users proposing in both rounds.
+///
+/// In the first round, each required role of a team is a small team of its own,
+/// open to the users with the skill and ranked by the users as they rank the team.
+/// Users left out then propose to the slots left in the teams. Pinned users with
+/// a required skill take the role in their team.
+pub(crate) fn roles_first(market: &Market, session: &Session) -> Vec<Option<usize>> {
+    let roles = &session.roles;
+
+    // Role slots of every team, as (team, role, count) within the team's capacity
+    let mut slots: Vec<(usize, &str, usize)> = Vec::new();
+    for team in 0..market.n_teams {
+        let pinned: Vec<usize> = (0..market.n_users)
+            .filter(|&user| market.pinned[user] == Some(team))
+            .collect();
+        let mut left = market.capacity[team];
+        let filled = roles.filled(team, &pinned);
+        for (requirement, filled) in roles.requirements(team).iter().zip(filled) {
+            let count = requirement.count.saturating_sub(filled).min(left);
+            left -= count;
+            slots.push((team, requirement.role.as_str(), count));
+        }
+    }
+
+    let slots = &slots;
+    let slot_lists: Vec<Vec<usize>> = (0..market.n_users)
+        .map(|user| {
+            market.user_lists[user]
+                .iter()
+                .flat_map(|&team| (0..slots.len()).filter(move |&slot| slots[slot].0 == team))
+                .filter(|&slot| roles.has_skill(user, slots[slot].1))
+                .collect()
+        })
+        .collect();
+    let slot_capacity: Vec<usize> = slots.iter().map(|&(_, _, count)| count).collect();
+    let users: Vec<usize> = (0..market.n_users).filter(|&user| market.pinned[user].is_none()).collect();
+    let slot_of = deferred_acceptance(&slot_lists, &slot_capacity, &users, |slot, user| {
+        market.team_rank(slots[slot].0, user)
+    });
+
+    let mut team_of: Vec<Option<usize>> = slot_of.iter().map(|slot| slot.map(|slot| slots[slot].0)).collect();
+    let mut capacity = market.capacity.clone();
+    for team in team_of.iter().flatten() {
+        capacity[*team] -= 1;
+    }
+    let left: Vec<usize> = users.into_iter().filter(|&user| team_of[user].is_none()).collect();
+    let rest = deferred_acceptance(&market.user_lists, &capacity, &left, |team, user| {
+        market.team_rank(team, user)
+    });
+    for user in left {
+        team_of[user] = rest[user];
+    }
+    team_of
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::assignment::Assignment;
+use crate::gale_shapley::Proposing;
+use crate::lottery::TieBreaking;
+use crate::mechanism::Mechanism;
+use crate::metrics::{evaluate, Metrics};
+use crate::probabilistic::{probabilistic_serial, Fractional, PROBABILISTIC_SERIAL};
+use crate::session::Session;
+use crate::settings::Settings;
+use crate::waitlist::{waitlists, withdraw, Withdrawal, WAITLIST};
+
+/// Name of the solver of a run edited by hand.
+pub const MANUAL: &str = "Manual";
+
+/// A saved result of a session, computed by a solver or edited by hand.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    pub id: Uuid,
+    /// Creation time, in milliseconds since the Unix epoch.
+    pub created_at: i64,
+    pub solver: String,
+    /// Seed of the random choices of the solver, if it made any.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Settings drawing on the seed when the run was made, so that re-running it
+    /// with its seed reproduces it. Runs recorded before they were kept have none.
+    #[serde(default)]
+    pub tie_breaking: Option<TieBreaking>,
+    #[serde(default)]
+    pub mechanism: Option<Mechanism>,
+    #[serde(default)]
+    pub proposing: Option<Proposing>,
+    /// [`Session::input_hash`] of the session the run was computed from.
+    #[serde(default)]
+    pub input_hash: String,
+    pub assignment: Assignment,
+    pub metrics: Metrics,
+    /// Probability of each user to end in each team, for runs drawn from them.
+    #[serde(default)]
+    pub probabilities: Option<Fractional>,
+    /// Names of the users and teams at the time of the run, to follow them
+    /// through later edits of the session.
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub teams: Vec<String>,
+    /// Run this one was derived from, when a user withdrew from it.
+    #[serde(default)]
+    pub previous: Option<Uuid>,
+    #[serde(default)]
+    pub withdrawal: Option<Withdrawal>,
+}
+
+impl Run {
+    pub fn new(
+        id: Uuid,
+        created_at: i64,
+        solver: &str,
+        seed: Option<u64>,
+        session: &Session,
+        assignment: Assignment,
+    ) -> Self {
+        Self {
+            id,
+            created_at,
+            solver: solver.to_string(),
+            seed,
+            tie_breaking: Some(session.settings.tie_breaking),
+            mechanism: Some(session.settings.mechanism),
+            proposing: Some(session.settings.proposing),
+            input_hash: session.input_hash(),
+            metrics: evaluate(session, &assignment),
+            assignment,
+            probabilities: (solver == PROBABILISTIC_SERIAL).then(|| probabilistic_serial(session)),
+            users: session.users.clone(),
+            teams: session.teams.clone(),
+            previous: None,
+            withdrawal: None,
+        }
+    }
+
+    /// Name of a user at the time of the run. Runs recorded before the names
+    /// were kept fall back on the names of the session.
+    fn user_name(&self, session: &Session, user: usize) -> Option<String> {
+        let users = if self.users.is_empty() { &session.users } else { &self.users };
+        users.get(user).cloned()
+    }
+
+    /// Name of a team at the time of the run, see [`Run::user_name`].
+    fn team_name(&self, session: &Session, team: usize) -> Option<String> {
+        let teams = if self.teams.is_empty() { &session.teams } else { &self.teams };
+        teams.get(team).cloned()
+    }
+
+    /// Index in `session` of a user or team of the run, found by name. Runs recorded
+    /// before the names were kept are taken to match the session.
+    fn index_in(names: &[String], current: &[String], idx: usize) -> Option<usize> {
+        if names.is_empty() {
+            return (idx < current.len()).then_some(idx);
+        }
+        let name = names.get(idx)?;
+        current.iter().position(|current| current == name)
+    }
+
+    /// Index in `session` of a team of the run, see [`Run::index_in`].
+    fn team_in(&self, session: &Session, team: usize) -> Option<usize> {
+        Self::index_in(&self.teams, &session.teams, team)
+    }
+
+    /// The assignment of the run over the users and teams of `session`, which may
+    /// have been edited since. Users and teams removed since are left out, and the
+    /// members of a removed team are left unassigned.
+    pub fn assignment_in(&self, session: &Session) -> Assignment {
+        let user = |user: usize| Self::index_in(&self.users, &session.users, user);
+        let mut assignment = Assignment {
+            teams: vec![Vec::new(); session.teams.len()],
+            unassigned: self.assignment.unassigned.iter().filter_map(|&member| user(member)).collect(),
+        };
+        for (team, members) in self.assignment.teams.iter().enumerate() {
+            let members = members.iter().filter_map(|&member| user(member));
+            match self.team_in(session, team) {
+                Some(team) => assignment.teams[team].extend(members),
+                None => assignment.unassigned.extend(members),
+            }
+        }
+        assignment.unassigned.sort_unstable();
+        assignment
+    }
+
+    /// `settings` with the tie-breaking, mechanism and proposing side of the run,
+    /// to sort the teams again as it did.
+    pub fn settings(&self, settings: &Settings) -> Settings {
+        Settings {
+            tie_breaking: self.tie_breaking.unwrap_or(settings.tie_breaking),
+            mechanism: self.mechanism.unwrap_or(settings.mechanism),
+            proposing: self.proposing.unwrap_or(settings.proposing),
+            ..settings.clone()
+        }
+    }
+
+    /// The run left once `user` of `session` withdrew from this one, see
+    /// [`crate::withdraw`]. The new run follows the users and teams of `session`.
+    pub fn withdraw(&self, id: Uuid, created_at: i64, session: &Session, user: usize) -> Self {
+        let session = Session {
+            settings: self.settings(&session.settings),
+            ..session.clone()
+        };
+        let assignment = self.assignment_in(&session);
+        let (assignment, withdrawal) = withdraw(&session, self.seed.unwrap_or(0), &assignment, user);
+        Self {
+            previous: Some(self.id),
+            withdrawal: Some(withdrawal),
+            ..Run::new(id, created_at, WAITLIST, self.seed, &session, assignment)
+        }
+    }
+}
+
+/// What participants see of a run: the members of each team, by name only.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PublishedRun {
+    pub id: Uuid,
+    pub session: String,
+    /// Creation time of the run, in milliseconds since the Unix epoch.
+    pub created_at: i64,
+    pub teams: Vec<PublishedTeam>,
+    pub unassigned: Vec<String>,
+    /// Users who withdrew since the results were published, oldest first.
+    #[serde(default)]
+    pub withdrawn: Vec<String>,
+    /// Users moved up from a waitlist since, oldest first.
+    #[serde(default)]
+    pub promotions: Vec<PublishedPromotion>,
+    /// User claimed by the caller, who may withdraw.
+    #[serde(default)]
+    pub own: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PublishedTeam {
+    pub name: String,
+    pub members: Vec<String>,
+    /// Users waiting for a seat, first in line first. Left empty when the
+    /// preferences are private, as it would reveal them.
+    #[serde(default)]
+    pub waitlist: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PublishedPromotion {
+    pub user: String,
+    /// Team the user left, `None` if they had none.
+    pub from: Option<String>,
+    pub to: String,
+}
+
+impl PublishedRun {
+    /// A published run as it stands after the runs derived from it by withdrawals,
+    /// oldest first. Users and teams are named as they were at the time of each run,
+    /// even if they were renamed or removed from the session since.
+    pub fn new(session: &Session, published: &Run, derived: &[Run]) -> Self {
+        let run = derived.last().unwrap_or(published);
+        let names = |users: &[usize]| users.iter().filter_map(|&user| run.user_name(session, user)).collect();
+        // Waitlists are computed over the session as it is now, since users join them
+        // by their current preferences, then matched to the teams of the run by name
+        let waitlists = if session.settings.waitlists && !session.settings.private_preferences {
+            let session = Session {
+                settings: run.settings(&session.settings),
+                ..session.clone()
+            };
+            waitlists(&session, run.seed.unwrap_or(0), &run.assignment_in(&session))
+        } else {
+            Vec::new()
+        };
+        let waitlist = |team: usize| -> Vec<String> {
+            run.team_in(session, team)
+                .and_then(|team| waitlists.get(team))
+                .map(|users| users.iter().filter_map(|&user| session.users.get(user).cloned()).collect())
+                .unwrap_or_default()
+        };
+        // Each withdrawal is named after the run it brought
+        let withdrawals = || {
+            derived
+                .iter()
+                .filter_map(|run| run.withdrawal.as_ref().map(|withdrawal| (run, withdrawal)))
+        };
+        Self {
+            id: run.id,
+            session: session.name.clone(),
+            created_at: published.created_at,
+            teams: run
+                .assignment
+                .teams
+                .iter()
+                .enumerate()
+                .filter_map(|(team, members)| {
+                    Some(PublishedTeam {
+                        name: run.team_name(session, team)?,
+                        members: names(members),
+                        waitlist: waitlist(team),
+                    })
+                })
+                .collect(),
+            unassigned: names(&run.assignment.unassigned),
+            withdrawn: withdrawals()
+                .filter_map(|(run, withdrawal)| run.user_name(session, withdrawal.user))
+                .collect(),
+            promotions: withdrawals()
+                .flat_map(|(run, withdrawal)| withdrawal.promotions.iter().map(move |promotion| (run, promotion)))
+                .filter_map(|(run, promotion)| {
+                    Some(PublishedPromotion {
+                        user: run.user_name(session, promotion.user)?,
+                        from: promotion.from.and_then(|team| run.team_name(session, team)),
+                        to: run.team_name(session, promotion.to)?,
+                    })
+                })
+                .collect(),
+            own: None,
+        }
+    }
+}
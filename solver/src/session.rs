@@ -0,0 +1,379 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::attributes::{Attributes, RuleKind};
+use crate::constraints::Constraints;
+use crate::gale_shapley::Proposing;
+use crate::lottery::TieBreaking;
+use crate::mechanism::Mechanism;
+use crate::privacy::Aggregates;
+use crate::roles::Roles;
+use crate::settings::Settings;
+
+/// A teams ordering session: the users, the teams and the users' preferences.
+///
+/// `preferences[user][team]` is the rank (starting at 1) given by `user` to `team`,
+/// 0 meaning that no preference was set yet.
+///
+/// The fields after `preferences` were added later, hence their default for
+/// sessions saved before.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub name: String,
+    pub users: Vec<String>,
+    pub teams: Vec<String>,
+    pub teams_size: Vec<usize>,
+    pub preferences: Vec<Vec<usize>>,
+    #[serde(default)]
+    pub constraints: Constraints,
+    #[serde(default)]
+    pub settings: Settings,
+    /// `members[user]` is the identity key of the participant who claimed the user,
+    /// to fill in their preferences in a private session.
+    #[serde(default)]
+    pub members: Vec<Option<Uuid>>,
+    #[serde(default)]
+    pub attributes: Attributes,
+    #[serde(default)]
+    pub roles: Roles,
+}
+
+impl Session {
+    /// A new session with two users and two teams to start from.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            users: vec!["User 1".to_string(), "User 2".to_string()],
+            teams: vec!["Team 1".to_string(), "Team 2".to_string()],
+            teams_size: vec![1, 1],
+            preferences: vec![vec![0; 2]; 2],
+            constraints: Constraints::default(),
+            settings: Settings::default(),
+            members: Vec::new(),
+            attributes: Attributes::default(),
+            roles: Roles::default(),
+        }
+    }
+
+    /// Add a user at the end, with no preference set yet.
+    pub fn add_user(&mut self, name: String) {
+        self.users.push(name);
+        // The preferences of a private session are filled in by the participants only
+        let prefs = if self.settings.private_preferences {
+            Vec::new()
+        } else {
+            vec![0; self.teams.len()]
+        };
+        self.preferences.push(prefs);
+    }
+
+    /// Add a team at the end, ranked by nobody yet.
+    pub fn add_team(&mut self, name: String, size: usize) {
+        self.teams.push(name);
+        self.teams_size.push(size);
+        // Hidden rows of a private session stay empty
+        let private = self.settings.private_preferences;
+        for prefs in self.preferences.iter_mut().filter(|prefs| !private || !prefs.is_empty()) {
+            prefs.push(0);
+        }
+    }
+
+    /// Remove a user along with everything about them.
+    pub fn remove_user(&mut self, user: usize) {
+        if user >= self.users.len() {
+            return;
+        }
+        self.users.remove(user);
+        if user < self.preferences.len() {
+            self.preferences.remove(user);
+        }
+        if user < self.members.len() {
+            self.members.remove(user);
+        }
+        self.constraints.remove_user(user);
+        self.attributes.remove_user(user);
+        self.roles.remove_user(user);
+    }
+
+    /// Remove a team along with its ranks and constraints.
+    pub fn remove_team(&mut self, team: usize) {
+        if team >= self.teams.len() {
+            return;
+        }
+        self.teams.remove(team);
+        if team < self.teams_size.len() {
+            self.teams_size.remove(team);
+        }
+        for prefs in self.preferences.iter_mut().filter(|prefs| team < prefs.len()) {
+            prefs.remove(team);
+        }
+        self.constraints.remove_team(team);
+        self.roles.remove_team(team);
+    }
+
+    /// The user claimed by a participant, if any.
+    pub fn member_of(&self, identity: Uuid) -> Option<usize> {
+        self.members.iter().position(|&member| member == Some(identity))
+    }
+
+    /// Total number of slots over all teams.
+    pub fn capacity(&self) -> usize {
+        self.teams_size.iter().take(self.teams.len()).sum()
+    }
+
+    /// Fingerprint of everything a matching depends on but the seed, i.e. all but
+    /// the name, the members and the privacy.
+    ///
+    /// 64-bit FNV-1a over a fixed-width encoding, so that the frontend (wasm32)
+    /// and the backend agree on it.
+    pub fn input_hash(&self) -> String {
+        let mut hash = Fnv::default();
+        // Every list is preceded by its length, so that moving an item from one
+        // list to the next changes the fingerprint
+        for names in [&self.users, &self.teams] {
+            hash.write_usize(names.len());
+            for name in names {
+                hash.write_usize(name.len());
+                hash.write(name.as_bytes());
+            }
+        }
+        hash.write_list(&self.teams_size);
+        hash.write_usize(self.preferences.len());
+        for prefs in &self.preferences {
+            hash.write_list(prefs);
+        }
+        hash.write_usize(self.constraints.pins.len());
+        for pin in &self.constraints.pins {
+            hash.write_usize(pin.map_or(0, |team| team + 1));
+        }
+        hash.write_usize(self.constraints.forbidden.len());
+        for forbidden in &self.constraints.forbidden {
+            hash.write_usize(forbidden.len());
+            hash.write(&forbidden.iter().map(|&f| f as u8).collect::<Vec<_>>());
+        }
+        // Only hashed when set, so that older runs keep their fingerprint
+        let priorities = &self.constraints.priorities;
+        if priorities.iter().flatten().any(|&rank| rank > 0) {
+            hash.write_usize(priorities.len());
+            for priorities in priorities {
+                hash.write_list(priorities);
+            }
+        }
+        hash.write_usize(self.constraints.groups.len());
+        for group in &self.constraints.groups {
+            hash.write_list(group);
+        }
+        hash.write_usize(self.constraints.apart.len());
+        for &(a, b) in &self.constraints.apart {
+            hash.write_list(&[a, b]);
+        }
+        // Attributes only matter through the balance rules
+        if !self.constraints.balance.is_empty() {
+            hash.write_usize(self.attributes.values.len());
+            for values in &self.attributes.values {
+                hash.write_usize(values.len());
+                for value in values {
+                    hash.write_usize(value.len());
+                    hash.write(value.as_bytes());
+                }
+            }
+            hash.write_usize(self.constraints.balance.len());
+            for rule in &self.constraints.balance {
+                hash.write_usize(rule.team.map_or(0, |team| team + 1));
+                hash.write_usize(rule.attribute);
+                match &rule.kind {
+                    RuleKind::AtLeast { value, count } => {
+                        hash.write_usize(value.len());
+                        hash.write(value.as_bytes());
+                        hash.write_usize(*count);
+                    }
+                    RuleKind::AtMostShare { percent } => hash.write_usize(*percent),
+                }
+            }
+        }
+        // Settings too are only hashed when they differ from their default
+        if self.settings.tie_breaking != TieBreaking::Order {
+            hash.write_usize(self.settings.tie_breaking as usize);
+        }
+        if self.settings.proposing != Proposing::Users {
+            hash.write_usize(self.settings.proposing as usize);
+        }
+        if !self.roles.is_empty() {
+            hash.write_usize(self.roles.skills.len());
+            for skills in &self.roles.skills {
+                hash.write_usize(skills.len());
+                for skill in skills {
+                    hash.write_usize(skill.len());
+                    hash.write(skill.as_bytes());
+                }
+            }
+            hash.write_usize(self.roles.requirements.len());
+            for requirements in &self.roles.requirements {
+                hash.write_usize(requirements.len());
+                for requirement in requirements {
+                    hash.write_usize(requirement.role.len());
+                    hash.write(requirement.role.as_bytes());
+                    hash.write_usize(requirement.count);
+                }
+            }
+            hash.write_usize(self.settings.fill_roles as usize);
+        }
+        if self.settings.mechanism != Mechanism::GaleShapley {
+            hash.write_usize(self.settings.mechanism as usize);
+        }
+        // The attribute ordering the users only matters to priority serial dictatorship
+        let order_by = self.settings.order_by.as_ref();
+        if let Some(name) = order_by.filter(|_| self.settings.mechanism == Mechanism::PrioritySerialDictatorship) {
+            hash.write_usize(name.len());
+            hash.write(name.as_bytes());
+            if let Some(attribute) = self.attributes.names.iter().position(|other| other == name) {
+                for user in 0..self.users.len() {
+                    let value = self.attributes.value(user, attribute).unwrap_or_default();
+                    hash.write_usize(value.len());
+                    hash.write(value.as_bytes());
+                }
+            }
+        }
+        format!("{:016x}", hash.0)
+    }
+}
+
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write(&(value as u64).to_le_bytes());
+    }
+
+    fn write_list(&mut self, values: &[usize]) {
+        self.write_usize(values.len());
+        for &value in values {
+            self.write_usize(value);
+        }
+    }
+}
+
+/// What is shown of a session in a list, without its full content.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: Uuid,
+    pub name: String,
+    /// Last edit time, in milliseconds since the Unix epoch.
+    pub updated_at: i64,
+    pub users: usize,
+    pub teams: usize,
+}
+
+impl SessionSummary {
+    pub fn new(id: Uuid, session: &Session, updated_at: i64) -> Self {
+        Self {
+            id,
+            name: session.name.clone(),
+            updated_at,
+            users: session.users.len(),
+            teams: session.teams.len(),
+        }
+    }
+}
+
+/// A change of a session, as streamed by the backend to the clients following it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionEvent {
+    /// Time of the change, in milliseconds since the Unix epoch.
+    pub at: i64,
+    /// Random key of the browser tab which made the change, so that it can ignore
+    /// its own changes.
+    #[serde(default)]
+    pub client: Option<Uuid>,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+/// What changed in a session. Events only say what changed, clients fetch the
+/// session or its runs again for the rest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    Created,
+    /// The organiser edited the session.
+    Edited,
+    /// A participant saved their preferences, as the user they claimed if any.
+    Submitted { user: Option<String> },
+    /// The organiser recorded a run, which participants can be shown.
+    RunRecorded { run: Uuid, solver: String },
+    /// A user withdrew from a published run, recorded as a new run.
+    Withdrawn { run: Uuid, user: String },
+    Deleted,
+    /// The session was deleted after it was not edited for too long.
+    Expired,
+}
+
+impl Change {
+    /// Names of all the changes, see [`Change::name`].
+    pub const NAMES: [&str; 7] = [
+        "created",
+        "edited",
+        "submitted",
+        "run_recorded",
+        "withdrawn",
+        "deleted",
+        "expired",
+    ];
+
+    /// Name of the change, as the type of server-sent events.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Change::Created => "created",
+            Change::Edited => "edited",
+            Change::Submitted { .. } => "submitted",
+            Change::RunRecorded { .. } => "run_recorded",
+            Change::Withdrawn { .. } => "withdrawn",
+            Change::Deleted => "deleted",
+            Change::Expired => "expired",
+        }
+    }
+
+    /// Whether the session is gone after this change.
+    pub fn is_final(&self) -> bool {
+        matches!(self, Change::Deleted | Change::Expired)
+    }
+}
+
+/// What the caller may do on a session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    /// Created the session or was given the organiser link: may edit the teams.
+    Organiser,
+    /// Joined with the participant link or code.
+    #[default]
+    Participant,
+}
+
+/// A session as returned by the backend.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionResource {
+    pub id: Uuid,
+    pub session: Session,
+    pub role: Role,
+    /// Short human-friendly code to join the session.
+    pub join_code: String,
+    /// Aggregated preferences, for the organiser of a private session who cannot see them.
+    #[serde(default)]
+    pub aggregates: Option<Aggregates>,
+    /// Version of the session, bumped by every change and sent as its `ETag`.
+    #[serde(default)]
+    pub version: i64,
+}
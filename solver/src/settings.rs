@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::gale_shapley::Proposing;
+use crate::lottery::TieBreaking;
+use crate::mechanism::Mechanism;
+use crate::roles::ROLES_FIRST;
+
+/// Options of a session, chosen by its organiser.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    /// Participants only see and edit the preferences of the user they claimed,
+    /// and the organiser only sees aggregates.
+    #[serde(default)]
+    pub private_preferences: bool,
+    #[serde(default)]
+    pub tie_breaking: TieBreaking,
+    #[serde(default)]
+    pub mechanism: Mechanism,
+    /// Name of the attribute ordering the users in priority serial dictatorship.
+    #[serde(default)]
+    pub order_by: Option<String>,
+    #[serde(default)]
+    pub proposing: Proposing,
+    /// Fill the roles required by the teams before the rest of their slots.
+    #[serde(default)]
+    pub fill_roles: bool,
+    /// Keep waitlists once the results are published, promoting users when
+    /// others withdraw.
+    #[serde(default)]
+    pub waitlists: bool,
+}
+
+impl Settings {
+    /// Whether sorting the teams draws lots, whose seed is then kept with each run.
+    pub fn uses_seed(&self) -> bool {
+        self.tie_breaking.is_random() || self.mechanism.is_random()
+    }
+
+    /// Name of the solver sorting the teams, as recorded in runs.
+    pub fn solver(&self) -> &'static str {
+        if self.mechanism != Mechanism::GaleShapley {
+            self.mechanism.label()
+        } else if self.fill_roles {
+            ROLES_FIRST
+        } else {
+            self.proposing.solver()
+        }
+    }
+}
@@ -0,0 +1,525 @@
+use serde::{Deserialize, Serialize};
+
+use crate::attributes::RuleKind;
+use crate::market::pinned;
+use crate::mechanism::Mechanism;
+use crate::session::Session;
+
+/// Whether an issue prevents the teams from being sorted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueKind {
+    /// The rank given by a user to a team is not between 1 and the number of teams.
+    MissingPreference,
+    /// A user gave the same rank to two teams.
+    DuplicatePreference { other_team: usize },
+    /// A team size is 0 or greater than the number of users.
+    InvalidTeamSize,
+    /// There are fewer team slots than users.
+    InsufficientCapacity { capacity: usize, users: usize },
+    /// A user or a team has an empty name.
+    EmptyName,
+    /// Two users or two teams share the same name.
+    DuplicateName { other: usize },
+    /// A user is pinned to a team they are forbidden from.
+    PinnedToForbiddenTeam,
+    /// More users are pinned to a team than it has slots.
+    TooManyPinned { pinned: usize },
+    /// A user is forbidden from every team.
+    NoAllowedTeam,
+    /// Two users of the same group asked to be apart.
+    ApartInGroup { other: usize },
+    /// Two users who asked to be apart are pinned to the same team.
+    ApartPinnedTogether { other: usize },
+    /// Two users of the same group are pinned to different teams.
+    GroupPinnedApart { other: usize },
+    /// A user is forbidden from the team another member of their group is pinned to.
+    GroupPinnedToForbiddenTeam { other: usize },
+    /// A group has more members than any team has slots.
+    GroupTooLarge { members: Vec<usize> },
+    /// Every team is forbidden to some member of a group.
+    NoTeamForGroup { members: Vec<usize> },
+    /// A balance rule cannot hold for every team it applies to, whatever the assignment.
+    UnsatisfiableRule { rule: usize },
+    /// A team requires more roles than it has slots.
+    TooManyRoles { required: usize },
+    /// Fewer users have a skill than the teams require.
+    MissingSkill { role: String, missing: usize },
+    /// The mechanism picked ignores the groups and the users asked to be apart.
+    GroupsIgnored,
+}
+
+/// A single validation issue, located on a user (row), a team (column) or both (cell).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub user: Option<usize>,
+    pub team: Option<usize>,
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        // The sorting follows the balance rules as much as it can
+        if is_name(self)
+            || matches!(
+                self.kind,
+                IssueKind::UnsatisfiableRule { .. } | IssueKind::MissingSkill { .. } | IssueKind::GroupsIgnored
+            )
+        {
+            Severity::Warning
+        } else {
+            Severity::Error
+        }
+    }
+
+    /// Human-readable description of the issue.
+    pub fn message(&self, session: &Session) -> String {
+        let user = |idx: usize| session.users.get(idx).map_or("?", String::as_str);
+        let team = |idx: usize| session.teams.get(idx).map_or("?", String::as_str);
+        match (&self.kind, self.user, self.team) {
+            (IssueKind::MissingPreference, Some(u), Some(t)) => {
+                format!("{} has no preference for {}", user(u), team(t))
+            }
+            (IssueKind::DuplicatePreference { other_team }, Some(u), Some(t)) => format!(
+                "{} has duplicate preferences for {} and {}",
+                user(u),
+                team(t),
+                team(*other_team)
+            ),
+            (IssueKind::InvalidTeamSize, _, Some(t)) => {
+                format!("{} has invalid team size", team(t))
+            }
+            (IssueKind::InsufficientCapacity { capacity, users }, _, _) => format!(
+                "Total team size ({}) is less than number of users ({})",
+                capacity, users
+            ),
+            (IssueKind::EmptyName, Some(u), _) => format!("User {} has no name", u + 1),
+            (IssueKind::EmptyName, _, Some(t)) => format!("Team {} has no name", t + 1),
+            (IssueKind::DuplicateName { other }, Some(u), _) => format!(
+                "Users {} and {} are both named {}",
+                other + 1,
+                u + 1,
+                user(u)
+            ),
+            (IssueKind::DuplicateName { other }, _, Some(t)) => format!(
+                "Teams {} and {} are both named {}",
+                other + 1,
+                t + 1,
+                team(t)
+            ),
+            (IssueKind::PinnedToForbiddenTeam, Some(u), Some(t)) => {
+                format!("{} is pinned to {} but forbidden from it", user(u), team(t))
+            }
+            (IssueKind::TooManyPinned { pinned }, _, Some(t)) => format!(
+                "{} users are pinned to {} which has only {} slot(s)",
+                pinned,
+                team(t),
+                session.teams_size.get(t).copied().unwrap_or(0)
+            ),
+            (IssueKind::NoAllowedTeam, Some(u), _) => {
+                format!("{} is forbidden from every team", user(u))
+            }
+            (IssueKind::ApartInGroup { other }, Some(u), _) => format!(
+                "{} and {} are in the same group but asked to be apart",
+                user(*other),
+                user(u)
+            ),
+            (IssueKind::ApartPinnedTogether { other }, Some(u), Some(t)) => format!(
+                "{} and {} asked to be apart but are both pinned to {}",
+                user(*other),
+                user(u),
+                team(t)
+            ),
+            (IssueKind::GroupPinnedApart { other }, Some(u), _) => format!(
+                "{} and {} are in the same group but pinned to different teams",
+                user(*other),
+                user(u)
+            ),
+            (IssueKind::GroupPinnedToForbiddenTeam { other }, Some(u), Some(t)) => format!(
+                "{} is forbidden from {}, where {} of their group is pinned",
+                user(u),
+                team(t),
+                user(*other)
+            ),
+            (IssueKind::GroupTooLarge { members }, _, _) => format!(
+                "The group of {} is larger than every team",
+                members.iter().map(|&u| user(u)).collect::<Vec<_>>().join(", ")
+            ),
+            (IssueKind::NoTeamForGroup { members }, _, _) => format!(
+                "No team is allowed to every member of the group of {}",
+                members.iter().map(|&u| user(u)).collect::<Vec<_>>().join(", ")
+            ),
+            (IssueKind::UnsatisfiableRule { rule }, _, _) => match session.constraints.balance.get(*rule) {
+                Some(rule) => format!(
+                    "The rule \"{}\" cannot hold for every team",
+                    rule.describe(&session.attributes, &session.teams)
+                ),
+                None => format!("Balance rule {} cannot hold", rule + 1),
+            },
+            (IssueKind::TooManyRoles { required }, _, Some(t)) => format!(
+                "{} requires {} roles but has only {} slot(s)",
+                team(t),
+                required,
+                session.teams_size.get(t).copied().unwrap_or(0)
+            ),
+            (IssueKind::MissingSkill { role, missing }, _, _) => {
+                format!("{} more user(s) with the skill {} are needed", missing, role)
+            }
+            (IssueKind::GroupsIgnored, _, _) => format!(
+                "{} ignores the groups and the users asked to be apart",
+                session.settings.mechanism.label()
+            ),
+            (kind, _, _) => format!("{:?}", kind),
+        }
+    }
+}
+
+/// All the issues found in a session.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validation {
+    pub issues: Vec<Issue>,
+}
+
+impl Validation {
+    /// A session is valid when it has no error; warnings are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.error_count() == 0
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Error)
+            .count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Warning)
+            .count()
+    }
+
+    /// Worst severity of the issues located exactly on a preference cell.
+    pub fn cell(&self, user: usize, team: usize) -> Option<Severity> {
+        self.worst(|issue| issue.user == Some(user) && issue.team == Some(team))
+    }
+
+    /// Severity of the issue on a user's name, if any.
+    pub fn user_name(&self, user: usize) -> Option<Severity> {
+        self.worst(|issue| issue.user == Some(user) && issue.team.is_none())
+    }
+
+    /// Severity of the issue on a team's name, if any.
+    pub fn team_name(&self, team: usize) -> Option<Severity> {
+        self.worst(|issue| issue.team == Some(team) && issue.user.is_none() && is_name(issue))
+    }
+
+    /// Severity of the issue on a team's size, if any.
+    pub fn team_size(&self, team: usize) -> Option<Severity> {
+        self.worst(|issue| {
+            issue.team == Some(team)
+                && matches!(
+                    issue.kind,
+                    IssueKind::InvalidTeamSize
+                        | IssueKind::TooManyPinned { .. }
+                        | IssueKind::TooManyRoles { .. }
+                )
+        })
+    }
+
+    /// Worst severity of the issues involving a user (its name or one of its preferences).
+    pub fn user(&self, user: usize) -> Option<Severity> {
+        self.worst(|issue| issue.user == Some(user))
+    }
+
+    /// Worst severity of the issues involving a team (its name, size or a preference for it).
+    pub fn team(&self, team: usize) -> Option<Severity> {
+        self.worst(|issue| issue.team == Some(team))
+    }
+
+    fn worst(&self, filter: impl Fn(&Issue) -> bool) -> Option<Severity> {
+        self.issues
+            .iter()
+            .filter(|issue| filter(issue))
+            .map(Issue::severity)
+            .min_by_key(|severity| match severity {
+                Severity::Error => 0,
+                Severity::Warning => 1,
+            })
+    }
+}
+
+fn is_name(issue: &Issue) -> bool {
+    matches!(
+        issue.kind,
+        IssueKind::EmptyName | IssueKind::DuplicateName { .. }
+    )
+}
+
+/// Check a session and collect every issue instead of stopping at the first one.
+pub fn validate(session: &Session) -> Validation {
+    let mut issues = Vec::new();
+    let n_teams = session.teams.len();
+
+    for user in 0..session.users.len() {
+        let prefs = session.preferences.get(user).map_or(&[][..], Vec::as_slice);
+        // Preferences hidden from the caller of a private session cannot be checked
+        if session.settings.private_preferences && prefs.is_empty() {
+            continue;
+        }
+        for team in 0..n_teams {
+            let rank = prefs.get(team).copied().unwrap_or(0);
+            // Check if preference is in range
+            if rank < 1 || rank > n_teams {
+                issues.push(Issue {
+                    kind: IssueKind::MissingPreference,
+                    user: Some(user),
+                    team: Some(team),
+                });
+                continue;
+            }
+            // Check if preference is unique, reporting each cell of a duplicate
+            if let Some(other_team) =
+                (0..n_teams).find(|&k| k != team && prefs.get(k) == Some(&rank))
+            {
+                issues.push(Issue {
+                    kind: IssueKind::DuplicatePreference { other_team },
+                    user: Some(user),
+                    team: Some(team),
+                });
+            }
+        }
+    }
+
+    for team in 0..n_teams {
+        let size = session.teams_size.get(team).copied().unwrap_or(0);
+        // Check if team size is valid
+        if size < 1 || size > session.users.len() {
+            issues.push(Issue {
+                kind: IssueKind::InvalidTeamSize,
+                user: None,
+                team: Some(team),
+            });
+        }
+    }
+
+    let capacity = session.capacity();
+    if capacity < session.users.len() {
+        issues.push(Issue {
+            kind: IssueKind::InsufficientCapacity {
+                capacity,
+                users: session.users.len(),
+            },
+            user: None,
+            team: None,
+        });
+    }
+
+    check_constraints(session, &mut issues);
+    check_groups(session, &mut issues);
+    check_balance(session, &mut issues);
+    check_roles(session, &mut issues);
+    // Probabilistic serial draws each user on their own
+    if session.settings.mechanism == Mechanism::ProbabilisticSerial && session.constraints.has_groups() {
+        issues.push(Issue {
+            kind: IssueKind::GroupsIgnored,
+            user: None,
+            team: None,
+        });
+    }
+    check_names(&session.users, &mut issues, |idx| (Some(idx), None));
+    check_names(&session.teams, &mut issues, |idx| (None, Some(idx)));
+
+    Validation { issues }
+}
+
+fn check_names(
+    names: &[String],
+    issues: &mut Vec<Issue>,
+    locate: impl Fn(usize) -> (Option<usize>, Option<usize>),
+) {
+    for (idx, name) in names.iter().enumerate() {
+        let kind = if name.trim().is_empty() {
+            IssueKind::EmptyName
+        } else if let Some(other) = names[..idx].iter().position(|n| n.trim() == name.trim()) {
+            IssueKind::DuplicateName { other }
+        } else {
+            continue;
+        };
+        let (user, team) = locate(idx);
+        issues.push(Issue { kind, user, team });
+    }
+}
+
+fn check_constraints(session: &Session, issues: &mut Vec<Issue>) {
+    let constraints = &session.constraints;
+    let n_teams = session.teams.len();
+    let mut counts = vec![0; n_teams];
+
+    for (user, pinned_team) in pinned(session).into_iter().enumerate() {
+        match pinned_team {
+            Some(team) => {
+                counts[team] += 1;
+                // Group members forbidden from the team are reported with their group
+                if constraints.pin(user) == Some(team) && constraints.is_forbidden(user, team) {
+                    issues.push(Issue {
+                        kind: IssueKind::PinnedToForbiddenTeam,
+                        user: Some(user),
+                        team: Some(team),
+                    });
+                }
+            }
+            None if n_teams > 0 && (0..n_teams).all(|team| constraints.is_forbidden(user, team)) => {
+                issues.push(Issue {
+                    kind: IssueKind::NoAllowedTeam,
+                    user: Some(user),
+                    team: None,
+                });
+            }
+            None => {}
+        }
+    }
+
+    for (team, &pinned) in counts.iter().enumerate() {
+        if pinned > session.teams_size.get(team).copied().unwrap_or(0) {
+            issues.push(Issue {
+                kind: IssueKind::TooManyPinned { pinned },
+                user: None,
+                team: Some(team),
+            });
+        }
+    }
+}
+
+fn check_groups(session: &Session, issues: &mut Vec<Issue>) {
+    let constraints = &session.constraints;
+    let n_teams = session.teams.len();
+    let largest = session.teams_size.iter().copied().max().unwrap_or(0);
+    let pinned_teams = pinned(session);
+    let pinned_team = |user: usize| pinned_teams.get(user).copied().flatten();
+
+    for &(a, b) in &constraints.apart {
+        if constraints.group_of(a).is_some() && constraints.group_of(a) == constraints.group_of(b) {
+            issues.push(Issue {
+                kind: IssueKind::ApartInGroup { other: a },
+                user: Some(b),
+                team: None,
+            });
+        }
+        if let Some(team) = pinned_team(a).filter(|&team| pinned_team(b) == Some(team)) {
+            issues.push(Issue {
+                kind: IssueKind::ApartPinnedTogether { other: a },
+                user: Some(b),
+                team: Some(team),
+            });
+        }
+    }
+
+    for group in &constraints.groups {
+        let mut pinned = group.iter().filter_map(|&user| constraints.pin(user).map(|team| (user, team)));
+        if let Some((first, team)) = pinned.next() {
+            if let Some((user, _)) = pinned.find(|&(_, other_team)| other_team != team) {
+                issues.push(Issue {
+                    kind: IssueKind::GroupPinnedApart { other: first },
+                    user: Some(user),
+                    team: None,
+                });
+            }
+            // The whole group follows the pinned member
+            for &user in group.iter().filter(|&&user| user != first && constraints.pin(user).is_none()) {
+                if constraints.is_forbidden(user, team) {
+                    issues.push(Issue {
+                        kind: IssueKind::GroupPinnedToForbiddenTeam { other: first },
+                        user: Some(user),
+                        team: Some(team),
+                    });
+                }
+            }
+        }
+        if group.len() > largest {
+            issues.push(Issue {
+                kind: IssueKind::GroupTooLarge { members: group.clone() },
+                user: None,
+                team: None,
+            });
+        }
+        let allowed = |team: usize| group.iter().all(|&user| !constraints.is_forbidden(user, team));
+        if n_teams > 0 && !(0..n_teams).any(allowed) {
+            issues.push(Issue {
+                kind: IssueKind::NoTeamForGroup { members: group.clone() },
+                user: None,
+                team: None,
+            });
+        }
+    }
+}
+
+fn check_balance(session: &Session, issues: &mut Vec<Issue>) {
+    let n_users = session.users.len();
+    for (idx, rule) in session.constraints.balance.iter().enumerate() {
+        let teams: Vec<usize> = (0..session.teams.len()).filter(|&team| rule.applies_to(team)).collect();
+        let unsatisfiable = rule.attribute >= session.attributes.names.len()
+            || match &rule.kind {
+                RuleKind::AtLeast { value, count } => {
+                    let holders = (0..n_users)
+                        .filter(|&user| session.attributes.value(user, rule.attribute) == Some(value.trim()))
+                        .count();
+                    holders < count * teams.len()
+                        || teams
+                            .iter()
+                            .any(|&team| session.teams_size.get(team).copied().unwrap_or(0) < *count)
+                }
+                RuleKind::AtMostShare { percent } => *percent > 100,
+            };
+        if unsatisfiable {
+            issues.push(Issue {
+                kind: IssueKind::UnsatisfiableRule { rule: idx },
+                user: None,
+                team: rule.team,
+            });
+        }
+    }
+}
+
+fn check_roles(session: &Session, issues: &mut Vec<Issue>) {
+    let roles = &session.roles;
+    // Users needed for each role over every team
+    let mut needed: Vec<(&str, usize)> = Vec::new();
+    for team in 0..session.teams.len() {
+        let requirements = roles.requirements(team);
+        let required: usize = requirements.iter().map(|requirement| requirement.count).sum();
+        if required > session.teams_size.get(team).copied().unwrap_or(0) {
+            issues.push(Issue {
+                kind: IssueKind::TooManyRoles { required },
+                user: None,
+                team: Some(team),
+            });
+        }
+        for requirement in requirements {
+            let role = requirement.role.trim();
+            match needed.iter_mut().find(|(known, _)| *known == role) {
+                Some((_, count)) => *count += requirement.count,
+                None => needed.push((role, requirement.count)),
+            }
+        }
+    }
+    for (role, count) in needed {
+        let skilled = (0..session.users.len()).filter(|&user| roles.has_skill(user, role)).count();
+        if skilled < count {
+            issues.push(Issue {
+                kind: IssueKind::MissingSkill {
+                    role: role.to_string(),
+                    missing: count - skilled,
+                },
+                user: None,
+                team: None,
+            });
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Waitlists kept once the results are published, and the promotions they bring
+//! when users withdraw.
+//!
+//! A user waits for every team they would rather be in than the team they got,
+//! in the order the team prefers its users. When a seat frees up, the first user
+//! waiting for it moves in, which frees their own seat for the next waitlist, and
+//! so on down the chain.
+
+use serde::{Deserialize, Serialize};
+
+use crate::assignment::Assignment;
+use crate::market::Market;
+use crate::session::Session;
+
+/// Name of the solver of a run derived from another by a withdrawal.
+pub const WAITLIST: &str = "Waitlist promotion";
+
+/// A user moved up from a waitlist after a withdrawal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Promotion {
+    pub user: usize,
+    /// Team the user left, `None` if they had none.
+    pub from: Option<usize>,
+    pub to: usize,
+}
+
+/// A user leaving the teams after the results were published, and the
+/// promotions it brought.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Withdrawal {
+    pub user: usize,
+    pub promotions: Vec<Promotion>,
+}
+
+/// Users waiting for a team, first in line first.
+///
+/// Users out of the assignment, e.g. who withdrew, pinned users and users in a
+/// group, who cannot move alone, wait for no team, nor do users who asked to be
+/// apart from a member of the team. Teams out of the session have no waitlist, and
+/// users in such a team wait for no other.
+fn waitlist(market: &Market, session: &Session, assignment: &Assignment, team: usize) -> Vec<usize> {
+    let constraints = &session.constraints;
+    let (Some(candidates), Some(members)) = (market.team_lists.get(team), assignment.teams.get(team)) else {
+        return Vec::new();
+    };
+    candidates
+        .iter()
+        .copied()
+        .filter(|&user| {
+            let current = assignment.team_of(user);
+            let present = current.is_some() || assignment.unassigned.contains(&user);
+            present
+                && current != Some(team)
+                && constraints.group_of(user).is_none()
+                && current.is_none_or(|current| current < market.n_teams && market.user_prefers(user, team, current))
+                && !members.iter().any(|&other| constraints.is_apart(user, other))
+        })
+        .collect()
+}
+
+/// The waitlist of every team, for an assignment computed with `seed`.
+pub fn waitlists(session: &Session, seed: u64, assignment: &Assignment) -> Vec<Vec<usize>> {
+    let market = Market::new(session, seed);
+    (0..market.n_teams.min(assignment.teams.len()))
+        .map(|team| waitlist(&market, session, assignment, team))
+        .collect()
+}
+
+/// Take a user out of an assignment computed with `seed`, promoting the users
+/// waiting for the seats freed in turn.
+///
+/// Without waitlists in the settings of the session, the seat is left empty.
+pub fn withdraw(session: &Session, seed: u64, assignment: &Assignment, user: usize) -> (Assignment, Withdrawal) {
+    let market = Market::new(session, seed);
+    let mut assignment = assignment.clone();
+    let mut freed = assignment.team_of(user);
+    for members in assignment.teams.iter_mut() {
+        members.retain(|&member| member != user);
+    }
+    assignment.unassigned.retain(|&member| member != user);
+
+    let mut promotions = Vec::new();
+    if session.settings.waitlists {
+        while let Some(team) = freed.filter(|&team| team < market.n_teams) {
+            let size = session.teams_size.get(team).copied().unwrap_or(0);
+            if assignment.teams.get(team).is_none_or(|members| members.len() >= size) {
+                break;
+            }
+            let Some(&next) = waitlist(&market, session, &assignment, team).first() else {
+                break;
+            };
+            let from = assignment.team_of(next);
+            assignment.move_user(next, Some(team));
+            promotions.push(Promotion { user: next, from, to: team });
+            freed = from;
+        }
+    }
+    (assignment, Withdrawal { user, promotions })
+}
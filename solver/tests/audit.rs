@@ -0,0 +1,92 @@
+//! Edits recorded in the audit log of a session.
+
+mod common;
+
+use solver::audit::edits;
+use solver::{Actor, AuditEdit, AuditEntry, Change, Field, Role};
+use uuid::Uuid;
+
+use common::base;
+
+fn edit(field: Field, user: Option<&str>, team: Option<&str>, before: Option<&str>, after: Option<&str>) -> AuditEdit {
+    AuditEdit {
+        field,
+        user: user.map(str::to_string),
+        team: team.map(str::to_string),
+        before: before.map(str::to_string),
+        after: after.map(str::to_string),
+    }
+}
+
+#[test]
+fn edits_tell_values_before_and_after() {
+    let mut edited = base();
+    edited.users[1] = "Robert".to_string();
+    edited.teams_size[0] = 2;
+    edited.preferences[0] = vec![2, 1];
+
+    assert_eq!(
+        edits(&base(), &edited),
+        vec![
+            edit(Field::User, Some("Bob"), None, Some("Bob"), Some("Robert")),
+            edit(Field::Size, None, Some("Red"), Some("1"), Some("2")),
+            edit(Field::Rank, Some("Ann"), Some("Red"), Some("1"), Some("2")),
+            edit(Field::Rank, Some("Ann"), Some("Blue"), Some("2"), Some("1")),
+        ]
+    );
+}
+
+#[test]
+fn private_ranks_are_not_recorded() {
+    let mut before = base();
+    before.settings.private_preferences = true;
+    let mut after = before.clone();
+    after.preferences[1] = vec![1, 2];
+
+    assert_eq!(
+        edits(&before, &after),
+        vec![edit(Field::Preferences, Some("Bob"), None, None, None)]
+    );
+}
+
+#[test]
+fn entries_are_filtered_by_user_and_team() {
+    let mut edited = base();
+    edited.users[1] = "Robert".to_string();
+    edited.preferences[0] = vec![2, 1];
+    let entry = AuditEntry {
+        at: 0,
+        actor: Actor {
+            role: Role::Organiser,
+            user: None,
+        },
+        change: Change::Edited,
+        edits: edits(&base(), &edited),
+    };
+
+    let bob = entry.clone().filtered(Some("Bob"), None).unwrap();
+    assert_eq!(bob.edits.len(), 1);
+    let robert = entry.clone().filtered(Some("Robert"), None).unwrap();
+    assert_eq!(robert.edits, bob.edits);
+    let ann_red = entry.clone().filtered(Some("Ann"), Some("Red")).unwrap();
+    assert_eq!(ann_red.edits, vec![edit(Field::Rank, Some("Ann"), Some("Red"), Some("1"), Some("2"))]);
+    assert!(entry.filtered(Some("Bob"), Some("Red")).is_none());
+}
+
+#[test]
+fn actors_are_the_users_they_claimed() {
+    let identity = Uuid::from_u128(1);
+    let mut session = base();
+    session.members = vec![None, Some(identity)];
+
+    let actor = Actor::new(&session, Role::Participant, Some(identity));
+    assert_eq!(actor.user.as_deref(), Some("Bob"));
+    let entry = AuditEntry {
+        at: 0,
+        actor,
+        change: Change::Submitted { user: Some("Bob".to_string()) },
+        edits: Vec::new(),
+    };
+    assert!(entry.clone().filtered(Some("Bob"), None).is_some());
+    assert!(entry.filtered(Some("Ann"), None).is_none());
+}
@@ -0,0 +1,31 @@
+//! Sessions shared by the tests.
+
+#![allow(dead_code, reason = "each test uses some of the sessions")]
+
+use solver::{Assignment, Session};
+
+/// Users ranking teams of `size` slots each, `preferences[user][team]` being the
+/// rank the user gives the team.
+pub fn session(users: &[&str], teams: &[&str], size: usize, preferences: &[&[usize]]) -> Session {
+    Session {
+        name: "Session".to_string(),
+        users: users.iter().map(|user| user.to_string()).collect(),
+        teams: teams.iter().map(|team| team.to_string()).collect(),
+        teams_size: vec![size; teams.len()],
+        preferences: preferences.iter().map(|prefs| prefs.to_vec()).collect(),
+        ..Session::default()
+    }
+}
+
+/// Two users ranking two teams of one slot, Ann preferring Red and Bob Blue.
+pub fn base() -> Session {
+    session(&["Ann", "Bob"], &["Red", "Blue"], 1, &[&[1, 2], &[2, 1]])
+}
+
+/// The members of each team, and the users left out.
+pub fn assignment(teams: &[&[usize]], unassigned: &[usize]) -> Assignment {
+    Assignment {
+        teams: teams.iter().map(|members| members.to_vec()).collect(),
+        unassigned: unassigned.to_vec(),
+    }
+}
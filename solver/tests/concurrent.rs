@@ -0,0 +1,327 @@
+//! Concurrent edits of a session, merged as operations.
+
+mod common;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use solver::ops::{apply, conflicts, diff};
+use solver::{rebase, Session};
+use uuid::Uuid;
+
+/// Three users ranking three teams, two slots each.
+fn base() -> Session {
+    common::session(
+        &["Ann", "Bob", "Cid"],
+        &["Red", "Blue", "Green"],
+        2,
+        &[&[1, 2, 3], &[3, 1, 2], &[2, 3, 1]],
+    )
+}
+
+/// The session once the edits of both replicas reached the backend, `first`'s
+/// then `second`'s, as `second` sees it after rebasing.
+fn merged(first: &Session, second: &Session) -> Session {
+    let mut stored = base();
+    apply(&mut stored, &diff(&base(), first));
+    apply(&mut stored, &diff(&base(), second));
+    stored
+}
+
+#[test]
+fn ranks_of_different_users_are_both_kept() {
+    let mut ann = base();
+    ann.preferences[0] = vec![3, 2, 1];
+    let mut bob = base();
+    bob.preferences[1] = vec![1, 2, 3];
+
+    for stored in [merged(&ann, &bob), merged(&bob, &ann)] {
+        assert_eq!(stored.preferences, vec![vec![3, 2, 1], vec![1, 2, 3], vec![2, 3, 1]]);
+    }
+}
+
+#[test]
+fn same_rank_goes_to_the_last_edit() {
+    let mut first = base();
+    first.preferences[0][0] = 2;
+    let mut second = base();
+    second.preferences[0][0] = 3;
+
+    assert_eq!(merged(&first, &second).preferences[0][0], 3);
+    assert_eq!(merged(&second, &first).preferences[0][0], 2);
+}
+
+#[test]
+fn rank_follows_a_renamed_user() {
+    let mut renaming = base();
+    renaming.users[1] = "Robert".to_string();
+    let mut ranking = base();
+    ranking.preferences[1] = vec![1, 2, 3];
+
+    for stored in [merged(&renaming, &ranking), merged(&ranking, &renaming)] {
+        assert_eq!(stored.users[1], "Robert");
+        assert_eq!(stored.preferences[1], vec![1, 2, 3]);
+    }
+}
+
+#[test]
+fn rank_follows_a_user_moved_by_a_removal() {
+    let mut removing = base();
+    removing.remove_user(0);
+    let mut ranking = base();
+    ranking.preferences[2] = vec![1, 2, 3];
+
+    for stored in [merged(&removing, &ranking), merged(&ranking, &removing)] {
+        assert_eq!(stored.users, vec!["Bob", "Cid"]);
+        assert_eq!(stored.preferences, vec![vec![3, 1, 2], vec![1, 2, 3]]);
+    }
+}
+
+#[test]
+fn edits_of_a_removed_user_are_dropped() {
+    let mut removing = base();
+    removing.remove_user(1);
+    let mut ranking = base();
+    ranking.preferences[1] = vec![1, 2, 3];
+    ranking.users[1] = "Robert".to_string();
+
+    let mut stored = base();
+    apply(&mut stored, &diff(&base(), &removing));
+    let applied = apply(&mut stored, &diff(&base(), &ranking));
+    assert_eq!(applied, 0);
+    assert_eq!(stored.users, vec!["Ann", "Cid"]);
+    assert_eq!(stored.preferences, vec![vec![1, 2, 3], vec![2, 3, 1]]);
+}
+
+#[test]
+fn users_added_on_both_sides_are_all_kept() {
+    let mut first = base();
+    first.add_user("Dee".to_string());
+    first.preferences[3] = vec![1, 0, 0];
+    let mut second = base();
+    second.add_user("Eve".to_string());
+    second.preferences[3] = vec![0, 1, 0];
+
+    let stored = merged(&first, &second);
+    assert_eq!(stored.users, vec!["Ann", "Bob", "Cid", "Dee", "Eve"]);
+    assert_eq!(stored.preferences[3], vec![1, 0, 0]);
+    assert_eq!(stored.preferences[4], vec![0, 1, 0]);
+}
+
+#[test]
+fn team_added_while_ranking_extends_every_row() {
+    let mut adding = base();
+    adding.add_team("Gold".to_string(), 1);
+    let mut ranking = base();
+    ranking.preferences[0] = vec![2, 1, 3];
+
+    for stored in [merged(&adding, &ranking), merged(&ranking, &adding)] {
+        assert_eq!(stored.teams, vec!["Red", "Blue", "Green", "Gold"]);
+        assert_eq!(stored.teams_size, vec![2, 2, 2, 1]);
+        assert_eq!(stored.preferences[0], vec![2, 1, 3, 0]);
+        assert_eq!(stored.preferences[1], vec![3, 1, 2, 0]);
+    }
+}
+
+#[test]
+fn removing_a_team_drops_its_ranks_only() {
+    let mut removing = base();
+    removing.remove_team(0);
+    let mut ranking = base();
+    ranking.preferences[1] = vec![2, 3, 1];
+
+    for stored in [merged(&removing, &ranking), merged(&ranking, &removing)] {
+        assert_eq!(stored.teams, vec!["Blue", "Green"]);
+        assert_eq!(stored.preferences[1], vec![3, 1]);
+    }
+}
+
+#[test]
+fn constraints_are_dropped_when_users_changed_meanwhile() {
+    let mut pinning = base();
+    pinning.constraints.set_pin(2, Some(1));
+    let mut adding = base();
+    adding.add_user("Dee".to_string());
+
+    // Applied after the user was added, the pins would not match the users anymore
+    let stored = merged(&adding, &pinning);
+    assert_eq!(stored.constraints.pin(2), None);
+    // Applied before, the pin is kept, the new user having none
+    let stored = merged(&pinning, &adding);
+    assert_eq!(stored.constraints.pin(2), Some(1));
+    assert_eq!(stored.constraints.pin(3), None);
+}
+
+#[test]
+fn claims_of_different_users_are_both_kept() {
+    let (ann, bob) = (Uuid::from_u128(1), Uuid::from_u128(2));
+    let mut first = base();
+    first.members = vec![Some(ann)];
+    let mut second = base();
+    second.members = vec![None, Some(bob)];
+
+    for stored in [merged(&first, &second), merged(&second, &first)] {
+        assert_eq!(stored.member_of(ann), Some(0));
+        assert_eq!(stored.member_of(bob), Some(1));
+    }
+}
+
+#[test]
+fn rebase_keeps_pending_edits_on_top_of_the_stored_session() {
+    let mut sent = base();
+    sent.preferences[0] = vec![3, 2, 1];
+    // Edited again while the first edit was on its way
+    let mut ours = sent.clone();
+    ours.teams_size[1] = 3;
+    // The backend got another edit before ours
+    let mut theirs = base();
+    theirs.users[2] = "Cyd".to_string();
+    apply(&mut theirs, &diff(&base(), &sent));
+
+    let shown = rebase(&sent, &ours, theirs);
+    assert_eq!(shown.users[2], "Cyd");
+    assert_eq!(shown.preferences[0], vec![3, 2, 1]);
+    assert_eq!(shown.teams_size, vec![2, 3, 2]);
+}
+
+#[test]
+fn conflicts_are_edits_of_the_same_values() {
+    let mut ours = base();
+    ours.preferences[0][0] = 2;
+    ours.preferences[1][1] = 3;
+    ours.teams_size[2] = 1;
+    let mut theirs = base();
+    theirs.preferences[0][0] = 3;
+    theirs.preferences[2][0] = 3;
+    theirs.remove_team(2);
+    let (ours, theirs) = (diff(&base(), &ours), diff(&base(), &theirs));
+
+    let clashes = conflicts(&ours, &theirs);
+    assert_eq!(clashes.len(), 2, "{clashes:?}");
+    // Ann's rank for Red, and the size of the team removed on the other side
+    assert!(clashes.iter().any(|(our, their)| our == &ours[1] && their == &theirs[1]));
+    assert!(clashes.iter().any(|(our, their)| our == &ours[0] && their == &theirs[0]));
+}
+
+#[test]
+fn same_edits_do_not_conflict() {
+    let mut edited = base();
+    edited.preferences[0][0] = 2;
+    edited.users[1] = "Robert".to_string();
+    let edits = diff(&base(), &edited);
+    assert!(conflicts(&edits, &edits).is_empty());
+}
+
+/// An edit made from the page of a session.
+#[derive(Clone, Debug)]
+enum Edit {
+    AddUser,
+    AddTeam,
+    RemoveUser(usize),
+    RemoveTeam(usize),
+    RenameUser(usize, String),
+    RenameTeam(usize, String),
+    Size(usize, usize),
+    Rank(usize, usize, usize),
+    Pin(usize, Option<usize>),
+    Claim(usize),
+}
+
+fn edit() -> impl Strategy<Value = Edit> {
+    let name = prop_oneof!["[A-D]", Just("User 1".to_string())];
+    prop_oneof![
+        Just(Edit::AddUser),
+        Just(Edit::AddTeam),
+        (0..6usize).prop_map(Edit::RemoveUser),
+        (0..6usize).prop_map(Edit::RemoveTeam),
+        (0..6usize, name.clone()).prop_map(|(user, name)| Edit::RenameUser(user, name)),
+        (0..6usize, name).prop_map(|(team, name)| Edit::RenameTeam(team, name)),
+        (0..6usize, 0..4usize).prop_map(|(team, size)| Edit::Size(team, size)),
+        (0..6usize, 0..6usize, 0..6usize).prop_map(|(user, team, rank)| Edit::Rank(user, team, rank)),
+        (0..6usize, proptest::option::of(0..6usize)).prop_map(|(user, team)| Edit::Pin(user, team)),
+        (0..6usize).prop_map(Edit::Claim),
+    ]
+}
+
+/// Make an edit as the page would, if it applies to the session.
+fn make(session: &mut Session, edit: &Edit) {
+    let (n_users, n_teams) = (session.users.len(), session.teams.len());
+    match edit.clone() {
+        Edit::AddUser => session.add_user(format!("User {}", n_users + 1)),
+        Edit::AddTeam => session.add_team(format!("Team {}", n_teams + 1), 1),
+        Edit::RemoveUser(user) if user < n_users => session.remove_user(user),
+        Edit::RemoveTeam(team) if team < n_teams => session.remove_team(team),
+        Edit::RenameUser(user, name) if user < n_users => session.users[user] = name,
+        Edit::RenameTeam(team, name) if team < n_teams => session.teams[team] = name,
+        Edit::Size(team, size) if team < n_teams => session.teams_size[team] = size,
+        Edit::Rank(user, team, rank) if user < n_users && team < n_teams => session.preferences[user][team] = rank,
+        Edit::Pin(user, team) if user < n_users && team.is_none_or(|team| team < n_teams) => {
+            session.constraints.set_pin(user, team)
+        }
+        Edit::Claim(user) if user < n_users => {
+            if session.members.len() <= user {
+                session.members.resize(user + 1, None);
+            }
+            session.members[user] = Some(Uuid::from_u128(user as u128 + 1));
+        }
+        _ => {}
+    }
+}
+
+/// The session with no unclaimed user at the end of the members, which is the same.
+fn normalized(mut session: Session) -> Session {
+    while session.members.last() == Some(&None) {
+        session.members.pop();
+    }
+    session
+}
+
+fn edited(session: &Session, edits: &[Edit]) -> Session {
+    let mut session = session.clone();
+    for edit in edits {
+        make(&mut session, edit);
+    }
+    session
+}
+
+proptest! {
+    #[test]
+    fn diff_applied_to_its_base_gives_the_edited_session(edits in vec(edit(), 0..12)) {
+        let edited = edited(&base(), &edits);
+        let mut replayed = base();
+        apply(&mut replayed, &diff(&base(), &edited));
+        prop_assert_eq!(normalized(replayed), normalized(edited));
+    }
+
+    #[test]
+    fn concurrent_edits_merge_without_panicking(
+        first in vec(edit(), 0..8),
+        second in vec(edit(), 0..8),
+    ) {
+        let (first, second) = (edited(&base(), &first), edited(&base(), &second));
+        let stored = merged(&first, &second);
+        // The replica which sent last ends up with the stored session
+        prop_assert_eq!(rebase(&base(), &second, merged(&first, &base())), stored.clone());
+        prop_assert_eq!(stored.preferences.len(), stored.users.len());
+        for prefs in &stored.preferences {
+            prop_assert_eq!(prefs.len(), stored.teams.len());
+        }
+        prop_assert_eq!(stored.teams_size.len(), stored.teams.len());
+    }
+
+    #[test]
+    fn edits_which_do_not_touch_the_same_users_commute(
+        first in vec((0..3usize, 0..3usize), 1..6),
+        second in vec((0..3usize, 0..3usize), 1..6),
+    ) {
+        // The first replica ranks for Ann, the second for Bob and Cid
+        let mut a = base();
+        for (team, rank) in first {
+            a.preferences[0][team] = rank;
+        }
+        let mut b = base();
+        for (idx, (team, rank)) in second.into_iter().enumerate() {
+            b.preferences[1 + idx % 2][team] = rank;
+        }
+        prop_assert_eq!(merged(&a, &b), merged(&b, &a));
+    }
+}
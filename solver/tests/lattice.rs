@@ -0,0 +1,53 @@
+//! Stable matchings of a session, from the best for users to the best for teams.
+
+mod common;
+
+use solver::{median_stable_matching, stable_matchings, Session};
+
+use common::assignment;
+
+/// Ann and Bob each rank first the team which ranks them last.
+fn crossed() -> Session {
+    let mut session = common::base();
+    session.constraints.priorities = vec![vec![2, 1], vec![1, 2]];
+    session
+}
+
+#[test]
+fn lattice_goes_from_users_to_teams() {
+    let stable = stable_matchings(&crossed(), 0, 10);
+    assert!(stable.complete);
+    assert_eq!(stable.matchings, vec![assignment(&[&[0], &[1]], &[]), assignment(&[&[1], &[0]], &[])]);
+}
+
+#[test]
+fn sessions_tying_users_together_are_not_enumerated() {
+    let mut apart = crossed();
+    apart.constraints.apart = vec![(0, 1)];
+    assert!(stable_matchings(&apart, 0, 10).matchings.is_empty());
+}
+
+/// Each user ranks first the team which ranks them last, and second the team
+/// which ranks them second, so that there are three stable matchings.
+fn latin_square() -> Session {
+    let mut session = common::session(
+        &["Ann", "Bob", "Cid"],
+        &["Red", "Blue", "Green"],
+        1,
+        &[&[1, 2, 3], &[3, 1, 2], &[2, 3, 1]],
+    );
+    session.constraints.priorities = vec![vec![3, 1, 2], vec![2, 3, 1], vec![1, 2, 3]];
+    session
+}
+
+#[test]
+fn median_gives_everyone_their_second_choice() {
+    let session = latin_square();
+    let stable = stable_matchings(&session, 0, 10);
+    let first = assignment(&[&[0], &[1], &[2]], &[]);
+    let second = assignment(&[&[2], &[0], &[1]], &[]);
+    let third = assignment(&[&[1], &[2], &[0]], &[]);
+    assert!(stable.complete);
+    assert_eq!(stable.matchings, vec![first, second.clone(), third]);
+    assert_eq!(median_stable_matching(&session, 0, &stable), second);
+}
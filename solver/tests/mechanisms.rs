@@ -0,0 +1,114 @@
+//! Each mechanism on a session small enough to sort by hand.
+
+mod common;
+
+use solver::{probabilistic_serial, solve, Attributes, Lottery, Mechanism, Session, TieBreaking};
+
+use common::assignment;
+
+/// Three users ranking three teams of one slot, every user in the same order.
+fn same_order() -> Session {
+    common::session(
+        &["Ann", "Bob", "Cid"],
+        &["Red", "Blue", "Green"],
+        1,
+        &[&[1, 2, 3], &[1, 2, 3], &[1, 2, 3]],
+    )
+}
+
+#[test]
+fn lotteries_only_depend_on_their_seed() {
+    // Seed 0 draws the order Bob, Ann, Cid first, then Cid, Bob, Ann
+    let single = Lottery::new(TieBreaking::Single, 3, 2, 0);
+    assert_eq!(single, Lottery::new(TieBreaking::Single, 3, 2, 0));
+    assert_eq!((0..3).map(|user| single.position(0, user)).collect::<Vec<_>>(), vec![1, 0, 2]);
+    assert_eq!((0..3).map(|user| single.position(1, user)).collect::<Vec<_>>(), vec![1, 0, 2]);
+    let multiple = Lottery::new(TieBreaking::Multiple, 3, 2, 0);
+    assert_eq!((0..3).map(|user| multiple.position(1, user)).collect::<Vec<_>>(), vec![2, 1, 0]);
+
+    // Everyone ranks Red then Blue, which break the ties with their lottery
+    let mut session = same_order();
+    session.teams.pop();
+    session.teams_size.pop();
+    session.settings.tie_breaking = TieBreaking::Single;
+    assert_eq!(solve(&session, 0), assignment(&[&[1], &[0]], &[2]));
+    session.settings.tie_breaking = TieBreaking::Multiple;
+    assert_eq!(solve(&session, 0), assignment(&[&[1], &[2]], &[0]));
+}
+
+#[test]
+fn random_serial_dictatorship_follows_the_lottery() {
+    // Seed 0 draws the order Bob, Ann, Cid
+    let mut session = same_order();
+    session.settings.mechanism = Mechanism::RandomSerialDictatorship;
+    assert_eq!(solve(&session, 0), assignment(&[&[1], &[0], &[2]], &[]));
+}
+
+#[test]
+fn priority_serial_dictatorship_follows_the_attribute() {
+    let mut session = same_order();
+    session.settings.mechanism = Mechanism::PrioritySerialDictatorship;
+    session.settings.order_by = Some("Signed up".to_string());
+    session.attributes = Attributes {
+        names: vec!["Signed up".to_string()],
+        values: vec![vec!["10".to_string()], vec!["2".to_string()], vec!["9".to_string()]],
+    };
+    // Numbers are compared as such: Bob, Cid, then Ann
+    assert_eq!(solve(&session, 0), assignment(&[&[1], &[2], &[0]], &[]));
+}
+
+#[test]
+fn boston_never_gives_a_seat_back() {
+    let mut session = common::session(
+        &["Ann", "Bob", "Cid"],
+        &["Red", "Blue", "Green"],
+        1,
+        &[&[2, 1, 3], &[1, 2, 3], &[2, 1, 3]],
+    );
+    session.constraints.priorities = vec![vec![1, 2, 3], vec![3, 1, 2], vec![1, 2, 3]];
+    session.settings.mechanism = Mechanism::Boston;
+    // Bob and Cid get their first choice in the first round; Red, which Ann ranked
+    // second and which prefers her, is full by then
+    assert_eq!(solve(&session, 0), assignment(&[&[1], &[2], &[0]], &[]));
+
+    session.settings.mechanism = Mechanism::GaleShapley;
+    assert_eq!(solve(&session, 0), assignment(&[&[0], &[1], &[2]], &[]));
+}
+
+#[test]
+fn top_trading_cycles_trades_priorities() {
+    let mut session = common::session(
+        &["Ann", "Bob", "Cid"],
+        &["Red", "Blue", "Green"],
+        1,
+        &[&[1, 2, 3], &[1, 3, 2], &[2, 1, 3]],
+    );
+    session.constraints.priorities = vec![vec![2, 3, 1], vec![1, 2, 3], vec![2, 1, 3]];
+    session.settings.mechanism = Mechanism::TopTradingCycles;
+    // Ann points to Red, which points to Cid, who points to Blue, which points to
+    // Ann: they trade. Bob then gets Green, the only team left
+    assert_eq!(solve(&session, 0), assignment(&[&[0], &[2], &[1]], &[]));
+}
+
+#[test]
+fn probabilistic_serial_shares_the_teams_eaten_together() {
+    let session = common::session(
+        &["Ann", "Bob", "Cid"],
+        &["Red", "Blue", "Green"],
+        1,
+        &[&[1, 2, 3], &[1, 2, 3], &[2, 1, 3]],
+    );
+    // Ann and Bob eat Red until 1/2 while Cid eats half of Blue, then everyone eats
+    // the rest of Blue until 2/3, and Green until 1
+    let expected = [
+        [1.0 / 2.0, 1.0 / 6.0, 1.0 / 3.0],
+        [1.0 / 2.0, 1.0 / 6.0, 1.0 / 3.0],
+        [0.0, 2.0 / 3.0, 1.0 / 3.0],
+    ];
+    let fractional = probabilistic_serial(&session);
+    for (row, expected) in fractional.probabilities.iter().zip(expected) {
+        for (probability, expected) in row.iter().zip(expected) {
+            assert!((probability - expected).abs() < 1e-9, "{row:?}");
+        }
+    }
+}
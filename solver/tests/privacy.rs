@@ -0,0 +1,88 @@
+//! What callers may change of a session they saw redacted.
+
+mod common;
+
+use solver::{gale_shapley, merge, redact, redact_run, BlockingPair, Role, Run, Session, GALE_SHAPLEY};
+use uuid::Uuid;
+
+/// Two users ranking two teams, the second user claimed.
+fn claimed(private: bool) -> Session {
+    let mut session = common::base();
+    session.members = vec![None, Some(Uuid::from_u128(2))];
+    session.settings.private_preferences = private;
+    session
+}
+
+#[test]
+fn organisers_never_set_private_preferences() {
+    let stored = claimed(true);
+    let mut incoming = redact(&stored, Role::Organiser, None);
+    incoming.preferences = vec![vec![2, 1], vec![1, 2]];
+
+    assert_eq!(merge(&stored, incoming, Role::Organiser, None).preferences, stored.preferences);
+}
+
+#[test]
+fn organisers_cannot_claim_users_of_a_private_session() {
+    let stored = claimed(true);
+    let identity = Uuid::from_u128(1);
+    let mut incoming = redact(&stored, Role::Organiser, Some(identity));
+    incoming.members = vec![Some(identity), Some(identity)];
+
+    let merged = merge(&stored, incoming, Role::Organiser, Some(identity));
+    assert_eq!(merged.members, stored.members);
+    let shown = redact(&merged, Role::Organiser, Some(identity));
+    assert_eq!(shown.preferences, vec![Vec::<usize>::new(), Vec::new()]);
+}
+
+#[test]
+fn organisers_release_users_of_a_private_session() {
+    let stored = claimed(true);
+    let mut incoming = redact(&stored, Role::Organiser, None);
+    incoming.members = vec![None, None];
+
+    assert_eq!(merge(&stored, incoming, Role::Organiser, None).members, vec![None, None]);
+}
+
+#[test]
+fn private_preferences_are_cleared_when_made_public() {
+    let stored = claimed(true);
+    let mut incoming = redact(&stored, Role::Organiser, None);
+    incoming.settings.private_preferences = false;
+
+    let merged = merge(&stored, incoming, Role::Organiser, None);
+    assert_eq!(merged.preferences, vec![vec![0, 0], vec![0, 0]]);
+}
+
+#[test]
+fn participants_only_edit_their_own_user() {
+    let stored = claimed(false);
+    let identity = Uuid::from_u128(2);
+    let mut incoming = redact(&stored, Role::Participant, Some(identity));
+    incoming.preferences = vec![vec![2, 1], vec![1, 2]];
+    incoming.teams_size = vec![2, 2];
+    incoming.settings.private_preferences = true;
+    incoming.users.push("Eve".to_string());
+
+    let merged = merge(&stored, incoming, Role::Participant, Some(identity));
+    assert_eq!(merged.preferences, vec![vec![1, 2], vec![1, 2]]);
+    assert_eq!(merged.teams_size, stored.teams_size);
+    assert_eq!(merged.settings, stored.settings);
+    assert_eq!(merged.users, stored.users);
+}
+
+#[test]
+fn runs_hide_what_tells_the_preferences_apart() {
+    let stored = claimed(false);
+    let mut run = Run::new(Uuid::nil(), 0, GALE_SHAPLEY, None, &stored, gale_shapley(&stored, 0));
+    run.metrics.blocking_pairs.push(BlockingPair { user: 0, team: 1 });
+
+    assert_eq!(redact_run(&run, &stored, Role::Organiser), run);
+    let shown = redact_run(&run, &stored, Role::Participant);
+    assert!(shown.metrics.blocking_pairs.is_empty());
+    assert!(shown.metrics.rank_counts.is_empty());
+    assert_eq!(shown.assignment, run.assignment);
+
+    let private = claimed(true);
+    assert!(redact_run(&run, &private, Role::Organiser).metrics.rank_counts.is_empty());
+}
@@ -0,0 +1,193 @@
+//! Invariants of the matchers on random sessions.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::select;
+use solver::{evaluate, solve, validate, Assignment, Constraints, Mechanism, Proposing, Session, Settings, TieBreaking};
+
+/// A session where every user ranked every team, the teams may rank users, and
+/// the teams may have fewer slots than there are users.
+fn session() -> impl Strategy<Value = Session> {
+    (1..=5usize, 1..=12usize)
+        .prop_flat_map(|(n_teams, n_users)| {
+            (
+                vec(1..=4usize.min(n_users), n_teams),
+                vec(Just((1..=n_teams).collect::<Vec<_>>()).prop_shuffle(), n_users),
+                vec(vec(0..=n_users, n_users), n_teams),
+                Just(n_users),
+            )
+        })
+        .prop_map(|(teams_size, preferences, priorities, n_users)| Session {
+            name: "Session".to_string(),
+            users: (1..=n_users).map(|user| format!("User {user}")).collect(),
+            teams: (1..=teams_size.len()).map(|team| format!("Team {team}")).collect(),
+            teams_size,
+            preferences,
+            constraints: Constraints {
+                priorities,
+                ..Constraints::default()
+            },
+            ..Session::default()
+        })
+}
+
+/// A [`session`] with at least as many slots as users.
+fn roomy_session() -> impl Strategy<Value = Session> {
+    session().prop_map(|mut session| {
+        let n_teams = session.teams.len();
+        let mut team = 0;
+        while session.capacity() < session.users.len() {
+            session.teams_size[team % n_teams] += 1;
+            team += 1;
+        }
+        session
+    })
+}
+
+/// A [`roomy_session`] with some users pinned, forbidden from teams, in groups
+/// or apart from others, and likely invalid.
+fn constrained_session() -> impl Strategy<Value = Session> {
+    roomy_session()
+        .prop_flat_map(|session| {
+            let (n_users, n_teams) = (session.users.len(), session.teams.len());
+            (
+                Just(session),
+                vec(proptest::option::weighted(0.15, 0..n_teams), n_users),
+                vec(vec(proptest::bool::weighted(0.1), n_teams), n_users),
+                vec((0..n_users, 0..n_users), 0..3),
+                vec((0..n_users, 0..n_users), 0..3),
+            )
+        })
+        .prop_map(|(mut session, pins, forbidden, together, apart)| {
+            let constraints = &mut session.constraints;
+            for (user, pin) in pins.into_iter().enumerate() {
+                constraints.set_pin(user, pin);
+            }
+            constraints.forbidden = forbidden;
+            for (a, b) in together {
+                constraints.join(a, b);
+            }
+            for (a, b) in apart {
+                constraints.set_apart(a, b, true);
+            }
+            session
+        })
+}
+
+/// Anything the solver could be given, however malformed: ragged preferences,
+/// ranks and constraints out of range, missing sizes.
+fn malformed_session() -> impl Strategy<Value = Session> {
+    (
+        0..8usize,
+        0..6usize,
+        vec(0..6usize, 0..8),
+        vec(vec(0..9usize, 0..8), 0..10),
+        vec(proptest::option::of(0..8usize), 0..10),
+        vec(vec(any::<bool>(), 0..8), 0..10),
+        vec(vec(0..10usize, 0..4), 0..3),
+        vec((0..10usize, 0..10usize), 0..4),
+    )
+        .prop_map(|(n_users, n_teams, teams_size, preferences, pins, forbidden, groups, apart)| Session {
+            name: String::new(),
+            users: (0..n_users).map(|user| user.to_string()).collect(),
+            teams: (0..n_teams).map(|team| team.to_string()).collect(),
+            teams_size,
+            preferences,
+            constraints: Constraints {
+                pins,
+                forbidden,
+                groups,
+                apart,
+                ..Constraints::default()
+            },
+            ..Session::default()
+        })
+}
+
+fn settings() -> impl Strategy<Value = Settings> {
+    (select(Mechanism::ALL.to_vec()), select(TieBreaking::ALL.to_vec()), select(Proposing::ALL.to_vec()))
+        .prop_map(|(mechanism, tie_breaking, proposing)| Settings {
+            mechanism,
+            tie_breaking,
+            proposing,
+            ..Settings::default()
+        })
+}
+
+/// Every user of the session is in exactly one team or unassigned, and no team
+/// is over its size.
+fn check_partition(session: &Session, assignment: &Assignment) -> Result<(), TestCaseError> {
+    prop_assert_eq!(assignment.teams.len(), session.teams.len());
+    for (team, members) in assignment.teams.iter().enumerate() {
+        prop_assert!(
+            members.len() <= session.teams_size[team],
+            "team {} has {} members for {} slots",
+            team,
+            members.len(),
+            session.teams_size[team]
+        );
+    }
+    let mut users: Vec<usize> = assignment.teams.iter().flatten().chain(&assignment.unassigned).copied().collect();
+    users.sort_unstable();
+    prop_assert_eq!(users, (0..session.users.len()).collect::<Vec<_>>());
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn capacity_is_respected(mut session in session(), settings in settings(), seed in any::<u64>()) {
+        session.settings = settings;
+        let assignment = solve(&session, seed);
+        check_partition(&session, &assignment)?;
+    }
+
+    #[test]
+    fn everyone_is_assigned_when_slots_suffice(mut session in roomy_session(), settings in settings(), seed in any::<u64>()) {
+        session.settings = settings;
+        let assignment = solve(&session, seed);
+        check_partition(&session, &assignment)?;
+        prop_assert!(assignment.unassigned.is_empty(), "unassigned: {:?}", assignment.unassigned);
+    }
+
+    #[test]
+    fn gale_shapley_is_stable(mut session in session(), tie_breaking in select(TieBreaking::ALL.to_vec()), proposing in select(Proposing::ALL.to_vec()), seed in any::<u64>()) {
+        session.settings.tie_breaking = tie_breaking;
+        session.settings.proposing = proposing;
+        let metrics = evaluate(&session, &solve(&session, seed));
+        prop_assert!(metrics.blocking_pairs.is_empty(), "blocking pairs: {:?}", metrics.blocking_pairs);
+        prop_assert!(metrics.is_stable());
+    }
+
+    #[test]
+    fn same_seed_same_assignment(mut session in session(), settings in settings(), seed in any::<u64>()) {
+        session.settings = settings;
+        prop_assert_eq!(solve(&session, seed), solve(&session, seed));
+    }
+
+    #[test]
+    fn valid_sessions_are_sorted_within_their_constraints(mut session in constrained_session(), settings in settings(), seed in any::<u64>()) {
+        session.settings = settings;
+        prop_assume!(validate(&session).is_valid());
+        let assignment = solve(&session, seed);
+        check_partition(&session, &assignment)?;
+        let metrics = evaluate(&session, &assignment);
+        prop_assert!(metrics.constraint_violations.is_empty(), "violations: {:?}", metrics.constraint_violations);
+        // Probabilistic serial leaves groups and apart pairs out
+        if session.settings.mechanism != Mechanism::ProbabilisticSerial {
+            prop_assert!(metrics.apart_together.is_empty(), "apart together: {:?}", metrics.apart_together);
+            prop_assert!(metrics.split_groups.is_empty(), "split groups: {:?}", metrics.split_groups);
+        }
+    }
+
+    #[test]
+    fn malformed_sessions_do_not_panic(mut session in malformed_session(), settings in settings(), seed in any::<u64>()) {
+        session.settings = settings;
+        let validation = validate(&session);
+        for issue in &validation.issues {
+            issue.message(&session);
+        }
+        session.input_hash();
+        let assignment = solve(&session, seed);
+        evaluate(&session, &assignment);
+    }
+}
@@ -0,0 +1,40 @@
+//! Re-matching from a previous run, moving as few users as possible.
+
+mod common;
+
+use solver::{rematch, Run, Session, MANUAL};
+use uuid::Uuid;
+
+use common::assignment;
+
+/// Ann in Red and Bob in Blue, the three teams having one slot.
+fn previous() -> (Session, Run) {
+    let session = common::session(&["Ann", "Bob"], &["Red", "Blue", "Green"], 1, &[&[1, 2, 3], &[2, 1, 3]]);
+    let run = Run::new(Uuid::nil(), 0, MANUAL, None, &session, assignment(&[&[0], &[1], &[]], &[]));
+    (session, run)
+}
+
+#[test]
+fn late_users_move_the_fewest_users() {
+    let (mut session, run) = previous();
+    session.users.push("Cid".to_string());
+    session.preferences.push(vec![1, 2, 3]);
+    // Cid may only join Red: Ann leaves it for Green, which is free, rather than
+    // for Blue, which she prefers but which would move Bob too
+    session.constraints.forbidden = vec![vec![false; 3], vec![false; 3], vec![false, true, true]];
+
+    assert_eq!(rematch(&session, 0, &run), assignment(&[&[2], &[1], &[0]], &[]));
+}
+
+#[test]
+fn late_users_never_move_users_next_to_those_they_asked_to_be_apart_from() {
+    let (mut session, run) = previous();
+    session.users.push("Cid".to_string());
+    session.preferences.push(vec![1, 2, 3]);
+    session.constraints.forbidden = vec![vec![false; 3], vec![false; 3], vec![false, true, true]];
+    // Ann could only make room by joining Bob, whom she asked to be apart from
+    session.teams_size = vec![1, 2, 0];
+    session.constraints.apart = vec![(0, 1)];
+
+    assert_eq!(rematch(&session, 0, &run), assignment(&[&[0], &[1], &[]], &[2]));
+}
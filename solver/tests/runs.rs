@@ -0,0 +1,112 @@
+//! Runs of a session: their fingerprint, and what follows them.
+
+mod common;
+
+use solver::{solve, Assignment, Mechanism, PublishedRun, Run, TieBreaking};
+use uuid::Uuid;
+
+use common::base;
+
+#[test]
+fn input_hash_tells_lists_apart() {
+    let mut moved = base();
+    moved.users.push(moved.teams.remove(0));
+    assert_ne!(base().input_hash(), moved.input_hash());
+
+    let mut preferences = base();
+    preferences.preferences = vec![vec![1, 2], vec![]];
+    let mut pins = preferences.clone();
+    pins.preferences = vec![vec![1, 2]];
+    pins.constraints.pins = vec![None];
+    assert_ne!(preferences.input_hash(), pins.input_hash());
+}
+
+#[test]
+fn input_hash_tells_settings_apart() {
+    let mut single = base();
+    single.settings.tie_breaking = TieBreaking::Single;
+    assert_ne!(base().input_hash(), single.input_hash());
+    single.settings.tie_breaking = TieBreaking::Order;
+    assert_eq!(base().input_hash(), single.input_hash());
+}
+
+#[test]
+fn assignments_fit_their_session() {
+    let assignment = Assignment {
+        teams: vec![vec![0], vec![]],
+        unassigned: vec![1],
+    };
+    assert!(assignment.fits(2, 2));
+    assert!(!assignment.fits(1, 2));
+    assert!(!assignment.fits(2, 1));
+}
+
+#[test]
+fn published_runs_keep_the_names_of_their_time() {
+    let assignment = Assignment {
+        teams: vec![vec![0], vec![1]],
+        unassigned: Vec::new(),
+    };
+    let run = Run::new(Uuid::from_u128(1), 0, "Test", None, &base(), assignment);
+    let mut session = base();
+    session.users[1] = "Robert".to_string();
+    session.teams.remove(0);
+    session.teams_size.remove(0);
+
+    let published = PublishedRun::new(&session, &run, &[]);
+    let teams: Vec<(&str, &[String])> = published
+        .teams
+        .iter()
+        .map(|team| (team.name.as_str(), team.members.as_slice()))
+        .collect();
+    assert_eq!(
+        teams,
+        vec![("Red", &["Ann".to_string()][..]), ("Blue", &["Bob".to_string()][..])]
+    );
+}
+
+#[test]
+fn runs_are_sorted_again_with_their_settings() {
+    let mut session = base();
+    session.preferences = vec![vec![1, 2], vec![1, 2]];
+    session.settings.tie_breaking = TieBreaking::Single;
+    let seed = 7;
+    let run = Run::new(Uuid::from_u128(1), 0, "Test", Some(seed), &session, solve(&session, seed));
+
+    session.settings.tie_breaking = TieBreaking::Order;
+    session.settings.mechanism = Mechanism::Boston;
+    let settings = run.settings(&session.settings);
+    assert_eq!(settings.tie_breaking, TieBreaking::Single);
+    assert_eq!(settings.mechanism, Mechanism::GaleShapley);
+    session.settings = settings;
+    assert_eq!(solve(&session, seed), run.assignment);
+}
+
+#[test]
+fn withdrawals_follow_the_session_edited_since() {
+    let assignment = Assignment {
+        teams: vec![vec![0], vec![1]],
+        unassigned: Vec::new(),
+    };
+    let run = Run::new(Uuid::from_u128(1), 0, "Test", None, &base(), assignment);
+    let mut session = base();
+    session.settings.waitlists = true;
+    session.teams.remove(0);
+    session.teams_size.remove(0);
+    for prefs in &mut session.preferences {
+        prefs.remove(0);
+    }
+    session.users.insert(0, "Cid".to_string());
+    session.preferences.insert(0, vec![1]);
+
+    let published = PublishedRun::new(&session, &run, &[]);
+    // Ann, whose team was removed, waits for Blue
+    let waitlists: Vec<&[String]> = published.teams.iter().map(|team| team.waitlist.as_slice()).collect();
+    assert_eq!(waitlists, vec![&[][..], &["Ann".to_string()][..]]);
+    // Bob left Blue, now the only team, which Ann from the team removed may take
+    let next = run.withdraw(Uuid::from_u128(2), 1, &session, 2);
+    assert_eq!(next.teams, vec!["Blue".to_string()]);
+    assert_eq!(next.withdrawal.as_ref().map(|withdrawal| withdrawal.user), Some(2));
+    assert!(next.assignment.team_of(2).is_none() && !next.assignment.unassigned.contains(&2));
+    assert!(next.assignment.team_of(1).is_some() || next.assignment.unassigned.contains(&1));
+}
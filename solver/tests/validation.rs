@@ -0,0 +1,34 @@
+//! Issues found in sessions before sorting them.
+
+mod common;
+
+use solver::{validate, IssueKind, Mechanism, Severity};
+
+use common::base;
+
+#[test]
+fn probabilistic_serial_warns_that_it_ignores_groups() {
+    let mut session = base();
+    session.settings.mechanism = Mechanism::ProbabilisticSerial;
+    session.constraints.apart = vec![(0, 1)];
+
+    let validation = validate(&session);
+    let issue = validation.issues.iter().find(|issue| issue.kind == IssueKind::GroupsIgnored);
+    assert_eq!(issue.map(|issue| issue.severity()), Some(Severity::Warning));
+    assert!(validation.is_valid());
+
+    session.settings.mechanism = Mechanism::Boston;
+    assert!(!validate(&session).issues.iter().any(|issue| issue.kind == IssueKind::GroupsIgnored));
+}
+
+#[test]
+fn teams_pinned_beyond_their_size_through_a_group_are_reported() {
+    let mut session = base();
+    session.constraints.pins = vec![Some(0), None];
+    session.constraints.groups = vec![vec![0, 1]];
+
+    let validation = validate(&session);
+    let issue = validation.issues.iter().find(|issue| issue.kind == IssueKind::TooManyPinned { pinned: 2 });
+    assert_eq!(issue.map(|issue| issue.team), Some(Some(0)));
+    assert!(!validation.is_valid());
+}
@@ -0,0 +1,50 @@
+//! Promotions from the waitlists when users withdraw.
+
+mod common;
+
+use solver::{waitlists, withdraw, Promotion, Session, Withdrawal};
+
+use common::assignment;
+
+/// Ann in Red, Bob in Blue waiting for Red, and Cid in Green waiting for Blue.
+fn chain() -> Session {
+    let mut session = common::session(
+        &["Ann", "Bob", "Cid"],
+        &["Red", "Blue", "Green"],
+        1,
+        &[&[1, 2, 3], &[1, 2, 3], &[3, 1, 2]],
+    );
+    session.settings.waitlists = true;
+    session
+}
+
+#[test]
+fn withdrawals_promote_down_the_chain() {
+    let session = chain();
+    let published = assignment(&[&[0], &[1], &[2]], &[]);
+    assert_eq!(waitlists(&session, 0, &published), vec![vec![1], vec![2], vec![]]);
+
+    let (promoted, withdrawal) = withdraw(&session, 0, &published, 0);
+    assert_eq!(promoted, assignment(&[&[1], &[2], &[]], &[]));
+    assert_eq!(
+        withdrawal,
+        Withdrawal {
+            user: 0,
+            promotions: vec![
+                Promotion { user: 1, from: Some(1), to: 0 },
+                Promotion { user: 2, from: Some(2), to: 1 },
+            ],
+        }
+    );
+}
+
+#[test]
+fn seats_stay_empty_without_waitlists() {
+    let mut session = chain();
+    session.settings.waitlists = false;
+    let published = assignment(&[&[0], &[1], &[2]], &[]);
+
+    let (promoted, withdrawal) = withdraw(&session, 0, &published, 0);
+    assert_eq!(promoted, assignment(&[&[], &[1], &[2]], &[]));
+    assert!(withdrawal.promotions.is_empty());
+}